@@ -1,16 +1,20 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use codex_common::CliConfigOverrides;
 use codex_common::SandboxPermissionOption;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::exec::INTERRUPT_GRACE_PERIOD;
 use codex_core::exec::StdioPolicy;
+use codex_core::exec::default_translation_shell;
 use codex_core::exec::spawn_command_under_linux_sandbox;
 use codex_core::exec::spawn_command_under_seatbelt;
 use codex_core::exec::spawn_command_under_win64_cmd;
 use codex_core::exec::spawn_command_under_win64_ps;
+use codex_core::exec::wait_child_with_interrupt;
 use codex_core::black_box::black_box::spawn_command_under_black_box;
-use codex_core::utils::child_ext::{ChildLike, BlackBoxChild};
+use codex_core::utils::child_ext::BlackBoxChild;
 use crate::BlackBoxCommand;
 use codex_core::exec::spawn_command_under_api;
 use codex_core::exec_env::create_env;
@@ -20,8 +24,22 @@ use crate::ApiCommand;
 use crate::LandlockCommand;
 use crate::SeatbeltCommand;
 use crate::exit_status::handle_exit_status;
+use tokio::sync::Notify;
 use translation::{DEFAULT_TRANSLATOR, OPERATING_SHELL, initialize};
 
+/// Notify fired on the first Ctrl-C. Installing the handler keeps SIGINT from
+/// killing this process, so the spawned child can be shut down properly.
+fn notify_on_ctrl_c() -> Arc<Notify> {
+    let ctrl_c = Arc::new(Notify::new());
+    let notify = Arc::clone(&ctrl_c);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            notify.notify_one();
+        }
+    });
+    ctrl_c
+}
+
 pub async fn run_command_under_seatbelt(
     command: SeatbeltCommand,
     codex_linux_sandbox_exe: Option<PathBuf>,
@@ -140,9 +158,10 @@ async fn run_command_under_sandbox(
     )?;
     let stdio_policy = StdioPolicy::Inherit;
     let env = create_env(&config.shell_environment_policy);
+    let ctrl_c = notify_on_ctrl_c();
 
     if DEFAULT_TRANSLATOR.get().is_none() {
-        initialize(std::env::consts::OS);
+        initialize(&default_translation_shell());
     }
     let translation_result = {
         let mut guard = DEFAULT_TRANSLATOR
@@ -150,10 +169,11 @@ async fn run_command_under_sandbox(
             .expect("translator initialized")
             .lock()
             .expect("lock translator");
+        let default_shell = default_translation_shell();
         let shell = OPERATING_SHELL
             .get()
             .map(String::as_str)
-            .unwrap_or(std::env::consts::OS);
+            .unwrap_or(&default_shell);
         guard.translate_command(&command[0], shell, "N/A", &[])
     };
 
@@ -256,7 +276,7 @@ async fn run_command_under_sandbox(
         }
     };
 
-    let status = child.wait_future().await?;
+    let status = wait_child_with_interrupt(&mut child, &ctrl_c, INTERRUPT_GRACE_PERIOD).await?;
     handle_exit_status(status);
 }
 
@@ -278,9 +298,10 @@ pub async fn run_command_under_win64_cmd(
     let cwd = std::env::current_dir()?;
     let env = create_env(&ShellEnvironmentPolicy::default());
     let stdio_policy = StdioPolicy::Inherit;
+    let ctrl_c = notify_on_ctrl_c();
 
     if DEFAULT_TRANSLATOR.get().is_none() {
-        initialize(std::env::consts::OS);
+        initialize(&default_translation_shell());
     }
     let translation_result = {
         let mut guard = DEFAULT_TRANSLATOR
@@ -288,10 +309,11 @@ pub async fn run_command_under_win64_cmd(
             .expect("translator initialized")
             .lock()
             .expect("lock translator");
+        let default_shell = default_translation_shell();
         let shell = OPERATING_SHELL
             .get()
             .map(String::as_str)
-            .unwrap_or(std::env::consts::OS);
+            .unwrap_or(&default_shell);
         guard.translate_command(&command[0], shell, "N/A", &[])
     };
 
@@ -305,7 +327,7 @@ pub async fn run_command_under_win64_cmd(
     )
     .await?;
 
-    let status = child.wait().await?;
+    let status = wait_child_with_interrupt(&mut child, &ctrl_c, INTERRUPT_GRACE_PERIOD).await?;
     handle_exit_status(status);
 }
 
@@ -316,9 +338,10 @@ pub async fn run_command_under_win64_ps(
     let cwd = std::env::current_dir()?;
     let env = create_env(&ShellEnvironmentPolicy::default());
     let stdio_policy = StdioPolicy::Inherit;
+    let ctrl_c = notify_on_ctrl_c();
 
     if DEFAULT_TRANSLATOR.get().is_none() {
-        initialize(std::env::consts::OS);
+        initialize(&default_translation_shell());
     }
     let translation_result = {
         let mut guard = DEFAULT_TRANSLATOR
@@ -326,10 +349,11 @@ pub async fn run_command_under_win64_ps(
             .expect("translator initialized")
             .lock()
             .expect("lock translator");
+        let default_shell = default_translation_shell();
         let shell = OPERATING_SHELL
             .get()
             .map(String::as_str)
-            .unwrap_or(std::env::consts::OS);
+            .unwrap_or(&default_shell);
         guard.translate_command(&command[0], shell, "N/A", &[])
     };
 
@@ -343,6 +367,6 @@ pub async fn run_command_under_win64_ps(
     )
     .await?;
 
-    let status = child.wait().await?;
+    let status = wait_child_with_interrupt(&mut child, &ctrl_c, INTERRUPT_GRACE_PERIOD).await?;
     handle_exit_status(status);
 }
\ No newline at end of file
@@ -66,6 +66,25 @@ pub struct SandboxPermissionOption {
     pub permissions: Option<Vec<SandboxPermission>>,
 }
 
+impl SandboxPermissionOption {
+    /// Union the permissions of `options`, e.g. a read-only base plus a
+    /// project folder plus the temp dirs. Writable roots accumulate and the
+    /// network is open if any option opens it. Duplicates are dropped; the
+    /// result has no permissions only if none of `options` had any.
+    pub fn merge(options: &[SandboxPermissionOption]) -> SandboxPermissionOption {
+        let mut merged: Option<Vec<SandboxPermission>> = None;
+        for permission in options.iter().filter_map(|o| o.permissions.as_ref()).flatten() {
+            let merged = merged.get_or_insert_with(Vec::new);
+            if !merged.contains(permission) {
+                merged.push(permission.clone());
+            }
+        }
+        SandboxPermissionOption {
+            permissions: merged,
+        }
+    }
+}
+
 /// Custom value-parser so we can keep the CLI surface small *and*
 /// still handle the parameterised `disk-write-folder` case.
 fn parse_sandbox_permission(raw: &str) -> std::io::Result<SandboxPermission> {
@@ -94,3 +113,50 @@ impl ValueParserFactory for SandboxPermissionOption {
         })
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use std::path::PathBuf;
+
+    use codex_core::protocol::SandboxPolicy;
+
+    use super::*;
+
+    #[test]
+    fn merge_unions_writable_roots() {
+        let folder = PathBuf::from("/opt/project");
+        let cwd = SandboxPermissionOption {
+            permissions: Some(vec![
+                SandboxPermission::DiskFullReadAccess,
+                SandboxPermission::DiskWriteCwd,
+            ]),
+        };
+        let project = SandboxPermissionOption {
+            permissions: Some(vec![
+                SandboxPermission::DiskFullReadAccess,
+                SandboxPermission::DiskWriteFolder {
+                    folder: folder.clone(),
+                },
+            ]),
+        };
+        let none = SandboxPermissionOption { permissions: None };
+
+        let merged = SandboxPermissionOption::merge(&[cwd, none, project]);
+        let permissions = merged.permissions.expect("merged permissions");
+        assert_eq!(permissions.len(), 3);
+
+        let policy = SandboxPolicy::from(permissions);
+        let work_dir = PathBuf::from("/work");
+        let roots = policy.get_writable_roots_with_cwd(&work_dir);
+        assert!(roots.contains(&work_dir));
+        assert!(roots.contains(&folder));
+        assert!(!policy.has_full_network_access());
+    }
+
+    #[test]
+    fn merge_of_nothing_has_no_permissions() {
+        let merged = SandboxPermissionOption::merge(&[SandboxPermissionOption { permissions: None }]);
+        assert!(merged.permissions.is_none());
+    }
+}
@@ -1,17 +1,76 @@
+use std::net::IpAddr;
+use std::net::SocketAddr;
+
 use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
 use tokio::time::{timeout, Duration};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rand::Rng;
 
 use crate::error::{CodexErr, Result};
+use crate::flags::{CODEX_API_ALLOW_NON_LOOPBACK, CODEX_API_BIND_ADDR};
+
+/// How long each accept attempt waits, growing from `initial` by
+/// `multiplier` per attempt up to `max`. Every wait is scaled by a random
+/// factor in `1 ± jitter` so concurrent retriers do not wake in lockstep.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackoffConfig {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: 0.1,
+        }
+    }
+}
+
+impl BackoffConfig {
+    /// The same wait for every attempt.
+    pub const fn fixed(delay: Duration) -> Self {
+        Self {
+            initial: delay,
+            max: delay,
+            multiplier: 1.0,
+            jitter: 0.0,
+        }
+    }
+
+    /// Wait for `attempt` (starting at 1) before jitter is applied.
+    pub fn base_delay(&self, attempt: usize) -> Duration {
+        let exp = self
+            .multiplier
+            .powi(i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX));
+        self.initial.mul_f64(exp.max(0.0)).min(self.max)
+    }
+
+    /// Wait for `attempt` (starting at 1), with jitter. Never exceeds `max`.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let base = self.base_delay(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return base;
+        }
+        let factor = rand::rng().random_range(1.0 - jitter..=1.0 + jitter);
+        base.mul_f64(factor).min(self.max)
+    }
+}
 
-pub async fn accept_with_retries(listener: TcpListener, tries: usize, retry: Duration) -> Result<(String, Option<TcpStream>)> {
+pub async fn accept_with_retries(listener: TcpListener, tries: usize, backoff: BackoffConfig) -> Result<(String, Option<TcpStream>)> {
     let mut attempts = 0usize;
     loop {
         if attempts >= tries {
             return Ok(("No response on the API".to_string(), None));
         }
         attempts += 1;
-        match timeout(retry, listener.accept()).await {
+        match timeout(backoff.delay(attempts), listener.accept()).await {
             Ok(Ok((mut stream, _))) => {
                 let mut compiled = Vec::new();
                 let mut buf = [0u8; 1024];
@@ -39,6 +98,60 @@ pub async fn accept_with_retries(listener: TcpListener, tries: usize, retry: Dur
     }
 }
 
+/// An API listener waiting for its handshake in a background task.
+///
+/// Dropping it aborts the task, which closes the listener and frees the port
+/// straight away instead of after the retries run out.
+pub struct ApiHandshake {
+    local_addr: SocketAddr,
+    task: JoinHandle<Result<(String, Option<TcpStream>)>>,
+}
+
+impl ApiHandshake {
+    /// Bind an ephemeral port on the interface configured by
+    /// `CODEX_API_BIND_ADDR` (localhost by default) and start accepting on it.
+    pub async fn bind(tries: usize, backoff: BackoffConfig) -> std::io::Result<Self> {
+        Self::bind_on(*CODEX_API_BIND_ADDR, *CODEX_API_ALLOW_NON_LOOPBACK, tries, backoff).await
+    }
+
+    /// Bind an ephemeral port on `addr` and start accepting on it.
+    ///
+    /// A non-loopback `addr` is refused with `PermissionDenied` unless
+    /// `allow_non_loopback` is set.
+    pub async fn bind_on(
+        addr: IpAddr,
+        allow_non_loopback: bool,
+        tries: usize,
+        backoff: BackoffConfig,
+    ) -> std::io::Result<Self> {
+        if !addr.is_loopback() && !allow_non_loopback {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("refusing to bind the API listener to non-loopback address {addr}"),
+            ));
+        }
+        let listener = TcpListener::bind((addr, 0)).await?;
+        let local_addr = listener.local_addr()?;
+        let task = tokio::spawn(accept_with_retries(listener, tries, backoff));
+        Ok(Self { local_addr, task })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Wait for the handshake result of [`accept_with_retries`].
+    pub async fn wait(mut self) -> Result<(String, Option<TcpStream>)> {
+        (&mut self.task).await?
+    }
+}
+
+impl Drop for ApiHandshake {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
 pub async fn send_payload(mut stream: TcpStream, payload: &[u8]) -> std::io::Result<Vec<u8>> {
     stream.write_all(payload).await?;
     stream.shutdown().await?;
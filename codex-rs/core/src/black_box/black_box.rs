@@ -1,13 +1,16 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use crate::config_types::ShellEnvironmentPolicy;
 use tokio::process::{Command, Child};
 use std::process::Stdio;
 use crate::protocol::SandboxPolicy;
 use crate::exec::StdioPolicy;
+use crate::exec::inherited_env_with;
+use crate::exec_env::apply_env_hook;
 use crate::utils::spawn_wrapper::wrap_spawn_result;
-use crate::utils::child_ext::{ChildExt, BlackBoxChild};
+use crate::utils::child_ext::{ChildExt, BlackBoxChild, InternalChild};
 use translation::command_translation::CommandTranslationResult;
 use anyhow::Result;
 use internal_commands::get_internal_command_function;
@@ -38,6 +41,55 @@ pub fn is_black_box_sandbox_enabled() -> bool {
     unsafe { BLACK_BOX_SANDBOX_ENABLED }
 }
 
+/// Maximum number of entries recorded by [`snapshot_cwd_mtimes`]. The black
+/// box read-only check is best effort, so very large trees are only
+/// partially covered.
+pub const READ_ONLY_SNAPSHOT_LIMIT: usize = 10_000;
+
+/// Modification times of the entries under a directory, keyed by path.
+pub type CwdSnapshot = HashMap<PathBuf, SystemTime>;
+
+/// Record the modification time of every file and directory under `cwd`,
+/// stopping after [`READ_ONLY_SNAPSHOT_LIMIT`] entries. Symlinks are not
+/// followed and unreadable entries are skipped.
+pub fn snapshot_cwd_mtimes(cwd: &Path) -> CwdSnapshot {
+    let mut snapshot = CwdSnapshot::new();
+    let mut pending = vec![cwd.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if snapshot.len() >= READ_ONLY_SNAPSHOT_LIMIT {
+                return snapshot;
+            }
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if let Ok(modified) = metadata.modified() {
+                snapshot.insert(entry.path(), modified);
+            }
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            }
+        }
+    }
+    snapshot
+}
+
+/// Compare `cwd` against a snapshot taken by [`snapshot_cwd_mtimes`] and
+/// return the sorted paths that were created, modified or removed since.
+pub fn detect_cwd_modifications(cwd: &Path, before: &CwdSnapshot) -> Vec<PathBuf> {
+    let after = snapshot_cwd_mtimes(cwd);
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, modified)| before.get(*path) != Some(*modified))
+        .map(|(path, _)| path.clone())
+        .chain(before.keys().filter(|path| !after.contains_key(*path)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
 
 pub async fn spawn_command_under_black_box(
     command: Vec<String>,
@@ -56,15 +108,23 @@ pub async fn spawn_command_under_black_box(
     };
 
     if let Some(internal_command_fn) = get_internal_command_function(&packaged_command[0]) {
-        let result = internal_command_fn(&packaged_command[1..], cwd.clone())?;
-
-        // Directly return the results of the internal command
-        return Ok((Child::from_internal_results(result.stdout, result.stderr), translation_result));
+        // Directly return the results of the internal command. A failing
+        // command becomes a child exiting with 1 and the error on stderr, the
+        // same as `exec_internal`, so the caller still sees its output.
+        let child = match internal_command_fn(&packaged_command[1..], cwd.clone()) {
+            Ok(result) => Child::from_internal_results(result.stdout, result.stderr),
+            Err(err) => BlackBoxChild::Internal(
+                InternalChild::new(String::new(), err.to_string()).with_exit_code(1),
+            ),
+        };
+        return Ok((child, translation_result));
     }
 
     let mut cmd = Command::new(&packaged_command[0]);
     cmd.args(&packaged_command[1..]);
     cmd.current_dir(cwd);
+    cmd.env_clear();
+    cmd.envs(apply_env_hook(inherited_env_with(HashMap::new())));
 
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
@@ -214,7 +214,7 @@ impl Session {
 
     fn current_threat_data(&self, params: &ExecParams) -> (String, Vec<f64>) {
         if let Some(watcher) = DEFAULT_WATCHER.get() {
-            let matrix = watcher.process_threat_matrix(vec![params.command.join(" ")]);
+            let matrix = watcher.process_threat_matrix(vec![params.command.join(" ")], None);
             let level = watcher.evaluate_matrix(&matrix);
             let info = format!("Current Threat Level: {:?}", level);
             (info, DEFAULT_CATEGORY_WEIGHTS.to_vec())
@@ -1266,6 +1266,7 @@ fn to_exec_params(params: ShellToolCallParams, sess: &Session) -> ExecParams {
         cwd: sess.resolve_path(params.workdir.clone()),
         timeout_ms: params.timeout_ms,
         env: create_env(&sess.shell_environment_policy),
+        correlation_id: None,
     }
 }
 
@@ -1396,6 +1397,7 @@ async fn handle_container_exec_with_params(
                 stderr,
                 duration,
                 translation_result,
+                ..
             } = output;
 
             sess.notify_exec_command_end(&sub_id, &call_id, &stdout, &stderr, exit_code)
@@ -1506,6 +1508,7 @@ async fn handle_sanbox_error(
                         stderr,
                         duration,
                         translation_result,
+                        ..
                     } = retry_output;
 
                     sess.notify_exec_command_end(
@@ -559,6 +559,22 @@ pub fn parse_sandbox_permission_with_base_path(
         };
     }
 
+    if let Some(host) = raw.strip_prefix("network-outbound-host=") {
+        let has_port = host
+            .rsplit_once(':')
+            .is_some_and(|(name, port)| !name.is_empty() && !port.is_empty());
+        return if !has_port {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--sandbox-permission network-outbound-host=<HOST:PORT> requires a host and a port",
+            ))
+        } else {
+            Ok(NetworkOutboundHost {
+                host: host.to_string(),
+            })
+        };
+    }
+
     match raw {
         "disk-full-read-access" => Ok(DiskFullReadAccess),
         "disk-write-platform-user-temp-folder" => Ok(DiskWritePlatformUserTempFolder),
@@ -919,4 +935,20 @@ disable_response_storage = true
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_network_outbound_host_permission() {
+        let base_path = PathBuf::from("/");
+        assert_eq!(
+            parse_sandbox_permission_with_base_path("network-outbound-host=api.openai.com:443", base_path.clone())
+                .unwrap(),
+            SandboxPermission::NetworkOutboundHost {
+                host: "api.openai.com:443".to_string(),
+            }
+        );
+        assert!(parse_sandbox_permission_with_base_path("network-outbound-host=", base_path.clone()).is_err());
+        assert!(
+            parse_sandbox_permission_with_base_path("network-outbound-host=example.com", base_path).is_err()
+        );
+    }
 }
@@ -5,6 +5,7 @@
 
 use std::collections::HashMap;
 use strum_macros::Display;
+use strum_macros::EnumString;
 use wildmatch::WildMatchPattern;
 
 use serde::Deserialize;
@@ -206,3 +207,29 @@ pub enum ReasoningSummary {
     /// Option to disable reasoning summaries.
     None,
 }
+
+/// How ANSI escape sequences in captured command output are handled.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum ColorMode {
+    /// Keep escape sequences, e.g. for a terminal UI.
+    #[default]
+    Preserve,
+    /// Remove CSI sequences (colors, cursor movement), e.g. for logs or JSON.
+    Strip,
+}
+
+/// What to do when an exec call would start another Codex process.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum NestedCodexMode {
+    /// Refuse to run it.
+    #[default]
+    Reject,
+    /// Run it under the sandbox the caller asked for. The nested Codex sees
+    /// `CODEX_NESTED=1` in its environment and can avoid stacking another
+    /// sandbox on top.
+    Flatten,
+}
@@ -1,6 +1,7 @@
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
+use std::path::PathBuf;
 use thiserror::Error;
 use tokio::task::JoinError;
 
@@ -33,6 +34,18 @@ pub enum SandboxErr {
     /// Error from linux landlock
     #[error("Landlock was not able to fully enforce all sandbox rules")]
     LandlockRestrict,
+
+    /// The wrapper script a Windows sandbox runs commands through does not exist
+    #[error("sandbox wrapper script not found: {}", .0.display())]
+    WrapperMissing(PathBuf),
+
+    /// The Windows sandbox wrapper exists but the command could not be started through it
+    #[error("sandbox wrapper {} failed to start command: {source}", .wrapper.display())]
+    WrapperFailed {
+        wrapper: PathBuf,
+        #[source]
+        source: io::Error,
+    },
 }
 
 #[derive(Error, Debug)]
@@ -58,6 +71,14 @@ pub enum CodexErr {
     #[error("interrupted (Ctrl-C)")]
     Interrupted,
 
+    /// The command line is longer than `CODEX_MAX_COMMAND_BYTES`.
+    #[error("command is {bytes} bytes long, more than the limit of {limit}")]
+    CommandTooLong { bytes: usize, limit: usize },
+
+    /// The command has more arguments than `CODEX_MAX_ARG_COUNT`.
+    #[error("command has {count} arguments, more than the limit of {limit}")]
+    TooManyArgs { count: usize, limit: usize },
+
     /// Unexpected HTTP status code.
     #[error("unexpected status {0}: {1}")]
     UnexpectedStatus(StatusCode, String),
@@ -3,13 +3,22 @@ use std::os::unix::process::ExitStatusExt;
 
 use std::collections::{HashMap, HashSet};
 use std::io;
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
 use std::process::ExitStatus;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::RwLock;
 use std::time::Duration;
 use std::time::Instant;
 
+use strum_macros::Display;
+use strum_macros::EnumString;
+use codex_execpolicy::DEFAULT_WATCHER;
+use tokio::io::AsyncBufRead;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
@@ -17,26 +26,59 @@ use tokio::io::BufReader;
 use tokio::process::Child;
 use tokio::process::Command;
 use tokio::sync::Notify;
+use tokio::sync::Semaphore;
+use tokio::sync::watch;
+use tracing::Instrument;
+use uuid::Uuid;
 
-use translation::{DEFAULT_TRANSLATOR, OPERATING_SHELL, initialize};
+use translation::{DEFAULT_TRANSLATOR, OPERATING_SHELL, initialize, scripts_dir};
 use translation::command_translation::normalize_path;
+use translation::command_translation::expand_env_vars;
+use translation::command_translation::{CommandTranslationResult, TranslatedArgv};
+use translation::TranslationStatus;
 
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::flags::CODEX_API_ALLOW_NON_LOOPBACK;
+use crate::flags::CODEX_API_BIND_ADDR;
+use crate::flags::CODEX_API_LOCAL_FALLBACK;
+use crate::flags::CODEX_BLACK_BOX_STRICT_READ_ONLY;
+use crate::flags::CODEX_COMBINED_CAPTURE;
+use crate::flags::CODEX_DISABLE_TRANSLATION;
+use crate::flags::CODEX_EXPAND_ENV_VARS;
+use crate::flags::CODEX_EXTRA_INTERPRETERS;
+use crate::flags::CODEX_MAX_ARG_COUNT;
+use crate::flags::CODEX_MAX_COMMAND_BYTES;
+use crate::flags::CODEX_MAX_CONCURRENT_EXECS;
+use crate::flags::CODEX_MAX_OUTPUT_LINE_BYTES;
+use crate::flags::CODEX_NESTED_INVOCATION;
+use crate::flags::CODEX_REQUIRE_TRANSLATION;
+use crate::flags::CODEX_OUTPUT_COLOR;
+use crate::flags::CODEX_EXEC_TRANSCRIPT;
+use crate::exec_env::apply_env_hook;
+use crate::exec_events::{ExecEvent, ExecEventSender, emit};
+use crate::exec_transcript::{ExecTranscriptEntry, append_exec_transcript};
+use crate::preflight::threat_level_or_blocked;
+use crate::protocol::NetworkRules;
 use crate::protocol::SandboxPolicy;
 use crate::safety::detect_windows_shell;
 
-use crate::api::{accept_with_retries, send_payload};
+use crate::api::{ApiHandshake, BackoffConfig, send_payload};
 pub use crate::black_box::black_box::spawn_command_under_black_box;
+use crate::black_box::black_box::{CwdSnapshot, detect_cwd_modifications, snapshot_cwd_mtimes};
 pub use crate::black_box::black_box::{
     CODEX_BLACK_BOX_SANDBOX_STATE,
     enable_black_box_sandbox,
     disable_black_box_sandbox,
+    is_black_box_sandbox_enabled,
 };
+use crate::config_types::ColorMode;
+use crate::config_types::NestedCodexMode;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::util::apply_color_mode;
 use crate::utils::spawn_wrapper::wrap_spawn_result;
-use internal_commands::is_internal_command;
+use internal_commands::{is_internal_command, run_internal_command};
 
 
 // Maximum we send for each stream, which is either:
@@ -47,6 +89,18 @@ const MAX_STREAM_OUTPUT_LINES: usize = 256;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 
+/// Default timeout for calls brokered through the API sandbox, which pay for
+/// a handshake and a round trip on top of the command itself.
+const API_DEFAULT_TIMEOUT_MS: u64 = 60_000;
+
+/// Default timeout for internal commands, which run in-process.
+const INTERNAL_DEFAULT_TIMEOUT_MS: u64 = 1_000;
+
+/// How long a stream may stay silent after the child exits before we stop
+/// reading it. A grandchild that inherited the pipe can otherwise keep it open
+/// indefinitely.
+const STREAM_DRAIN_GRACE: Duration = Duration::from_millis(500);
+
 
 // Hardcode these since it does not seem worth including the libc crate just
 // for these.
@@ -76,6 +130,20 @@ const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
 /// attributes, so this may change in the future.
 pub const CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR: &str = "CODEX_SANDBOX_NETWORK_DISABLED";
 
+/// Set to `1` in the environment of every child Codex spawns, so a Codex
+/// process started from a tool call can tell it is nested.
+pub const CODEX_NESTED_ENV_VAR: &str = "CODEX_NESTED";
+
+/// File names of the Codex binaries [`is_nested_codex_invocation`] looks for.
+/// `codex-linux-sandbox` is left out because Codex runs it itself.
+const CODEX_BINARY_NAMES: &[&str] = &[
+    "codex",
+    "codex-exec",
+    "codex-execpolicy",
+    "codex-tui",
+    "codex-mcp-server",
+];
+
 /// Integer constants representing sandbox states.
 pub const CODEX_API_SANDBOX_STATE: i32 = 1;
 pub const CODEX_WINDOWS_CMD_SANDBOX_STATE: i32 = 2;
@@ -111,6 +179,28 @@ pub fn determine_sandbox_state() -> i32 {
     }
 }
 
+/// Translation key for the shell this process runs under, used when
+/// `OPERATING_SHELL` has not been set.
+///
+/// On Windows the shell is detected, since cmd, PowerShell and WSL each have
+/// their own translation column; elsewhere this is [`std::env::consts::OS`].
+pub fn default_translation_shell() -> String {
+    if cfg!(target_os = "windows") {
+        windows_translation_shell(&detect_windows_shell()).to_string()
+    } else {
+        std::env::consts::OS.to_string()
+    }
+}
+
+/// Translation key for a Windows shell as named by `detect_windows_shell`.
+pub fn windows_translation_shell(detected: &str) -> &'static str {
+    match detected {
+        "powershell" => "powershell",
+        "wsl" | "bash for windows" => "wsl",
+        _ => "windows",
+    }
+}
+
 /// Functions to toggle API and Black Box states.
 pub fn enable_api_sandbox() {
     unsafe {
@@ -124,6 +214,61 @@ pub fn disable_api_sandbox() {
     }
 }
 
+pub fn is_api_sandbox_enabled() -> bool {
+    unsafe { API_SANDBOX_ENABLED }
+}
+
+/// The API and black box sandbox toggles at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxStateSnapshot {
+    pub api_enabled: bool,
+    pub black_box_enabled: bool,
+}
+
+/// Capture the current API and black box sandbox toggles.
+pub fn snapshot_sandbox_state() -> SandboxStateSnapshot {
+    SandboxStateSnapshot {
+        api_enabled: is_api_sandbox_enabled(),
+        black_box_enabled: is_black_box_sandbox_enabled(),
+    }
+}
+
+/// Set the API and black box sandbox toggles back to `snapshot`.
+pub fn restore_sandbox_state(snapshot: SandboxStateSnapshot) {
+    if snapshot.api_enabled {
+        enable_api_sandbox();
+    } else {
+        disable_api_sandbox();
+    }
+    if snapshot.black_box_enabled {
+        enable_black_box_sandbox();
+    } else {
+        disable_black_box_sandbox();
+    }
+}
+
+/// Restores the sandbox toggles that were active when it was created once it
+/// goes out of scope.
+#[derive(Debug)]
+#[must_use = "the previous sandbox state is restored when the guard is dropped"]
+pub struct SandboxStateGuard {
+    snapshot: SandboxStateSnapshot,
+}
+
+impl SandboxStateGuard {
+    pub fn capture() -> Self {
+        Self {
+            snapshot: snapshot_sandbox_state(),
+        }
+    }
+}
+
+impl Drop for SandboxStateGuard {
+    fn drop(&mut self) {
+        restore_sandbox_state(self.snapshot);
+    }
+}
+
 
 #[derive(Debug, Clone)]
 pub struct ExecParams {
@@ -131,9 +276,81 @@ pub struct ExecParams {
     pub cwd: PathBuf,
     pub timeout_ms: Option<u64>,
     pub env: HashMap<String, String>,
+    /// Tags the call's output, transcript entry and tracing span. When `None`
+    /// [`process_exec_tool_call`] generates one.
+    pub correlation_id: Option<String>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+impl ExecParams {
+    pub fn builder() -> ExecParamsBuilder {
+        ExecParamsBuilder::default()
+    }
+}
+
+/// Builds [`ExecParams`]. Unset fields default to the current directory, an
+/// empty environment, no timeout and a generated correlation id.
+#[derive(Debug, Clone, Default)]
+pub struct ExecParamsBuilder {
+    command: Vec<String>,
+    cwd: Option<PathBuf>,
+    timeout_ms: Option<u64>,
+    env: HashMap<String, String>,
+    correlation_id: Option<String>,
+}
+
+impl ExecParamsBuilder {
+    pub fn command(mut self, command: Vec<String>) -> Self {
+        self.command = command;
+        self
+    }
+
+    pub fn cwd(mut self, cwd: PathBuf) -> Self {
+        self.cwd = Some(cwd);
+        self
+    }
+
+    pub fn timeout_ms(mut self, timeout_ms: Option<u64>) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Fails with [`io::ErrorKind::InvalidInput`] when no command was given,
+    /// the same error [`process_exec_tool_call`] reports for it.
+    pub fn build(self) -> Result<ExecParams> {
+        if self.command.is_empty() {
+            return Err(CodexErr::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "command args are empty",
+            )));
+        }
+        let cwd = match self.cwd {
+            Some(cwd) => cwd,
+            None => std::env::current_dir()?,
+        };
+        Ok(ExecParams {
+            command: self.command,
+            cwd,
+            timeout_ms: self.timeout_ms,
+            env: self.env,
+            correlation_id: self.correlation_id,
+        })
+    }
+}
+
+/// Where a command runs. Displays and parses as a stable lowercase name such
+/// as `seatbelt`, `linux-seccomp` or `black-box`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
 pub enum SandboxType {
     None,
 
@@ -141,6 +358,7 @@ pub enum SandboxType {
     BlackBox,
 
     /// Only available on macOS.
+    #[strum(serialize = "seatbelt")]
     MacosSeatbelt,
 
     /// Only available on Linux.
@@ -154,6 +372,352 @@ pub enum SandboxType {
 
     /// API sandbox agnostic to platform.
     Api,
+
+    /// Codex internal command, handled in-process without spawning anything.
+    Internal,
+}
+
+/// Sandbox types that can be requested on this platform, in declaration
+/// order. `None`, `BlackBox` and `Api` are always listed; the OS specific
+/// sandboxes only where they can run. `Internal` is never listed because it
+/// is chosen automatically for internal commands.
+pub fn available_sandbox_types() -> Vec<SandboxType> {
+    let mut types = vec![SandboxType::None, SandboxType::BlackBox];
+    if cfg!(target_os = "macos") {
+        types.push(SandboxType::MacosSeatbelt);
+    }
+    if cfg!(target_os = "linux") {
+        types.push(SandboxType::LinuxSeccomp);
+    }
+    if cfg!(windows) {
+        types.extend([SandboxType::Win64Cmd, SandboxType::Win64Ps]);
+    }
+    types.push(SandboxType::Api);
+    types
+}
+
+/// What commands can do under the currently detected sandbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxCapabilities {
+    pub can_write_cwd: bool,
+    pub can_read_all: bool,
+    pub network_allowed: bool,
+    pub sandbox_type: SandboxType,
+}
+
+/// The sandbox that [`determine_sandbox_state`] currently selects.
+pub fn detected_sandbox_type() -> SandboxType {
+    match determine_sandbox_state() {
+        CODEX_API_SANDBOX_STATE => SandboxType::Api,
+        CODEX_WINDOWS_CMD_SANDBOX_STATE => SandboxType::Win64Cmd,
+        CODEX_WINDOWS_PS_SANDBOX_STATE => SandboxType::Win64Ps,
+        CODEX_LINUX_SHELL_SANDBOX_STATE => SandboxType::LinuxSeccomp,
+        CODEX_MACOS_SANDBOX_STATE => SandboxType::MacosSeatbelt,
+        _ => SandboxType::BlackBox,
+    }
+}
+
+/// Combine the detected sandbox with `policy` to describe what a command run
+/// from the current directory may do.
+///
+/// The black box does not enforce the policy (writes are only reported after
+/// the fact), so it is reported as unrestricted.
+pub fn current_sandbox_capabilities(policy: &SandboxPolicy) -> SandboxCapabilities {
+    let sandbox_type = detected_sandbox_type();
+    if sandbox_type == SandboxType::BlackBox {
+        return SandboxCapabilities {
+            can_write_cwd: true,
+            can_read_all: true,
+            network_allowed: true,
+            sandbox_type,
+        };
+    }
+    let can_write_cwd = match std::env::current_dir() {
+        Ok(cwd) => policy.has_cwd_write_access(&cwd),
+        Err(_) => policy.has_full_disk_write_access(),
+    };
+    SandboxCapabilities {
+        can_write_cwd,
+        can_read_all: policy.has_full_disk_read_access(),
+        network_allowed: policy.has_full_network_access(),
+        sandbox_type,
+    }
+}
+
+/// Limits how many [`process_exec_tool_call`]s run at once. Calls beyond the
+/// limit wait for a permit before spawning anything.
+static EXEC_PERMITS: LazyLock<Arc<Semaphore>> =
+    LazyLock::new(|| Arc::new(Semaphore::new((*CODEX_MAX_CONCURRENT_EXECS).max(1))));
+
+/// Number of exec calls that could start right now without waiting.
+pub fn available_exec_permits() -> usize {
+    EXEC_PERMITS.available_permits()
+}
+
+/// Per-call interrupt of every running [`process_exec_tool_call`], by
+/// correlation id.
+static RUNNING_EXECS: LazyLock<Mutex<HashMap<String, Arc<Notify>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Interrupt the running exec call with `correlation_id`, as if its `ctrl_c`
+/// had fired. Only that call is affected, even when several calls share one
+/// `ctrl_c`. Returns `false` when no such call is running.
+pub fn cancel_exec(correlation_id: &str) -> bool {
+    match RUNNING_EXECS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(correlation_id)
+    {
+        Some(ctrl_c) => {
+            // `notify_one` keeps the wakeup even when the call has not started
+            // waiting on its child yet.
+            ctrl_c.notify_one();
+            true
+        }
+        None => false,
+    }
+}
+
+/// How long [`wait_child_with_interrupt`] lets an interrupted child exit on
+/// its own before killing it.
+pub const INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Wait for `child` to exit. Once `ctrl_c` fires, the child gets `grace` to
+/// finish by itself (a terminal Ctrl-C also reaches a child sharing its
+/// process group) and is then killed.
+pub async fn wait_child_with_interrupt<C: ChildLike>(
+    child: &mut C,
+    ctrl_c: &Notify,
+    grace: Duration,
+) -> io::Result<ExitStatus> {
+    tokio::select! {
+        status = child.wait_future() => return status,
+        _ = ctrl_c.notified() => {}
+    }
+    if let Ok(status) = tokio::time::timeout(grace, child.wait_future()).await {
+        return status;
+    }
+    child.start_kill()?;
+    child.wait_future().await
+}
+
+/// Keeps a call in [`RUNNING_EXECS`] until it finishes.
+///
+/// The call gets its own `interrupt`, fired by the caller's `ctrl_c` or by
+/// [`cancel_exec`]. Registering the caller's `ctrl_c` directly would not work:
+/// a session shares one `ctrl_c` between all of its calls, so `notify_one` on
+/// it could wake any of them.
+struct RunningExec {
+    correlation_id: String,
+    interrupt: Arc<Notify>,
+    forward_ctrl_c: tokio::task::JoinHandle<()>,
+}
+
+impl RunningExec {
+    fn register(correlation_id: &str, ctrl_c: &Arc<Notify>) -> Self {
+        let interrupt = Arc::new(Notify::new());
+        let forward_ctrl_c = tokio::spawn({
+            let ctrl_c = Arc::clone(ctrl_c);
+            let interrupt = Arc::clone(&interrupt);
+            async move {
+                ctrl_c.notified().await;
+                interrupt.notify_one();
+            }
+        });
+        RUNNING_EXECS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(correlation_id.to_string(), Arc::clone(&interrupt));
+        Self {
+            correlation_id: correlation_id.to_string(),
+            interrupt,
+            forward_ctrl_c,
+        }
+    }
+
+    /// What the call waits on instead of the caller's `ctrl_c`.
+    fn interrupt(&self) -> Arc<Notify> {
+        Arc::clone(&self.interrupt)
+    }
+}
+
+impl Drop for RunningExec {
+    fn drop(&mut self) {
+        self.forward_ctrl_c.abort();
+        let mut running = RUNNING_EXECS.lock().unwrap_or_else(PoisonError::into_inner);
+        // A later call may have reused the id; leave its entry alone.
+        if running
+            .get(&self.correlation_id)
+            .is_some_and(|interrupt| Arc::ptr_eq(interrupt, &self.interrupt))
+        {
+            running.remove(&self.correlation_id);
+        }
+    }
+}
+
+/// Decide which sandbox `command` actually runs under.
+///
+/// Internal commands never reach the OS and always resolve to
+/// [`SandboxType::Internal`]. Otherwise an enabled black box overrides the
+/// requested sandbox.
+pub fn resolve_sandbox_type(command: &[String], requested: SandboxType) -> SandboxType {
+    if command.first().is_some_and(|program| is_internal_command(program)) {
+        SandboxType::Internal
+    } else if CODEX_BLACK_BOX_SANDBOX_STATE == determine_sandbox_state() {
+        SandboxType::BlackBox
+    } else {
+        requested
+    }
+}
+
+/// Timeout applied by [`process_exec_tool_call`] when the call does not set
+/// [`ExecParams::timeout_ms`].
+pub fn default_timeout_for(sandbox_type: SandboxType) -> Duration {
+    let millis = match sandbox_type {
+        SandboxType::Api => API_DEFAULT_TIMEOUT_MS,
+        SandboxType::Internal => INTERNAL_DEFAULT_TIMEOUT_MS,
+        SandboxType::None
+        | SandboxType::BlackBox
+        | SandboxType::MacosSeatbelt
+        | SandboxType::LinuxSeccomp
+        | SandboxType::Win64Cmd
+        | SandboxType::Win64Ps => DEFAULT_TIMEOUT_MS,
+    };
+    Duration::from_millis(millis)
+}
+
+/// Reject `command` if it exceeds `CODEX_MAX_ARG_COUNT` arguments or
+/// `CODEX_MAX_COMMAND_BYTES` bytes.
+pub fn check_command_limits(command: &[String]) -> Result<()> {
+    check_command_limits_with(command, *CODEX_MAX_COMMAND_BYTES, *CODEX_MAX_ARG_COUNT)
+}
+
+/// [`check_command_limits`] with explicit limits. Each argument counts its
+/// length plus one byte for its terminator.
+pub fn check_command_limits_with(command: &[String], max_bytes: usize, max_args: usize) -> Result<()> {
+    if command.len() > max_args {
+        return Err(CodexErr::TooManyArgs {
+            count: command.len(),
+            limit: max_args,
+        });
+    }
+    let bytes: usize = command.iter().map(|arg| arg.len() + 1).sum();
+    if bytes > max_bytes {
+        return Err(CodexErr::CommandTooLong {
+            bytes,
+            limit: max_bytes,
+        });
+    }
+    Ok(())
+}
+
+/// Whether `command` starts Codex itself: its program is one of the Codex
+/// binaries, by file name (optionally with `.exe`), or the executable of the
+/// current process. For `sh -c`-style calls the program of every command in
+/// the script is checked too.
+pub fn is_nested_codex_invocation(command: &[String]) -> bool {
+    let Some(program) = command.first() else {
+        return false;
+    };
+    if is_codex_program(program) {
+        return true;
+    }
+    match command {
+        [shell, flag, script, ..] if is_posix_shell(shell) && flag == "-c" => {
+            script_programs(script).any(is_codex_program)
+        }
+        _ => false,
+    }
+}
+
+fn is_codex_program(program: &str) -> bool {
+    let path = Path::new(program);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    if CODEX_BINARY_NAMES.contains(&name) {
+        return true;
+    }
+    match (path.canonicalize(), std::env::current_exe().and_then(|exe| exe.canonicalize())) {
+        (Ok(program), Ok(current)) => program == current,
+        _ => false,
+    }
+}
+
+fn is_posix_shell(program: &str) -> bool {
+    let name = Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    matches!(name.as_str(), "sh" | "bash" | "zsh" | "dash" | "ksh")
+}
+
+/// First word of every command in a shell script, split on the usual
+/// separators, with surrounding quotes removed.
+fn script_programs(script: &str) -> impl Iterator<Item = &str> {
+    script
+        .split(['\n', ';', '&', '|', '(', ')', '`'])
+        .filter_map(|segment| segment.split_whitespace().find(|word| !word.contains('=')))
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
+}
+
+/// Expand environment variable references in every argument of `command`
+/// against `env`, using the variable syntax of `shell`.
+pub fn expand_command_env(command: &[String], env: &HashMap<String, String>, shell: &str) -> Vec<String> {
+    command
+        .iter()
+        .map(|arg| expand_env_vars(arg, env, shell))
+        .collect()
+}
+
+/// Settings of [`process_exec_tool_call_with_config`] that callers usually
+/// leave to the environment. [`ExecConfig::default`] reads each one from its
+/// `CODEX_*` flag.
+#[derive(Debug, Clone)]
+pub struct ExecConfig {
+    /// Skip the command translator and run the command exactly as given
+    /// (`CODEX_DISABLE_TRANSLATION`).
+    pub disable_translation: bool,
+    /// Refuse commands the translator has no mapping for when the operating
+    /// shell is not the host's default shell (`CODEX_REQUIRE_TRANSLATION`).
+    pub require_translation: bool,
+    /// What to do with a command that starts Codex itself
+    /// (`CODEX_NESTED_INVOCATION`).
+    pub nested_invocation: NestedCodexMode,
+    /// Also capture stdout and stderr merged in arrival order
+    /// (`CODEX_COMBINED_CAPTURE`).
+    pub combined_capture: bool,
+    /// Whether ANSI color codes in captured output are kept
+    /// (`CODEX_OUTPUT_COLOR`).
+    pub output_color: ColorMode,
+    /// Permits the call waits for before spawning anything. The default is
+    /// shared by every call and sized by `CODEX_MAX_CONCURRENT_EXECS`.
+    pub permits: Arc<Semaphore>,
+    /// How [`SandboxType::Api`] commands reach their broker.
+    pub api: ApiSandboxConfig,
+    /// File each call appends a transcript line to (`CODEX_EXEC_TRANSCRIPT`).
+    pub transcript: Option<PathBuf>,
+    /// Where output rendered through `exec_output_template.md` is written.
+    /// Defaults to `templated_output.txt` next to the template.
+    pub templated_output: PathBuf,
+}
+
+impl Default for ExecConfig {
+    fn default() -> Self {
+        Self {
+            disable_translation: *CODEX_DISABLE_TRANSLATION,
+            require_translation: *CODEX_REQUIRE_TRANSLATION,
+            nested_invocation: *CODEX_NESTED_INVOCATION,
+            combined_capture: *CODEX_COMBINED_CAPTURE,
+            output_color: *CODEX_OUTPUT_COLOR,
+            permits: Arc::clone(&EXEC_PERMITS),
+            api: ApiSandboxConfig::default(),
+            transcript: CODEX_EXEC_TRANSCRIPT.map(PathBuf::from),
+            templated_output: scripts_dir().join("templated_output.txt"),
+        }
+    }
 }
 
 pub async fn process_exec_tool_call(
@@ -164,198 +728,416 @@ pub async fn process_exec_tool_call(
     codex_linux_sandbox_exe: &Option<PathBuf>,
     threat_info: &str,
     threat_weights: &[f64],
+) -> Result<ExecToolCallOutput> {
+    process_exec_tool_call_with_events(
+        params,
+        sandbox_type,
+        ctrl_c,
+        sandbox_policy,
+        codex_linux_sandbox_exe,
+        threat_info,
+        threat_weights,
+        None,
+        &ExecConfig::default(),
+    )
+    .await
+}
+
+/// [`process_exec_tool_call`] with settings chosen by the caller instead of
+/// read from the environment.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_exec_tool_call_with_config(
+    params: ExecParams,
+    sandbox_type: SandboxType,
+    ctrl_c: Arc<Notify>,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    threat_info: &str,
+    threat_weights: &[f64],
+    config: &ExecConfig,
+) -> Result<ExecToolCallOutput> {
+    exec_tool_call(
+        params,
+        sandbox_type,
+        ctrl_c,
+        sandbox_policy,
+        codex_linux_sandbox_exe,
+        threat_info,
+        threat_weights,
+        None,
+        config,
+    )
+    .await
+}
+
+/// [`process_exec_tool_call`] that also reports progress on `events` while
+/// the command runs. Output chunks are streamed for spawned processes;
+/// internal commands and the API broker only report start and finish.
+///
+/// The channel is awaited on, so the receiver must keep draining it.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_exec_tool_call_with_events(
+    params: ExecParams,
+    sandbox_type: SandboxType,
+    ctrl_c: Arc<Notify>,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    threat_info: &str,
+    threat_weights: &[f64],
+    events: Option<ExecEventSender>,
+    config: &ExecConfig,
+) -> Result<ExecToolCallOutput> {
+    exec_tool_call(
+        params,
+        sandbox_type,
+        ctrl_c,
+        sandbox_policy,
+        codex_linux_sandbox_exe,
+        threat_info,
+        threat_weights,
+        events,
+        config,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn exec_tool_call(
+    params: ExecParams,
+    sandbox_type: SandboxType,
+    ctrl_c: Arc<Notify>,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    threat_info: &str,
+    threat_weights: &[f64],
+    events: Option<ExecEventSender>,
+    config: &ExecConfig,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
 
-    if DEFAULT_TRANSLATOR.get().is_none() {
-        initialize(std::env::consts::OS);
+    if params.command.is_empty() {
+        return Err(CodexErr::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "command args are empty",
+        )));
+    }
+
+    let nested = is_nested_codex_invocation(&params.command);
+    if nested && config.nested_invocation == NestedCodexMode::Reject {
+        return Err(CodexErr::Io(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "refusing to run nested Codex invocation `{}`; set CODEX_NESTED_INVOCATION=flatten to allow it",
+                params.command[0]
+            ),
+        )));
     }
-    let translation_result = {
+
+    let original_argv = params.command.clone();
+    let correlation_id = params
+        .correlation_id
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let span = tracing::info_span!("exec", correlation_id = %correlation_id);
+    let running = RunningExec::register(&correlation_id, &ctrl_c);
+    let ctrl_c = running.interrupt();
+
+    let default_shell = default_translation_shell();
+    let shell = OPERATING_SHELL
+        .get()
+        .map(String::as_str)
+        .unwrap_or(&default_shell);
+    let translated = if config.disable_translation {
+        TranslatedArgv {
+            argv: params.command.clone(),
+            result: CommandTranslationResult::disabled(&params.command[0]),
+        }
+    } else {
+        if DEFAULT_TRANSLATOR.get().is_none() {
+            initialize(&default_shell);
+        }
         let mut guard = DEFAULT_TRANSLATOR
             .get()
             .expect("translator initialized")
             .lock()
             .expect("lock translator");
-        let shell = OPERATING_SHELL
-            .get()
-            .map(String::as_str)
-            .unwrap_or(std::env::consts::OS);
-        guard.translate_command(&params.command[0], shell, threat_info, threat_weights)
+        guard.translate_argv(&params.command, &params.cwd, shell, threat_info, threat_weights)
     };
+    let translation_result = translated.result;
+    if let Some(resolved_os) = translation_result
+        .resolved_os
+        .as_deref()
+        .filter(|resolved_os| *resolved_os != shell)
+    {
+        tracing::debug!(
+            "translated `{}` for {shell} using the {resolved_os} mapping",
+            translation_result.original_command
+        );
+    }
 
     let mut params = params;
-    let translated_or_original = translation_result.translated_command.as_ref().map(|s| s.clone()).unwrap_or_else(|| params.command[0].clone());
-    params.command[0] = translated_or_original;
-    
-    let mut sandbox_type = sandbox_type;
-    if is_internal_command(params.command[0].as_str()) || CODEX_BLACK_BOX_SANDBOX_STATE == determine_sandbox_state() {
-        sandbox_type = SandboxType::BlackBox;
-    }
-
-    let raw_output_result = match sandbox_type {
-        SandboxType::None => exec(params, sandbox_policy, ctrl_c, Some(translation_result.clone())).await,
-        SandboxType::BlackBox => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env: _,
-            } = params;
-            let (child, translation_result) = spawn_command_under_black_box(
-                command,
-                sandbox_policy.clone(),
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                ShellEnvironmentPolicy::default(),
-                Some(translation_result.clone()),
-            )
-            .await?;
-            consume_truncated_output(child, ctrl_c, timeout_ms, translation_result).await
-        }
-        SandboxType::MacosSeatbelt => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-            let (child, translation_result) = spawn_command_under_seatbelt(
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-                Some(translation_result.clone()),
-            )
-            .await?;
-            consume_truncated_output(child, ctrl_c, timeout_ms, translation_result).await
-        }
-        SandboxType::LinuxSeccomp => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-
-            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
-                .as_ref()
-                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
-            let (child, translation_result) = spawn_command_under_linux_sandbox(
-                codex_linux_sandbox_exe,
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-                Some(translation_result.clone()),
-            )
-            .await?;
-
-            consume_truncated_output(child, ctrl_c, timeout_ms, translation_result).await
-        }
-        SandboxType::Win64Cmd => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-
-            let (child, translation_result) = spawn_command_under_win64_cmd(
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-                Some(translation_result.clone()),
-            )
-            .await?;
-
-            consume_truncated_output(child, ctrl_c, timeout_ms, translation_result).await
-        }
-        SandboxType::Win64Ps => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-
-            let (child, translation_result) = spawn_command_under_win64_ps(
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-                Some(translation_result.clone()),
-            )
-            .await?;
-
-            consume_truncated_output(child, ctrl_c, timeout_ms, translation_result).await
-        }
-        SandboxType::Api => {
-            let ExecParams {
-                command,
-                cwd,
-                timeout_ms,
-                env,
-            } = params;
-
-            spawn_command_under_api(
-                command,
-                sandbox_policy,
-                cwd,
-                StdioPolicy::RedirectForShellTool,
-                env,
-                timeout_ms,
-                Some(translation_result.clone()),
-            )
+    params.command = translated.argv;
+    if *CODEX_EXPAND_ENV_VARS {
+        params.command = expand_command_env(&params.command, &params.env, shell);
+    }
+
+    // A permitted nested Codex still runs under the sandbox the caller asked
+    // for; it only learns it is nested through `CODEX_NESTED_ENV_VAR`.
+    let sandbox_type = resolve_sandbox_type(&params.command, sandbox_type);
+    if params.timeout_ms.is_none() {
+        params.timeout_ms = Some(default_timeout_for(sandbox_type).as_millis() as u64);
+    }
+    if config.require_translation
+        && sandbox_type != SandboxType::Internal
+        && translation_result.status == TranslationStatus::NoMapping
+        && shell != default_shell
+    {
+        return Err(CodexErr::Io(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "no translation of `{}` for {shell}; refusing to run it because CODEX_REQUIRE_TRANSLATION is set",
+                translation_result.original_command
+            ),
+        )));
+    }
+
+    check_command_limits(&params.command)?;
+
+    let translated_argv = params.command.clone();
+    let result = async {
+        let _permit = config
+            .permits
+            .acquire()
             .await
-        }
-    };
-    let duration = start.elapsed();
-    match raw_output_result {
-        Ok(raw_output) => {
-            let stdout = String::from_utf8_lossy(&raw_output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&raw_output.stderr).to_string();
-
-            #[cfg(target_family = "unix")]
-            match raw_output.exit_status.signal() {
-                Some(TIMEOUT_CODE) => return Err(CodexErr::Sandbox(SandboxErr::Timeout)),
-                Some(signal) => {
-                    return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
+            .map_err(|e| CodexErr::Io(io::Error::other(e)))?;
+        emit(&events, ExecEvent::started(&params.command, sandbox_type)).await;
+
+        let raw_output_result = match sandbox_type {
+            SandboxType::None => {
+                exec(params, sandbox_policy, ctrl_c, Some(translation_result.clone()), events.clone(), config).await
+            }
+            SandboxType::Internal => Ok(exec_internal(params, Some(translation_result.clone()))),
+            SandboxType::BlackBox => {
+                let ExecParams {
+                    command,
+                    cwd,
+                    timeout_ms,
+                    env: _,
+                    correlation_id: _,
+                } = params;
+                // The black box cannot stop writes, but it can notice them when
+                // the policy says the cwd should be read-only.
+                let read_only_snapshot =
+                    (!sandbox_policy.has_cwd_write_access(&cwd)).then(|| snapshot_cwd_mtimes(&cwd));
+                let (child, translation_result) = spawn_command_under_black_box(
+                    command,
+                    sandbox_policy.clone(),
+                    cwd.clone(),
+                    StdioPolicy::RedirectForShellTool,
+                    ShellEnvironmentPolicy::default(),
+                    Some(translation_result.clone()),
+                )
+                .await?;
+                let output =
+                    consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, events.clone(), config)
+                        .await;
+                match (output, read_only_snapshot) {
+                    (Ok(output), Some(snapshot)) => report_read_only_violations(output, &cwd, &snapshot),
+                    (output, _) => output,
                 }
-                None => {}
             }
-
-            let exit_code = raw_output.exit_status.code().unwrap_or(-1);
-
-            // NOTE(ragona): This is much less restrictive than the previous check. If we exec
-            // a command, and it returns anything other than success, we assume that it may have
-            // been a sandboxing error and allow the user to retry. (The user of course may choose
-            // not to retry, or in a non-interactive mode, would automatically reject the approval.)
-            if exit_code != 0 &&
-                !(matches!(sandbox_type, SandboxType::None | SandboxType::BlackBox))
-            {
-                return Err(CodexErr::Sandbox(SandboxErr::Denied(
-                    exit_code, stdout, stderr,
-                )));
+            SandboxType::MacosSeatbelt => {
+                let ExecParams {
+                    command,
+                    cwd,
+                    timeout_ms,
+                    env,
+                    correlation_id: _,
+                } = params;
+                let (child, translation_result) = spawn_command_under_seatbelt(
+                    command,
+                    sandbox_policy,
+                    cwd,
+                    StdioPolicy::RedirectForShellTool,
+                    env,
+                    Some(translation_result.clone()),
+                )
+                .await?;
+                consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, events.clone(), config)
+                    .await
             }
+            SandboxType::LinuxSeccomp => {
+                let ExecParams {
+                    command,
+                    cwd,
+                    timeout_ms,
+                    env,
+                    correlation_id: _,
+                } = params;
+
+                let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                    .as_ref()
+                    .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+                let (child, translation_result) = spawn_command_under_linux_sandbox(
+                    codex_linux_sandbox_exe,
+                    command,
+                    sandbox_policy,
+                    cwd,
+                    StdioPolicy::RedirectForShellTool,
+                    env,
+                    Some(translation_result.clone()),
+                )
+                .await?;
+
+                consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, events.clone(), config)
+                    .await
+            }
+            SandboxType::Win64Cmd => {
+                let ExecParams {
+                    command,
+                    cwd,
+                    timeout_ms,
+                    env,
+                    correlation_id: _,
+                } = params;
+
+                let (child, translation_result) = spawn_command_under_win64_cmd(
+                    command,
+                    sandbox_policy,
+                    cwd,
+                    StdioPolicy::RedirectForShellTool,
+                    env,
+                    Some(translation_result.clone()),
+                )
+                .await?;
+
+                consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, events.clone(), config)
+                    .await
+            }
+            SandboxType::Win64Ps => {
+                let ExecParams {
+                    command,
+                    cwd,
+                    timeout_ms,
+                    env,
+                    correlation_id: _,
+                } = params;
+
+                let (child, translation_result) = spawn_command_under_win64_ps(
+                    command,
+                    sandbox_policy,
+                    cwd,
+                    StdioPolicy::RedirectForShellTool,
+                    env,
+                    Some(translation_result.clone()),
+                )
+                .await?;
+
+                consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, events.clone(), config)
+                    .await
+            }
+            SandboxType::Api => {
+                let ExecParams {
+                    command,
+                    cwd,
+                    timeout_ms,
+                    env,
+                    correlation_id: _,
+                } = params;
+
+                spawn_command_under_api_with(
+                    command,
+                    sandbox_policy,
+                    cwd,
+                    StdioPolicy::RedirectForShellTool,
+                    env,
+                    timeout_ms,
+                    Some(translation_result.clone()),
+                    config,
+                )
+                .await
+            }
+        };
+        let duration = start.elapsed();
+        match raw_output_result {
+            Ok(raw_output) => {
+                emit(
+                    &events,
+                    ExecEvent::Finished {
+                        code: raw_output.exit_status.code().unwrap_or(-1),
+                        duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                    },
+                )
+                .await;
+                let stdout = String::from_utf8_lossy(&raw_output.stdout).to_string();
+                let stderr = String::from_utf8_lossy(&raw_output.stderr).to_string();
+
+                #[cfg(target_family = "unix")]
+                match raw_output.exit_status.signal() {
+                    Some(TIMEOUT_CODE) => return Err(CodexErr::Sandbox(SandboxErr::Timeout)),
+                    Some(signal) => {
+                        return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
+                    }
+                    None => {}
+                }
 
-            Ok(ExecToolCallOutput {
-                exit_code,
-                stdout,
-                stderr,
-                duration,
-                translation_result: raw_output.translation_result,
-            })
+                let exit_code = raw_output.exit_status.code().unwrap_or(-1);
+
+                // NOTE(ragona): This is much less restrictive than the previous check. If we exec
+                // a command, and it returns anything other than success, we assume that it may have
+                // been a sandboxing error and allow the user to retry. (The user of course may choose
+                // not to retry, or in a non-interactive mode, would automatically reject the approval.)
+                if exit_code != 0 &&
+                    !(matches!(sandbox_type, SandboxType::None | SandboxType::BlackBox | SandboxType::Internal))
+                {
+                    return Err(CodexErr::Sandbox(SandboxErr::Denied(
+                        exit_code, stdout, stderr,
+                    )));
+                }
+
+                Ok(ExecToolCallOutput {
+                    exit_code,
+                    stdout,
+                    stderr,
+                    duration,
+                    translation_result: raw_output.translation_result,
+                    correlation_id: correlation_id.clone(),
+                    combined: raw_output.combined,
+                    stdout_truncated: raw_output.stdout_truncated,
+                    stderr_truncated: raw_output.stderr_truncated,
+                    total_stdout_bytes: raw_output.total_stdout_bytes,
+                    total_stderr_bytes: raw_output.total_stderr_bytes,
+                })
+            }
+            Err(err) => {
+                tracing::error!("exec error: {err}");
+                Err(err)
+            }
         }
-        Err(err) => {
-            tracing::error!("exec error: {err}");
-            Err(err)
+    }
+    .instrument(span)
+    .await;
+
+    if let Some(path) = &config.transcript {
+        let threat_level = DEFAULT_WATCHER
+            .get()
+            .map(|watcher| threat_level_or_blocked(watcher, &original_argv, None));
+        let entry = ExecTranscriptEntry::new(
+            original_argv,
+            translated_argv,
+            sandbox_type,
+            &result,
+            start.elapsed(),
+            threat_level,
+            threat_info,
+            &correlation_id,
+        );
+        if let Err(e) = append_exec_transcript(path, &entry) {
+            tracing::warn!("failed to write exec transcript {}: {e}", path.display());
         }
     }
+
+    result
 }
 
 pub async fn spawn_command_under_seatbelt(
@@ -417,6 +1199,34 @@ where
     )
 }
 
+/// Wrapper script the Windows sandboxes run commands through, resolved
+/// against [`scripts_dir`]. `None` for sandbox types that use no wrapper.
+pub fn windows_sandbox_wrapper_path(sandbox_type: SandboxType) -> Option<PathBuf> {
+    let script = match sandbox_type {
+        SandboxType::Win64Cmd => "win64_cmd_restricted.bat",
+        SandboxType::Win64Ps => "win64_ps_restricted.ps1",
+        _ => return None,
+    };
+    Some(scripts_dir().join(script))
+}
+
+/// The wrapper for `sandbox_type`, or [`SandboxErr::WrapperMissing`] when it
+/// is not on disk, so a broken install is not reported as a failed command.
+#[cfg(windows)]
+fn existing_windows_sandbox_wrapper(sandbox_type: SandboxType) -> Result<PathBuf> {
+    let path = windows_sandbox_wrapper_path(sandbox_type).ok_or_else(|| {
+        CodexErr::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{sandbox_type} is not a Windows sandbox"),
+        ))
+    })?;
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(CodexErr::Sandbox(SandboxErr::WrapperMissing(path)))
+    }
+}
+
 /// Windows CMD shell sandbox.
 pub async fn spawn_command_under_win64_cmd(
     command: Vec<String>,
@@ -425,25 +1235,20 @@ pub async fn spawn_command_under_win64_cmd(
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
     translation_result: Option<translation::command_translation::CommandTranslationResult>,
-) -> std::io::Result<(Child, Option<translation::command_translation::CommandTranslationResult>)> {
+) -> Result<(Child, Option<translation::command_translation::CommandTranslationResult>)> {
     #[cfg(windows)]
     {
         // Use a helper script to restrict command execution. This wrapper denies
         // attempts to change directories above the current working directory and
         // runs the command under a restricted user account.
-        let manifest_dir = env!("CARGO_MANIFEST_DIR");
-        let parent = Path::new(&manifest_dir).parent();
-        
-        let batch_script_path = format!("{}/{}",
-            parent.unwrap().to_str().unwrap(),
-            "scripts/win64_cmd_restricted.bat"
-        );
-        let normalized_path = normalize_path(&batch_script_path);
+        let batch_script_path = existing_windows_sandbox_wrapper(SandboxType::Win64Cmd)?;
+        let normalized_path = normalize_path(&batch_script_path.to_string_lossy());
         let mut cmd = Command::new("cmd.exe");
         cmd.arg("/C").arg(normalized_path);
         cmd.args(&command);
         cmd.current_dir(&cwd);
-        cmd.envs(&env);
+        cmd.env_clear();
+        cmd.envs(apply_env_hook(inherited_env_with(env)));
 
         match stdio_policy {
             StdioPolicy::RedirectForShellTool => {
@@ -457,15 +1262,20 @@ pub async fn spawn_command_under_win64_cmd(
             }
         }
 
-        wrap_spawn_result(cmd.spawn(), translation_result)
+        wrap_spawn_result(cmd.spawn(), translation_result).map_err(|source| {
+            CodexErr::Sandbox(SandboxErr::WrapperFailed {
+                wrapper: batch_script_path,
+                source,
+            })
+        })
     }
 
     #[cfg(not(windows))]
     {
-        Err(std::io::Error::new(
+        Err(CodexErr::Io(std::io::Error::new(
             std::io::ErrorKind::Unsupported,
             "Windows CMD shell sandbox is only available on Windows targets",
-        ))
+        )))
     }
 }
 
@@ -477,19 +1287,17 @@ pub async fn spawn_command_under_win64_ps(
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
     translation_result: Option<translation::command_translation::CommandTranslationResult>,
-) -> std::io::Result<(Child, Option<translation::command_translation::CommandTranslationResult>)> {
+) -> Result<(Child, Option<translation::command_translation::CommandTranslationResult>)> {
     #[cfg(windows)]
     {
-        let powershell_script_path = concat!(
-            env!("CARGO_MANIFEST_DIR"),
-            "src/scripts/win64_ps_restricted.ps1"
-        );
-        let normalized_path = normalize_path(&powershell_script_path);
+        let powershell_script_path = existing_windows_sandbox_wrapper(SandboxType::Win64Ps)?;
+        let normalized_path = normalize_path(&powershell_script_path.to_string_lossy());
         let mut cmd = Command::new("powershell.exe");
         cmd.arg("-File").arg(normalized_path);
         cmd.args(&command);
         cmd.current_dir(&cwd);
-        cmd.envs(&env);
+        cmd.env_clear();
+        cmd.envs(apply_env_hook(inherited_env_with(env)));
 
         match stdio_policy {
             StdioPolicy::RedirectForShellTool => {
@@ -503,20 +1311,77 @@ pub async fn spawn_command_under_win64_ps(
             }
         }
 
-        wrap_spawn_result(cmd.spawn(), translation_result)
+        wrap_spawn_result(cmd.spawn(), translation_result).map_err(|source| {
+            CodexErr::Sandbox(SandboxErr::WrapperFailed {
+                wrapper: powershell_script_path,
+                source,
+            })
+        })
     }
 
     #[cfg(not(windows))]
     {
-        Err(std::io::Error::new(
+        Err(CodexErr::Io(std::io::Error::new(
             std::io::ErrorKind::Unsupported,
             "Windows PowerShell sandbox is only available on Windows targets",
-        ))
+        )))
+    }
+}
+
+/// How [`spawn_command_under_api_with`] talks to the API broker.
+/// [`ApiSandboxConfig::default`] reads each setting from its `CODEX_*` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiSandboxConfig {
+    /// Interface the broker handshake listens on (`CODEX_API_BIND_ADDR`).
+    pub bind_addr: IpAddr,
+    /// Allow `bind_addr` to be a non-loopback address
+    /// (`CODEX_API_ALLOW_NON_LOOPBACK`).
+    pub allow_non_loopback: bool,
+    /// Run a non-interpreter command locally when no broker answers the
+    /// handshake, instead of echoing the command line back as stdout
+    /// (`CODEX_API_LOCAL_FALLBACK`).
+    pub local_fallback: bool,
+}
+
+impl Default for ApiSandboxConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: *CODEX_API_BIND_ADDR,
+            allow_non_loopback: *CODEX_API_ALLOW_NON_LOOPBACK,
+            local_fallback: *CODEX_API_LOCAL_FALLBACK,
+        }
     }
 }
 
 /// API sandbox agnostic to platform.
+///
+/// Non-interpreter commands that no broker picks up are run locally when
+/// `CODEX_API_LOCAL_FALLBACK` is set, see [`spawn_command_under_api_with`].
 pub async fn spawn_command_under_api(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: PathBuf,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+    timeout_ms: Option<u64>,
+    translation_result: Option<translation::command_translation::CommandTranslationResult>,
+) -> Result<RawExecToolCallOutput> {
+    spawn_command_under_api_with(
+        command,
+        sandbox_policy,
+        cwd,
+        stdio_policy,
+        env,
+        timeout_ms,
+        translation_result,
+        &ExecConfig::default(),
+    )
+    .await
+}
+
+/// [`spawn_command_under_api`] with the broker and output settings in `config`.
+#[allow(clippy::too_many_arguments)]
+pub async fn spawn_command_under_api_with(
     command: Vec<String>,
     _sandbox_policy: &SandboxPolicy,
     cwd: PathBuf,
@@ -524,28 +1389,45 @@ pub async fn spawn_command_under_api(
     env: HashMap<String, String>,
     timeout_ms: Option<u64>,
     translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    config: &ExecConfig,
 ) -> Result<RawExecToolCallOutput> {
-    use tokio::net::TcpListener;
     use tokio::sync::Notify;
 
-    let listener = TcpListener::bind("127.0.0.1:0").await?; // Bind to an ephemeral port
-    let local_addr = listener.local_addr()?; // Get the bound address
+    let api = &config.api;
+    let program = command.first().map(String::as_str).unwrap_or("");
+    let interpreter = is_interpreter(program);
+
+    // Non-interpreters are handed to the API rather than spawned, so check up
+    // front that there is something to hand over.
+    if !interpreter && !program_exists(program, &cwd, &env) {
+        tracing::warn!("API command not found: {}", program);
+        return Ok(RawExecToolCallOutput::uncapped(
+            synthetic_exit_status(API_SPAWN_FAILURE),
+            Vec::new(),
+            format!("Program not found: {}", command.join(" ")).into_bytes(),
+            translation_result,
+        ));
+    }
 
-    tracing::info!("API listener bound to: {}", local_addr);
+    const HANDSHAKE_TRIES: usize = 3;
 
-    let mut status_factor = 1i32;
+    // Dropping `handshake` on any early return below releases the port.
+    let handshake = ApiHandshake::bind_on(
+        api.bind_addr,
+        api.allow_non_loopback,
+        HANDSHAKE_TRIES,
+        BackoffConfig::default(),
+    )
+    .await?;
 
-    const HANDSHAKE_TRIES: usize = 3;
-    const HANDSHAKE_RETRY: Duration = Duration::from_secs(1);
+    tracing::info!("API listener bound to: {}", handshake.local_addr());
 
-    let handshake_handle = tokio::spawn(async move {
-        accept_with_retries(listener, HANDSHAKE_TRIES, HANDSHAKE_RETRY).await
-    });
+    let mut status_factor = 1i32;
 
     let command_line = command.join(" ");
 
-    if !is_interpreter(command.get(0).map(String::as_str).unwrap_or("")) {
-        let (handshake_message, stream_opt) = handshake_handle.await??;
+    if !interpreter {
+        let (handshake_message, stream_opt) = handshake.wait().await?;
         if let Some(stream) = stream_opt {
             let response = match send_payload(stream, command_line.as_bytes()).await {
                 Ok(resp) => String::from_utf8_lossy(&resp).to_string(),
@@ -560,69 +1442,68 @@ pub async fn spawn_command_under_api(
                 output.push_str("\n");
                 output.push_str(&response);
             } else {
-                output.push_str("\n");
+                output.push_str("\nwould run: ");
                 output.push_str(&command_line);
             }
             let code = if status_factor == 1 { 0 } else { status_factor };
-            return Ok(RawExecToolCallOutput {
-                exit_status: synthetic_exit_status(code),
-                stdout: output.into_bytes(),
-                stderr: Vec::new(),
+            return Ok(RawExecToolCallOutput::uncapped(
+                synthetic_exit_status(code),
+                output.into_bytes(),
+                Vec::new(),
                 translation_result,
-            });
+            ));
+        } else if api.local_fallback {
+            tracing::info!("no API broker connected; running `{}` locally", command_line);
+            let child = match local_api_command(&command, cwd, stdio_policy, env).spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    tracing::warn!("Failed to spawn command: {}", e);
+                    return Ok(RawExecToolCallOutput::uncapped(
+                        synthetic_exit_status(API_SPAWN_FAILURE),
+                        Vec::new(),
+                        format!("Program not found: {}", command_line).into_bytes(),
+                        translation_result,
+                    ));
+                }
+            };
+            return consume_truncated_output(child, Arc::new(Notify::new()), timeout_ms, translation_result, config)
+                .await;
         } else {
             status_factor *= API_HANDSHAKE_FAILURE;
-            let output = format!("{}\n{}", handshake_message, command_line);
-            return Ok(RawExecToolCallOutput {
-                exit_status: synthetic_exit_status(status_factor),
-                stdout: output.into_bytes(),
-                stderr: Vec::new(),
+            let output = format!("{}\nwould run: {}", handshake_message, command_line);
+            return Ok(RawExecToolCallOutput::uncapped(
+                synthetic_exit_status(status_factor),
+                output.into_bytes(),
+                Vec::new(),
                 translation_result,
-            });
+            ));
 
         }
     }
 
-    let mut cmd = Command::new(&command[0]);
-    cmd.args(&command[1..]);
-
-    cmd.current_dir(cwd);
-    cmd.envs(env);
-
-    match stdio_policy {
-        StdioPolicy::RedirectForShellTool => {
-            cmd.stdin(Stdio::null());
-            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-        }
-        StdioPolicy::Inherit => {
-            cmd.stdin(Stdio::inherit())
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit());
-        }
-    }
-
-
+    let mut cmd = local_api_command(&command, cwd, stdio_policy, env);
     let child = match cmd.spawn() {
         Ok(child) => child,
         Err(e) => {
             status_factor *= API_SPAWN_FAILURE;
             tracing::warn!("Failed to spawn command: {}", e);
-            return Ok(RawExecToolCallOutput {
-                exit_status: synthetic_exit_status(status_factor),
-                stdout: Vec::new(),
-                stderr: format!("Program not found: {}", command_line).into_bytes(),
+            return Ok(RawExecToolCallOutput::uncapped(
+                synthetic_exit_status(status_factor),
+                Vec::new(),
+                format!("Program not found: {}", command_line).into_bytes(),
                 translation_result,
-            });
+            ));
         }
     };
 
     let output_handle = {
         let ctrl_c = Arc::new(Notify::new());
         let tr = translation_result.clone();
-        tokio::spawn(async move { consume_truncated_output(child, ctrl_c, timeout_ms, tr).await })
+        let config = config.clone();
+        tokio::spawn(async move { consume_truncated_output(child, ctrl_c, timeout_ms, tr, &config).await })
     };
 
-    let (handshake_message, _stream) = handshake_handle.await??;
+    let (handshake_message, _stream) = handshake.wait().await?;
     if handshake_message == "No response on the API" {
         status_factor *= API_HANDSHAKE_FAILURE;
     }
@@ -643,20 +1524,116 @@ pub async fn spawn_command_under_api(
     Ok(output)
 }
 
-fn is_interpreter(program: &str) -> bool {
+/// The process [`spawn_command_under_api_with`] starts for commands it runs
+/// itself rather than handing to the broker.
+fn local_api_command(
+    command: &[String],
+    cwd: PathBuf,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+) -> Command {
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..]);
+
+    cmd.current_dir(cwd);
+    cmd.env_clear();
+    cmd.envs(apply_env_hook(inherited_env_with(env)));
+
+    match stdio_policy {
+        StdioPolicy::RedirectForShellTool => {
+            cmd.stdin(Stdio::null());
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        StdioPolicy::Inherit => {
+            cmd.stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit());
+        }
+    }
+    cmd
+}
+
+/// Whether `program` names an existing file, either directly (when it
+/// contains a path separator, relative to `cwd`) or somewhere on the `PATH`
+/// from `env`, falling back to our own `PATH`.
+fn program_exists(program: &str, cwd: &Path, env: &HashMap<String, String>) -> bool {
+    if program.is_empty() {
+        return false;
+    }
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 || candidate.is_absolute() {
+        return cwd.join(candidate).is_file();
+    }
+    let path = env
+        .get("PATH")
+        .cloned()
+        .or_else(|| std::env::var("PATH").ok())
+        .unwrap_or_default();
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat"] } else { &[""] };
+    std::env::split_paths(&path).any(|dir| {
+        extensions
+            .iter()
+            .any(|ext| dir.join(format!("{program}{ext}")).is_file())
+    })
+}
+
+/// Interpreters known without any configuration.
+const DEFAULT_INTERPRETERS: &[&str] = &[
+    "sh", "bash", "zsh", "cmd", "powershell", "pwsh", "python", "python3", "node", "perl",
+];
+
+/// Programs [`spawn_command_under_api`] runs locally instead of handing to
+/// the API broker. Seeded from [`DEFAULT_INTERPRETERS`] and
+/// `CODEX_EXTRA_INTERPRETERS`; names are stored lowercase.
+static INTERPRETERS: LazyLock<RwLock<HashSet<String>>> = LazyLock::new(|| {
+    let interpreters = interpreters_with_extra(CODEX_EXTRA_INTERPRETERS.unwrap_or_default());
+    RwLock::new(interpreters.into_iter().collect())
+});
+
+/// [`DEFAULT_INTERPRETERS`] plus the comma-separated names in `extra`, in
+/// the form `CODEX_EXTRA_INTERPRETERS` takes. Names are lowercased.
+pub fn interpreters_with_extra(extra: &str) -> Vec<String> {
+    DEFAULT_INTERPRETERS
+        .iter()
+        .copied()
+        .chain(extra.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+/// Replace the interpreter set, including the defaults.
+pub fn set_interpreters(interpreters: Vec<String>) {
+    *INTERPRETERS.write().unwrap_or_else(PoisonError::into_inner) = interpreters
+        .iter()
+        .map(|name| name.to_ascii_lowercase())
+        .collect();
+}
+
+/// Treat `program` as an interpreter from now on.
+pub fn add_interpreter(program: &str) {
+    INTERPRETERS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(program.to_ascii_lowercase());
+}
+
+/// Whether the file name of `program` is a configured interpreter.
+pub fn is_interpreter(program: &str) -> bool {
     let name = program
         .rsplit_once('/')
         .map(|(_, n)| n)
         .unwrap_or(program)
         .to_ascii_lowercase();
-    matches!(
-        name.as_str(),
-        "sh" | "bash" | "zsh" | "cmd" | "powershell" | "pwsh" | "python" | "python3" | "node" | "perl"
-    )
+    INTERPRETERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains(&name)
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
-fn create_linux_sandbox_command_args(
+pub fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
@@ -691,6 +1668,12 @@ fn create_linux_sandbox_command_args(
 
     if sandbox_policy.has_full_network_access() {
         linux_cmd.extend(["-s", "network-full-access"].map(String::from));
+    } else {
+        // Accepted by the helper but not enforced: seccomp cannot tell hosts
+        // apart, so outbound access stays blocked.
+        for host in sandbox_policy.network_allowlist() {
+            linux_cmd.extend(["-s".to_string(), format!("network-outbound-host={host}")]);
+        }
     }
 
     // Separator so that command arguments starting with `-` are not parsed as
@@ -703,7 +1686,9 @@ fn create_linux_sandbox_command_args(
     linux_cmd
 }
 
-fn create_seatbelt_command_args(
+/// Converts the sandbox policy into the `sandbox-exec` arguments, including
+/// the generated Seatbelt policy.
+pub fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
@@ -746,11 +1731,21 @@ fn create_seatbelt_command_args(
     };
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
-    let network_policy = if sandbox_policy.has_full_network_access() {
-        "(allow network-outbound)\n(allow network-inbound)\n(allow system-socket)"
-    } else {
-        ""
-    };
+    let network_rules = sandbox_policy.network_rules();
+    let network_policy = [
+        (network_rules.outbound, "(allow network-outbound)"),
+        (network_rules.inbound, "(allow network-inbound)"),
+        (network_rules.socket, "(allow system-socket)"),
+    ]
+    .into_iter()
+    .filter_map(|(allowed, rule)| allowed.then_some(rule))
+    .map(str::to_string)
+    .chain(seatbelt_allowlist_rules(
+        &network_rules,
+        &sandbox_policy.network_allowlist(),
+    ))
+    .collect::<Vec<_>>()
+    .join("\n");
 
     let full_policy = format!(
         "{MACOS_SEATBELT_BASE_POLICY}\n{file_read_policy}\n{file_write_policy}\n{network_policy}"
@@ -762,12 +1757,145 @@ fn create_seatbelt_command_args(
     seatbelt_args
 }
 
+/// Outbound rules scoped to `allowlist`, plus the socket and DNS access
+/// needed to reach those hosts. Empty when outbound access is already
+/// unrestricted or nothing is allowlisted.
+///
+/// Seatbelt cannot scope by host name: its `remote ip` filter only accepts
+/// `*` or `localhost` as the host, so `api.openai.com:443` allows outbound
+/// traffic to port 443 of any host. An entry must therefore name a port;
+/// one without a port, or a wildcard port on a host other than `localhost`,
+/// would become `*:*` and open all outbound traffic, so it is skipped. So are
+/// entries whose port is not a number, since a single malformed rule makes
+/// `sandbox-exec` reject the whole profile.
+fn seatbelt_allowlist_rules(network_rules: &NetworkRules, allowlist: &[String]) -> Vec<String> {
+    if network_rules.outbound {
+        return Vec::new();
+    }
+    let mut remotes: Vec<String> = allowlist
+        .iter()
+        .filter_map(|entry| {
+            let Some((host, port)) = entry.rsplit_once(':') else {
+                tracing::warn!("skipping network allowlist entry `{entry}`: no port");
+                return None;
+            };
+            let host = if host == "localhost" { "localhost" } else { "*" };
+            if port == "*" && host == "*" {
+                tracing::warn!("skipping network allowlist entry `{entry}`: would allow every host and port");
+                return None;
+            }
+            if port != "*" && port.parse::<u16>().is_err() {
+                tracing::warn!("skipping network allowlist entry `{entry}`: invalid port");
+                return None;
+            }
+            Some(format!("{host}:{port}"))
+        })
+        .collect();
+    if remotes.is_empty() {
+        return Vec::new();
+    }
+    remotes.sort();
+    remotes.dedup();
+    let mut rules = Vec::new();
+    if !network_rules.socket {
+        rules.push("(allow system-socket)".to_string());
+    }
+    rules.push(
+        r#"(allow network-outbound (remote unix-socket (path-literal "/private/var/run/mDNSResponder")))"#
+            .to_string(),
+    );
+    rules.extend(
+        remotes
+            .into_iter()
+            .map(|remote| format!("(allow network-outbound (remote ip \"{remote}\"))")),
+    );
+    rules
+}
+
 #[derive(Debug)]
 pub struct RawExecToolCallOutput {
     pub exit_status: ExitStatus,
     pub stdout: Vec<u8>,
     pub stderr: Vec<u8>,
     pub translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub total_stdout_bytes: usize,
+    pub total_stderr_bytes: usize,
+    /// Both streams in arrival order; only captured when
+    /// `CODEX_COMBINED_CAPTURE` is set.
+    pub combined: Option<Vec<OutputChunk>>,
+}
+
+/// Which stream an [`OutputChunk`] was read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A run of bytes read from one stream without output from the other in
+/// between.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputChunk {
+    pub stream: OutputStream,
+    pub bytes: Vec<u8>,
+}
+
+/// Collects stdout and stderr chunks, kept within the stream caps, in the
+/// order they are read. Consecutive reads from the same stream are merged.
+#[derive(Clone, Debug, Default)]
+struct CombinedCapture {
+    chunks: Arc<std::sync::Mutex<Vec<OutputChunk>>>,
+}
+
+impl CombinedCapture {
+    fn record(&self, stream: OutputStream, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        let mut chunks = match self.chunks.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match chunks.last_mut() {
+            Some(last) if last.stream == stream => last.bytes.extend_from_slice(bytes),
+            _ => chunks.push(OutputChunk {
+                stream,
+                bytes: bytes.to_vec(),
+            }),
+        }
+    }
+
+    fn into_chunks(self) -> Vec<OutputChunk> {
+        let mut chunks = match self.chunks.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        std::mem::take(&mut *chunks)
+    }
+}
+
+impl RawExecToolCallOutput {
+    /// Output that was produced in full, without passing through the stream caps.
+    fn uncapped(
+        exit_status: ExitStatus,
+        stdout: Vec<u8>,
+        stderr: Vec<u8>,
+        translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    ) -> Self {
+        Self {
+            exit_status,
+            total_stdout_bytes: stdout.len(),
+            total_stderr_bytes: stderr.len(),
+            stdout,
+            stderr,
+            translation_result,
+            stdout_truncated: false,
+            stderr_truncated: false,
+            combined: None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -777,6 +1905,18 @@ pub struct ExecToolCallOutput {
     pub stderr: String,
     pub duration: Duration,
     pub translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    /// The call's [`ExecParams::correlation_id`], or the one generated for it.
+    pub correlation_id: String,
+    /// Both streams in arrival order when `CODEX_COMBINED_CAPTURE` is set.
+    pub combined: Option<Vec<OutputChunk>>,
+    /// Whether `stdout` was cut short by the per-stream byte or line cap.
+    pub stdout_truncated: bool,
+    /// Whether `stderr` was cut short by the stream caps.
+    pub stderr_truncated: bool,
+    /// Number of bytes the command wrote to stdout, before truncation.
+    pub total_stdout_bytes: usize,
+    /// Number of bytes the command wrote to stderr, before truncation.
+    pub total_stderr_bytes: usize,
 }
 
 async fn exec(
@@ -785,10 +1925,13 @@ async fn exec(
         cwd,
         timeout_ms,
         env,
+        correlation_id: _,
     }: ExecParams,
     sandbox_policy: &SandboxPolicy,
     ctrl_c: Arc<Notify>,
     translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    events: Option<ExecEventSender>,
+    config: &ExecConfig,
 ) -> Result<RawExecToolCallOutput> {
     let (program, args) = command.split_first().ok_or_else(|| {
         CodexErr::Io(io::Error::new(
@@ -807,7 +1950,54 @@ async fn exec(
         env,
     )
     .await?;
-    consume_truncated_output(child, ctrl_c, timeout_ms, translation_result).await
+    consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, events, config).await
+}
+
+/// Append a warning to `output.stderr` for every path under `cwd` that changed
+/// since `snapshot`, or fail the call when
+/// [`CODEX_BLACK_BOX_STRICT_READ_ONLY`] is set.
+fn report_read_only_violations(
+    mut output: RawExecToolCallOutput,
+    cwd: &Path,
+    snapshot: &CwdSnapshot,
+) -> Result<RawExecToolCallOutput> {
+    let modified = detect_cwd_modifications(cwd, snapshot);
+    if modified.is_empty() {
+        return Ok(output);
+    }
+
+    let mut warning = String::from("\nwarning: command modified the read-only working directory:\n");
+    for path in &modified {
+        warning.push_str(&format!("  {}\n", path.display()));
+    }
+    output.stderr.extend_from_slice(warning.as_bytes());
+
+    if *CODEX_BLACK_BOX_STRICT_READ_ONLY {
+        return Err(CodexErr::Sandbox(SandboxErr::Denied(
+            output.exit_status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )));
+    }
+    Ok(output)
+}
+
+/// Run an internal command in-process. Failures are reported through a
+/// non-zero exit status and stderr, like a real process would.
+fn exec_internal(
+    ExecParams { command, cwd, .. }: ExecParams,
+    translation_result: Option<translation::command_translation::CommandTranslationResult>,
+) -> RawExecToolCallOutput {
+    let (code, stdout, stderr) = match run_internal_command(&command, cwd) {
+        Ok(output) => (0, output.stdout, output.stderr),
+        Err(err) => (1, String::new(), err.to_string()),
+    };
+    RawExecToolCallOutput::uncapped(
+        synthetic_exit_status(code),
+        stdout.into_bytes(),
+        stderr.into_bytes(),
+        translation_result,
+    )
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -816,6 +2006,14 @@ pub enum StdioPolicy {
     Inherit,
 }
 
+/// The environment a child that inherits ours would see with `env` set on
+/// top, for spawn paths that do not start from an empty environment.
+pub(crate) fn inherited_env_with(env: HashMap<String, String>) -> HashMap<String, String> {
+    let mut inherited: HashMap<String, String> = std::env::vars().collect();
+    inherited.extend(env);
+    inherited
+}
+
 /// Spawns the appropriate child process for the ExecParams and SandboxPolicy,
 /// ensuring the args and environment variables used to create the `Command`
 /// (and `Child`) honor the configuration.
@@ -838,7 +2036,7 @@ async fn spawn_child_async(
     cmd.args(args);
     cmd.current_dir(cwd);
     cmd.env_clear();
-    cmd.envs(env);
+    cmd.envs(apply_env_hook(env));
 
     if !sandbox_policy.has_full_network_access() {
         cmd.env(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR, "1");
@@ -870,10 +2068,24 @@ async fn spawn_child_async(
 use crate::utils::child_ext::ChildLike;
 
 pub(crate) async fn consume_truncated_output<C: ChildLike>(
+    child: C,
+    ctrl_c: Arc<Notify>,
+    timeout_ms: Option<u64>,
+    translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    config: &ExecConfig,
+) -> Result<RawExecToolCallOutput> {
+    consume_truncated_output_with_events(child, ctrl_c, timeout_ms, translation_result, None, config).await
+}
+
+/// [`consume_truncated_output`] that also sends each kept chunk of output to
+/// `events` as it is read, capturing it as `config` asks.
+async fn consume_truncated_output_with_events<C: ChildLike>(
     mut child: C,
     ctrl_c: Arc<Notify>,
     timeout_ms: Option<u64>,
     translation_result: Option<translation::command_translation::CommandTranslationResult>,
+    events: Option<ExecEventSender>,
+    config: &ExecConfig,
 ) -> Result<RawExecToolCallOutput> {
     let stdout_reader = child.take_stdout().ok_or_else(|| {
         CodexErr::Io(io::Error::other(
@@ -886,33 +2098,66 @@ pub(crate) async fn consume_truncated_output<C: ChildLike>(
         ))
     })?;
 
-    let stdout_handle: tokio::task::JoinHandle<std::result::Result<Vec<u8>, std::io::Error>> = tokio::spawn(async move {
+    let (exit_tx, exit_rx) = watch::channel(false);
+    let combined = config.combined_capture.then(CombinedCapture::default);
+
+    let stdout_exited = exit_rx.clone();
+    let stdout_combined = combined.clone();
+    let stdout_events = events.clone();
+    let stdout_handle: tokio::task::JoinHandle<std::result::Result<(Vec<u8>, usize), std::io::Error>> = tokio::spawn(async move {
         let mut reader = BufReader::new(stdout_reader);
         let mut buffer = Vec::new();
         let mut result = Vec::new();
-        while let Ok(bytes_read) = reader.read_until(b'\n', &mut buffer).await {
+        let mut total_bytes = 0;
+        let mut remaining_bytes = MAX_STREAM_OUTPUT;
+        let mut remaining_lines = MAX_STREAM_OUTPUT_LINES;
+        loop {
+            // Once the child has exited, give up on a pipe that stays silent
+            // for the grace period and keep whatever partial line we have.
+            let (bytes_read, abandoned) = tokio::select! {
+                read = read_line_capped(&mut reader, &mut buffer, *CODEX_MAX_OUTPUT_LINE_BYTES) => (read.unwrap_or(0), false),
+                _ = grace_after_exit(stdout_exited.clone()) => (buffer.len(), true),
+            };
             if bytes_read == 0 {
                 break;
             }
+            total_bytes += bytes_read;
 
-            // Append the read buffer to the result
-            result.extend_from_slice(&buffer);
+            // Append the read buffer to the result, up to the stream caps
+            let kept = take_within_caps(&buffer, &mut remaining_bytes, &mut remaining_lines);
+            result.extend_from_slice(&buffer[..kept]);
+            if let Some(combined) = &stdout_combined {
+                combined.record(OutputStream::Stdout, &buffer[..kept]);
+            }
+            if kept > 0 {
+                let chunk = String::from_utf8_lossy(&buffer[..kept]).to_string();
+                emit(&stdout_events, ExecEvent::Stdout { chunk }).await;
+            }
 
             // Simulate token-based delay
-            let token_estimate = buffer.len() / 4; // Approximate tokens by dividing char count
+            let token_estimate = kept / 4; // Approximate tokens by dividing char count
             let delay_per_token = Duration::from_millis(50); // Example: 50ms per token
             let total_delay = delay_per_token * token_estimate as u32;
             tokio::time::sleep(total_delay).await;
 
             buffer.clear(); // Clear the buffer for the next read
+            if abandoned {
+                break;
+            }
         }
-        Ok(result) // Return the accumulated result
+        if total_bytes > result.len() {
+            trim_partial_utf8(&mut result);
+        }
+        Ok((result, total_bytes)) // Return the accumulated result
     });
 
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         MAX_STREAM_OUTPUT,
         MAX_STREAM_OUTPUT_LINES,
+        exit_rx,
+        combined.clone(),
+        events,
     ));
 
     let interrupted = ctrl_c.notified();
@@ -934,12 +2179,26 @@ pub(crate) async fn consume_truncated_output<C: ChildLike>(
         }
     };
 
-    let mut stdout = stdout_handle.await??;
-    let stderr = stderr_handle.await??;
+    let _ = exit_tx.send(true);
+
+    let (stdout, total_stdout_bytes) = stdout_handle.await??;
+    let (stderr, total_stderr_bytes) = stderr_handle.await??;
+    let stdout_truncated = total_stdout_bytes > stdout.len();
+    let stderr_truncated = total_stderr_bytes > stderr.len();
+    let mut stdout = apply_color_mode(config.output_color, stdout);
+    let stderr = apply_color_mode(config.output_color, stderr);
+    let combined = combined.map(|combined| {
+        combined
+            .into_chunks()
+            .into_iter()
+            .map(|chunk| OutputChunk {
+                bytes: apply_color_mode(config.output_color, chunk.bytes),
+                ..chunk
+            })
+            .collect()
+    });
 
-    let manifest_dir = env!("CARGO_MANIFEST_DIR");
-    let parent = Path::new(&manifest_dir).parent().unwrap();
-    let template_path = parent.join("scripts/exec_output_template.md");
+    let template_path = scripts_dir().join("exec_output_template.md");
 
     if template_path.exists() {
         let template = std::fs::read_to_string(&template_path)?;
@@ -953,8 +2212,7 @@ pub(crate) async fn consume_truncated_output<C: ChildLike>(
                 .replace("{{informational_output}}", &translation.informational_output);
         }
 
-        let reader_path = template_path.with_file_name("templated_output.txt");
-        std::fs::write(&reader_path, &templated_content)?;
+        std::fs::write(&config.templated_output, &templated_content)?;
         stdout = templated_content.into_bytes();
     }
 
@@ -963,45 +2221,140 @@ pub(crate) async fn consume_truncated_output<C: ChildLike>(
         stdout,
         stderr,
         translation_result,
+        stdout_truncated,
+        stderr_truncated,
+        total_stdout_bytes,
+        total_stderr_bytes,
+        combined,
     })
 }
 
+/// Return how many leading bytes of `chunk` fit in the remaining byte and line
+/// budget, and charge them against it.
+fn take_within_caps(chunk: &[u8], remaining_bytes: &mut usize, remaining_lines: &mut usize) -> usize {
+    let mut copy_len = 0;
+    for &b in chunk {
+        if *remaining_bytes == 0 || *remaining_lines == 0 {
+            break;
+        }
+        copy_len += 1;
+        *remaining_bytes -= 1;
+        if b == b'\n' {
+            *remaining_lines -= 1;
+        }
+    }
+    copy_len
+}
+
+/// Drop a multibyte UTF-8 sequence left incomplete at the end of `buf` by
+/// truncation, so lossy decoding does not turn it into U+FFFD. Bytes that
+/// could not start a valid sequence are left alone.
+fn trim_partial_utf8(buf: &mut Vec<u8>) {
+    let tail_start = buf.len().saturating_sub(3);
+    let Some(start) = (tail_start..buf.len()).rev().find(|&i| buf[i] & 0xC0 != 0x80) else {
+        return;
+    };
+    let needed = match buf[start] {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return,
+    };
+    if buf.len() - start < needed {
+        buf.truncate(start);
+    }
+}
+
+/// Like `read_until(b'\n', buf)`, but stops once `buf` holds `max_len` bytes
+/// so a single line without a newline is returned in `max_len`-sized pieces.
+/// Returns the number of bytes appended; `0` means EOF.
+///
+/// Cancel safe: bytes are moved into `buf` as soon as they are consumed from
+/// `reader`, so dropping the future loses nothing.
+pub async fn read_line_capped<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_len: usize,
+) -> io::Result<usize> {
+    let max_len = max_len.max(1);
+    let start = buf.len();
+    while buf.len() < max_len {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        let room = max_len - buf.len();
+        let (used, done) = match available.iter().take(room).position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (available.len().min(room), false),
+        };
+        buf.extend_from_slice(&available[..used]);
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+    Ok(buf.len() - start)
+}
+
+/// Resolve [`STREAM_DRAIN_GRACE`] after `exited` reports that the child has
+/// exited. Never resolves if the sender goes away without reporting an exit.
+async fn grace_after_exit(mut exited: watch::Receiver<bool>) {
+    if exited.wait_for(|exited| *exited).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+    tokio::time::sleep(STREAM_DRAIN_GRACE).await;
+}
+
+/// Read `reader` to EOF, keeping at most `max_output` bytes / `max_lines`
+/// lines. Returns the kept bytes and the total number of bytes read.
+///
+/// Reading stops early if the stream stays silent for [`STREAM_DRAIN_GRACE`]
+/// after `exited` reports that the child has exited.
 async fn read_capped<R: AsyncRead + Unpin>(
     mut reader: R,
     max_output: usize,
     max_lines: usize,
-) -> io::Result<Vec<u8>> {
+    exited: watch::Receiver<bool>,
+    combined: Option<CombinedCapture>,
+    events: Option<ExecEventSender>,
+) -> io::Result<(Vec<u8>, usize)> {
     let mut buf = Vec::with_capacity(max_output.min(8 * 1024));
     let mut tmp = [0u8; 8192];
 
     let mut remaining_bytes = max_output;
     let mut remaining_lines = max_lines;
+    let mut total_bytes = 0;
 
     loop {
-        let n = reader.read(&mut tmp).await?;
+        let n = tokio::select! {
+            read = reader.read(&mut tmp) => read?,
+            _ = grace_after_exit(exited.clone()) => break,
+        };
         if n == 0 {
             break;
         }
+        total_bytes += n;
 
         // Copy into the buffer only while we still have byte and line budget.
         if remaining_bytes > 0 && remaining_lines > 0 {
-            let mut copy_len = 0;
-            for &b in &tmp[..n] {
-                if remaining_bytes == 0 || remaining_lines == 0 {
-                    break;
-                }
-                copy_len += 1;
-                remaining_bytes -= 1;
-                if b == b'\n' {
-                    remaining_lines -= 1;
-                }
-            }
+            let copy_len = take_within_caps(&tmp[..n], &mut remaining_bytes, &mut remaining_lines);
             buf.extend_from_slice(&tmp[..copy_len]);
+            if let Some(combined) = &combined {
+                combined.record(OutputStream::Stderr, &tmp[..copy_len]);
+            }
+            if copy_len > 0 {
+                let chunk = String::from_utf8_lossy(&tmp[..copy_len]).to_string();
+                emit(&events, ExecEvent::Stderr { chunk }).await;
+            }
         }
         // Continue reading to EOF to avoid back-pressure, but discard once caps are hit.
     }
 
-    Ok(buf)
+    if total_bytes > buf.len() {
+        trim_partial_utf8(&mut buf);
+    }
+    Ok((buf, total_bytes))
 }
 
 #[cfg(unix)]
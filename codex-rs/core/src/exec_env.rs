@@ -1,6 +1,19 @@
 use crate::config_types::{EnvironmentVariablePattern, ShellEnvironmentPolicy, ShellEnvironmentPolicyInherit};
+use crate::exec::CODEX_NESTED_ENV_VAR;
+use crate::flags::CODEX_ENV_DENYLIST;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+/// Transforms the final environment of a command just before it is spawned.
+pub type EnvHook = Arc<dyn Fn(HashMap<String, String>) -> HashMap<String, String> + Send + Sync>;
+
+/// Hook installed with [`set_env_hook`]; `None` means
+/// [`strip_denylisted_env`] with the `CODEX_ENV_DENYLIST` names.
+static ENV_HOOK: LazyLock<RwLock<Option<EnvHook>>> = LazyLock::new(|| RwLock::new(None));
 
 /// Construct an environment map based on the rules in the specified policy. The
 /// resulting map can be passed directly to `Command::envs()` after calling
@@ -66,6 +79,52 @@ where
     env_map
 }
 
+/// Names listed in `CODEX_ENV_DENYLIST`.
+pub fn env_denylist() -> Vec<String> {
+    parse_env_denylist(&CODEX_ENV_DENYLIST)
+}
+
+/// Names in the comma-separated `list`, trimmed, skipping empty entries.
+pub fn parse_env_denylist(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Remove every variable named in `denylist` from `env`.
+pub fn strip_denylisted_env(
+    mut env: HashMap<String, String>,
+    denylist: &[String],
+) -> HashMap<String, String> {
+    env.retain(|name, _| !denylist.iter().any(|denied| denied == name));
+    env
+}
+
+/// Replace the hook every sandbox type runs the final environment through.
+pub fn set_env_hook(hook: EnvHook) {
+    *ENV_HOOK.write().unwrap_or_else(PoisonError::into_inner) = Some(hook);
+}
+
+/// Go back to stripping the `CODEX_ENV_DENYLIST` names.
+pub fn reset_env_hook() {
+    *ENV_HOOK.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Run `env` through the installed hook and mark it with
+/// [`CODEX_NESTED_ENV_VAR`]. Called by every spawn path with the complete
+/// environment the child will see.
+pub fn apply_env_hook(env: HashMap<String, String>) -> HashMap<String, String> {
+    let hook = ENV_HOOK.read().unwrap_or_else(PoisonError::into_inner).clone();
+    let mut env = match hook {
+        Some(hook) => hook(env),
+        None => strip_denylisted_env(env, &env_denylist()),
+    };
+    env.insert(CODEX_NESTED_ENV_VAR.to_string(), "1".to_string());
+    env
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used, clippy::expect_used)]
@@ -191,4 +250,12 @@ mod tests {
         };
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn denylist_entries_are_trimmed_and_empty_ones_skipped() {
+        assert_eq!(
+            parse_env_denylist(" CODEX_TEST_DENIED, LD_PRELOAD,,"),
+            vec!["CODEX_TEST_DENIED".to_string(), "LD_PRELOAD".to_string()]
+        );
+    }
 }
@@ -0,0 +1,46 @@
+//! Progress events emitted while an exec call runs, for callers that want to
+//! show output live instead of waiting for the final
+//! [`ExecToolCallOutput`](crate::exec::ExecToolCallOutput).
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::exec::SandboxType;
+
+/// One step of a running exec call, in the order they happen: `Started`, any
+/// number of `Stdout`/`Stderr` chunks, then `Finished`. Calls that fail
+/// before producing an exit status end without `Finished`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecEvent {
+    Started {
+        /// The command as dispatched, after translation.
+        argv: Vec<String>,
+        /// Display name of the [`SandboxType`] it runs under.
+        sandbox: String,
+    },
+    /// Output kept within the stream caps, before color handling.
+    Stdout { chunk: String },
+    Stderr { chunk: String },
+    Finished { code: i32, duration_ms: u64 },
+}
+
+pub type ExecEventSender = mpsc::Sender<ExecEvent>;
+
+impl ExecEvent {
+    pub(crate) fn started(argv: &[String], sandbox: SandboxType) -> Self {
+        Self::Started {
+            argv: argv.to_vec(),
+            sandbox: sandbox.to_string(),
+        }
+    }
+}
+
+/// Send `event` if anyone is listening. A dropped receiver is not an error;
+/// the call simply stops reporting.
+pub(crate) async fn emit(events: &Option<ExecEventSender>, event: ExecEvent) {
+    if let Some(events) = events {
+        let _ = events.send(event).await;
+    }
+}
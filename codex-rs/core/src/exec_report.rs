@@ -0,0 +1,47 @@
+//! Single JSON document for a "check then run" flow.
+//!
+//! [`ExecReport`] carries what a command produced and, when it was checked
+//! with `codex-execpolicy` first, the policy verdict in the same
+//! `{"result": ..}` shape the `check` subcommand prints:
+//!
+//! ````text
+//! {"exit_code":0,"stdout":"..","stderr":"","duration_ms":12,"correlation_id":"..","policy":{"result":"safe","match":{..}}}
+//! ````
+
+use codex_execpolicy::Output;
+use serde::Serialize;
+
+use crate::exec::ExecToolCallOutput;
+
+#[derive(Debug, Serialize)]
+pub struct ExecReport {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub correlation_id: String,
+    /// Verdict from checking the command against an execpolicy before it ran.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<Output>,
+}
+
+impl ExecReport {
+    /// Attach the verdict the command was checked against.
+    pub fn with_policy(mut self, policy: Output) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+}
+
+impl From<ExecToolCallOutput> for ExecReport {
+    fn from(output: ExecToolCallOutput) -> Self {
+        Self {
+            exit_code: output.exit_code,
+            stdout: output.stdout,
+            stderr: output.stderr,
+            duration_ms: output.duration.as_millis() as u64,
+            correlation_id: output.correlation_id,
+            policy: None,
+        }
+    }
+}
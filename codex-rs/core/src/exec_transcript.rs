@@ -0,0 +1,188 @@
+//! Append-only audit log of exec tool calls.
+//!
+//! When `CODEX_EXEC_TRANSCRIPT` names a file, every call to
+//! [`process_exec_tool_call`](crate::exec::process_exec_tool_call) appends
+//! **one JSON object per line** describing what ran:
+//!
+//! ````text
+//! {"ts":<unix_seconds>,"argv":[..],"translated_argv":[..],"sandbox_type":"none","exit_code":0,"duration_ms":12,"threat_level":"Low","threat_info":"..","error":null,"correlation_id":".."}
+//! ````
+//!
+//! Each line is written with a single `write(2)` on a file opened with
+//! `O_APPEND`, so concurrent writers do not interleave short records.
+//!
+//! [`replay_transcript`] reads such a file back and [`recheck_transcript`]
+//! evaluates the recorded commands against the current policy without
+//! running them, e.g. to see which past runs a tightened policy would block.
+
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Result;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::Output;
+use codex_execpolicy::PolicyWatcher;
+use codex_execpolicy::ThreatLevel;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecToolCallOutput;
+use crate::exec::SandboxType;
+use crate::preflight::threat_level_or_blocked;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExecTranscriptEntry {
+    pub ts: u64,
+    /// The command as requested, before translation.
+    pub argv: Vec<String>,
+    /// The command that was actually dispatched.
+    pub translated_argv: Vec<String>,
+    pub sandbox_type: String,
+    /// `None` when the call failed before the command produced an exit code.
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Threat level of `argv` from the loaded policy watcher's risk database,
+    /// as [`recheck_transcript`] computes it. `None` when no watcher was
+    /// loaded.
+    #[serde(default)]
+    pub threat_level: Option<ThreatLevel>,
+    /// Free-text threat summary the caller passed along with the command.
+    #[serde(default)]
+    pub threat_info: String,
+    pub error: Option<String>,
+    /// See [`ExecParams::correlation_id`](crate::exec::ExecParams::correlation_id).
+    /// Empty for entries written before it was recorded.
+    #[serde(default)]
+    pub correlation_id: String,
+}
+
+impl ExecTranscriptEntry {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        argv: Vec<String>,
+        translated_argv: Vec<String>,
+        sandbox_type: SandboxType,
+        result: &std::result::Result<ExecToolCallOutput, CodexErr>,
+        duration: Duration,
+        threat_level: Option<ThreatLevel>,
+        threat_info: &str,
+        correlation_id: &str,
+    ) -> Self {
+        let (exit_code, error) = match result {
+            Ok(output) => (Some(output.exit_code), None),
+            Err(CodexErr::Sandbox(SandboxErr::Denied(code, _, _))) => {
+                (Some(*code), Some("sandbox denied exec".to_string()))
+            }
+            Err(err) => (None, Some(err.to_string())),
+        };
+        let ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            ts,
+            argv,
+            translated_argv,
+            sandbox_type: sandbox_type.to_string(),
+            exit_code,
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            threat_level,
+            threat_info: threat_info.to_string(),
+            error,
+            correlation_id: correlation_id.to_string(),
+        }
+    }
+}
+
+/// Append `entry` to the transcript at `path`, creating the file if needed.
+pub fn append_exec_transcript(path: &Path, entry: &ExecTranscriptEntry) -> Result<()> {
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| std::io::Error::other(format!("failed to serialise transcript entry: {e}")))?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    file.flush()
+}
+
+/// Read back the transcript at `path`, one entry per non-blank line in the
+/// order they were written. A line that is not a valid entry fails the whole
+/// read with [`std::io::ErrorKind::InvalidData`] naming its line number.
+pub fn replay_transcript(path: &Path) -> Result<Vec<ExecTranscriptEntry>> {
+    let file = std::fs::File::open(path)?;
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}:{}: invalid transcript entry: {e}", path.display(), index + 1),
+            )
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// A recorded call evaluated again under the current policy, see
+/// [`recheck_transcript`].
+#[derive(Debug)]
+pub struct TranscriptRecheck {
+    pub entry: ExecTranscriptEntry,
+    /// Threat level of the recorded `argv` for the requested environment;
+    /// [`ThreatLevel::High`] when the risk database cannot be read.
+    pub threat_level: ThreatLevel,
+    /// Verdict of the current policy for the dispatched command.
+    pub policy_verdict: Output,
+}
+
+impl TranscriptRecheck {
+    /// Whether the current policy would forbid the recorded command.
+    pub fn is_forbidden(&self) -> bool {
+        matches!(self.policy_verdict, Output::Forbidden { .. })
+    }
+}
+
+/// Re-run the threat and policy evaluation of `watcher` on every entry,
+/// without executing anything.
+///
+/// The policy sees the command that was actually dispatched
+/// (`translated_argv`, or `argv` for entries that recorded none), the threat
+/// level is computed from the requested `argv` for `env` (the host OS when
+/// `None`), as in [`preflight`](crate::preflight::preflight).
+pub fn recheck_transcript(
+    entries: Vec<ExecTranscriptEntry>,
+    watcher: &PolicyWatcher,
+    env: Option<&str>,
+) -> Vec<TranscriptRecheck> {
+    entries
+        .into_iter()
+        .map(|entry| {
+            let threat_level = threat_level_or_blocked(watcher, &entry.argv, env);
+            let dispatched = if entry.translated_argv.is_empty() {
+                &entry.argv
+            } else {
+                &entry.translated_argv
+            };
+            let (program, args) = match dispatched.split_first() {
+                Some((program, args)) => (program.as_str(), args.iter().map(String::as_str).collect()),
+                None => ("", Vec::new()),
+            };
+            let policy_verdict = Output::from(watcher.check(&ExecCall::new(program, &args)));
+            TranscriptRecheck {
+                entry,
+                threat_level,
+                policy_verdict,
+            }
+        })
+        .collect()
+}
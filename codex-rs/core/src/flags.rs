@@ -1,7 +1,12 @@
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
 use std::time::Duration;
 
 use env_flags::env_flags;
 
+use crate::config_types::ColorMode;
+use crate::config_types::NestedCodexMode;
+
 env_flags! {
     pub OPENAI_DEFAULT_MODEL: &str = "codex-mini-latest";
     pub OPENAI_API_BASE: &str = "https://api.openai.com/v1";
@@ -22,4 +27,71 @@ env_flags! {
 
     /// Fixture path for offline tests (see client.rs).
     pub CODEX_RS_SSE_FIXTURE: Option<&str> = None;
+
+    /// Expand `$VAR`/`${VAR}` (or `%VAR%` on Windows shells) in exec arguments
+    /// against the command's environment before spawning.
+    pub CODEX_EXPAND_ENV_VARS: bool = false;
+
+    /// Fail black box commands that modify a cwd the sandbox policy does not
+    /// allow writing to, instead of only warning on stderr.
+    pub CODEX_BLACK_BOX_STRICT_READ_ONLY: bool = false;
+
+    /// Append a JSON line describing every exec call to this file.
+    pub CODEX_EXEC_TRANSCRIPT: Option<&str> = None;
+
+    /// Longest stdout line buffered before it is split into several reads, so
+    /// output without newlines cannot grow the line buffer without bound.
+    pub CODEX_MAX_OUTPUT_LINE_BYTES: usize = 64 * 1024;
+
+    /// Longest command line exec calls accept, in bytes of all arguments
+    /// plus one terminator each, as execve(2) counts them.
+    pub CODEX_MAX_COMMAND_BYTES: usize = 256 * 1024;
+
+    /// Most arguments, program included, exec calls accept.
+    pub CODEX_MAX_ARG_COUNT: usize = 4096;
+
+    /// How many exec calls may run concurrently; defaults to the number of
+    /// CPUs available to the process.
+    pub CODEX_MAX_CONCURRENT_EXECS: usize =
+        std::thread::available_parallelism().map(usize::from).unwrap_or(1);
+
+    /// Skip the command translator and run exec commands exactly as given.
+    pub CODEX_DISABLE_TRANSLATION: bool = false;
+
+    /// Refuse to run commands the translator has no mapping for when the
+    /// operating shell is not the host's default shell, instead of running
+    /// them as given.
+    pub CODEX_REQUIRE_TRANSLATION: bool = false;
+
+    /// Comma-separated programs treated as interpreters by the API sandbox
+    /// (run locally rather than handed to the broker), in addition to the
+    /// built-in set.
+    pub CODEX_EXTRA_INTERPRETERS: Option<&str> = None;
+
+    /// Interface the API sandbox listens on for its broker handshake.
+    pub CODEX_API_BIND_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST), |value| value.parse::<IpAddr>();
+
+    /// Allow `CODEX_API_BIND_ADDR` to be a non-loopback address. Off by
+    /// default so the command channel is not exposed by accident.
+    pub CODEX_API_ALLOW_NON_LOOPBACK: bool = false;
+
+    /// Run non-interpreter API sandbox commands locally when no broker
+    /// answers the handshake, instead of only echoing the command line.
+    pub CODEX_API_LOCAL_FALLBACK: bool = false;
+
+    /// Also capture stdout and stderr merged in arrival order, for tools
+    /// whose output only makes sense interleaved.
+    pub CODEX_COMBINED_CAPTURE: bool = false;
+
+    /// Comma-separated environment variables removed from every spawned
+    /// command's environment unless a custom env hook is installed.
+    pub CODEX_ENV_DENYLIST: &str = "LD_PRELOAD,DYLD_INSERT_LIBRARIES";
+
+    /// Whether exec calls that start Codex itself are rejected (`reject`) or
+    /// run, still sandboxed, with the `CODEX_NESTED` marker set (`flatten`).
+    pub CODEX_NESTED_INVOCATION: NestedCodexMode = NestedCodexMode::Reject, |value| value.parse::<NestedCodexMode>();
+
+    /// Whether ANSI color codes in captured exec output are kept (`preserve`)
+    /// or removed (`strip`).
+    pub CODEX_OUTPUT_COLOR: ColorMode = ColorMode::Preserve, |value| value.parse::<ColorMode>();
 }
@@ -1,5 +1,6 @@
 //! Core library for Codex.
 pub mod exec_env;
+pub mod exec_events;
 pub mod config_types;
 pub mod config;
 pub mod protocol;
@@ -13,6 +14,12 @@ pub mod codex;
 
 /// Command execution utilities
 pub mod exec;
+/// append-only audit log of exec calls
+pub mod exec_transcript;
+/// exec output combined with its policy verdict
+pub mod exec_report;
+/// translation, threat and policy checks without executing
+pub mod preflight;
 pub mod client;
 pub mod client_common;
 pub mod conversation_history;
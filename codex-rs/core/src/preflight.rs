@@ -0,0 +1,78 @@
+//! Dry run of the checks an exec call goes through, without running it.
+
+use std::path::Path;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::Output;
+use codex_execpolicy::PolicyWatcher;
+use codex_execpolicy::ThreatLevel;
+use serde::Serialize;
+use translation::CommandTranslator;
+use translation::DEFAULT_TRANSLATOR;
+use translation::OPERATING_SHELL;
+
+use crate::exec::default_translation_shell;
+
+#[derive(Debug, Serialize)]
+pub struct PreflightReport {
+    /// The command as it would be dispatched.
+    pub translated_argv: Vec<String>,
+    /// Risk of the command as given, from the watcher's risk database;
+    /// [`ThreatLevel::High`] when that database cannot be read.
+    pub threat_level: ThreatLevel,
+    /// Verdict of the watcher's policy for `translated_argv`.
+    pub policy_verdict: Output,
+}
+
+/// Translate `command` for `env`, score its threat and check it against the
+/// policy of `watcher`, without spawning anything.
+///
+/// `env` defaults to the operating shell for translation and to the host OS
+/// for scoring. Translation runs on a copy of the global translator, so the
+/// command's warning count is left untouched.
+pub fn preflight(command: &[String], env: Option<&str>, watcher: &PolicyWatcher) -> PreflightReport {
+    let mut translator = match DEFAULT_TRANSLATOR.get() {
+        Some(translator) => match translator.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        },
+        None => CommandTranslator::new(),
+    };
+    let shell = match env {
+        Some(env) => env.to_string(),
+        None => OPERATING_SHELL
+            .get()
+            .cloned()
+            .unwrap_or_else(default_translation_shell),
+    };
+    let translated_argv = if command.is_empty() {
+        Vec::new()
+    } else {
+        translator.translate_argv(command, Path::new("."), &shell, "N/A", &[]).argv
+    };
+
+    let threat_level = threat_level_or_blocked(watcher, command, env);
+
+    let (program, args) = match translated_argv.split_first() {
+        Some((program, args)) => (program.as_str(), args.iter().map(String::as_str).collect()),
+        None => ("", Vec::new()),
+    };
+    let policy_verdict = Output::from(watcher.check(&ExecCall::new(program, &args)));
+
+    PreflightReport {
+        translated_argv,
+        threat_level,
+        policy_verdict,
+    }
+}
+
+/// Threat level of `command` for `env`, or [`ThreatLevel::High`], which
+/// blocks everything but read-only access, when the risk database cannot be
+/// read.
+pub(crate) fn threat_level_or_blocked(watcher: &PolicyWatcher, command: &[String], env: Option<&str>) -> ThreatLevel {
+    let command = command.join(" ");
+    watcher.threat_level_for(&command, env).unwrap_or_else(|e| {
+        tracing::warn!("cannot score `{command}`: {e:#}; treating it as blocked");
+        ThreatLevel::High
+    })
+}
@@ -138,6 +138,18 @@ pub enum AskForApproval {
     Never,
 }
 
+/// Network access granted by a [`SandboxPolicy`], split the way sandbox
+/// backends (e.g. Seatbelt) express it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct NetworkRules {
+    /// May open outgoing connections.
+    pub outbound: bool,
+    /// May accept incoming connections.
+    pub inbound: bool,
+    /// May create system sockets.
+    pub socket: bool,
+}
+
 /// Determines execution restrictions for model shell commands
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -215,6 +227,27 @@ impl SandboxPolicy {
             .any(|perm| matches!(perm, SandboxPermission::NetworkFullAccess))
     }
 
+    /// Hosts granted by [`SandboxPermission::NetworkOutboundHost`], in the
+    /// order they were given. Ignored when full network access is granted.
+    pub fn network_allowlist(&self) -> Vec<String> {
+        self.permissions
+            .iter()
+            .filter_map(|perm| match perm {
+                SandboxPermission::NetworkOutboundHost { host } => Some(host.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn network_rules(&self) -> NetworkRules {
+        let full = self.has_full_network_access();
+        NetworkRules {
+            outbound: full,
+            inbound: full,
+            socket: full,
+        }
+    }
+
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
         let mut writable_roots = Vec::<PathBuf>::new();
         for perm in &self.permissions {
@@ -257,7 +290,7 @@ impl SandboxPolicy {
                 DiskWriteFolder { folder } => {
                     writable_roots.push(folder.clone());
                 }
-                DiskFullReadAccess | NetworkFullAccess => {}
+                DiskFullReadAccess | NetworkFullAccess | NetworkOutboundHost { .. } => {}
                 DiskFullWriteAccess => {
                     // Currently, we expect callers to only invoke this method
                     // after verifying has_full_disk_write_access() is false.
@@ -267,6 +300,15 @@ impl SandboxPolicy {
         writable_roots
     }
 
+    /// Whether commands run in `cwd` may write to it under this policy.
+    pub fn has_cwd_write_access(&self, cwd: &Path) -> bool {
+        self.has_full_disk_write_access()
+            || self
+                .get_writable_roots_with_cwd(cwd)
+                .iter()
+                .any(|root| cwd.starts_with(root))
+    }
+
     pub fn is_unrestricted(&self) -> bool {
         self.has_full_disk_read_access()
             && self.has_full_disk_write_access()
@@ -306,6 +348,14 @@ pub enum SandboxPermission {
 
     /// Can make arbitrary network requests.
     NetworkFullAccess,
+
+    /// May open outbound connections to `host`, given as `name:port`.
+    /// Seatbelt cannot filter by host name, so it only scopes outbound rules
+    /// to the port and ignores entries without one. On Linux the permission
+    /// is passed to the helper but is a no-op: the seccomp filter cannot tell
+    /// hosts apart, so outbound access stays blocked unless
+    /// [`SandboxPermission::NetworkFullAccess`] is granted.
+    NetworkOutboundHost { host: String },
 }
 
 impl SandboxPermission {
@@ -314,7 +364,10 @@ impl SandboxPermission {
     }
 
     pub fn is_network_accessible(&self) -> bool {
-        matches!(self, SandboxPermission::NetworkFullAccess)
+        matches!(
+            self,
+            SandboxPermission::NetworkFullAccess | SandboxPermission::NetworkOutboundHost { .. }
+        )
     }
 }
 
@@ -588,6 +641,22 @@ mod tests {
     #![allow(clippy::unwrap_used)]
     use super::*;
 
+    #[test]
+    fn network_rules_follow_network_permission() {
+        let full = SandboxPolicy::full_jailbreak().network_rules();
+        assert_eq!(
+            full,
+            NetworkRules {
+                outbound: true,
+                inbound: true,
+                socket: true,
+            }
+        );
+
+        let none = SandboxPolicy::new_read_only_policy().network_rules();
+        assert_eq!(none, NetworkRules::default());
+    }
+
     /// Serialize Event to verify that its JSON representation has the expected
     /// amount of nesting.
     #[test]
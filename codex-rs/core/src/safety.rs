@@ -1,13 +1,14 @@
 use std::collections::HashSet;
 use std::env;
-use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
+use codex_execpolicy::normalize_path;
 
 use crate::exec::SandboxType;
+use crate::exec::check_command_limits;
 
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
@@ -64,12 +65,76 @@ pub fn assess_patch_safety(
     }
 }
 
+/// Combine [`assess_patch_safety`] over every patch in a changeset into the
+/// most restrictive decision: `Reject` if any patch is rejected, otherwise
+/// `AskUser` if any needs approval, otherwise `AutoApprove`, under a sandbox
+/// if any patch needs one. An empty changeset is rejected like an empty patch.
+pub fn assess_patches_safety(
+    actions: &[ApplyPatchAction],
+    policy: AskForApproval,
+    writable_roots: &[PathBuf],
+    cwd: &Path,
+) -> SafetyCheck {
+    if actions.is_empty() {
+        return SafetyCheck::Reject {
+            reason: "empty patch".to_string(),
+        };
+    }
+
+    let mut ask_user = false;
+    let mut approved_sandbox = SandboxType::None;
+    for action in actions {
+        match assess_patch_safety(action, policy, writable_roots, cwd) {
+            reject @ SafetyCheck::Reject { .. } => return reject,
+            SafetyCheck::AskUser => ask_user = true,
+            SafetyCheck::AutoApprove { sandbox_type } => {
+                if approved_sandbox == SandboxType::None {
+                    approved_sandbox = sandbox_type;
+                }
+            }
+        }
+    }
+
+    if ask_user {
+        SafetyCheck::AskUser
+    } else {
+        SafetyCheck::AutoApprove {
+            sandbox_type: approved_sandbox,
+        }
+    }
+}
+
 pub fn assess_command_safety(
-    _command: &[String],
+    command: &[String],
+    approval_policy: AskForApproval,
+    sandbox_policy: &SandboxPolicy,
+    approved: &HashSet<Vec<String>>,
+) -> SafetyCheck {
+    assess_command_safety_with(
+        command,
+        approval_policy,
+        sandbox_policy,
+        approved,
+        get_platform_sandbox(),
+    )
+}
+
+/// [`assess_command_safety`] with the available sandbox passed in instead of
+/// detected, so the decision does not depend on global sandbox state.
+/// Commands over the limits of [`check_command_limits`] are always rejected.
+pub fn assess_command_safety_with(
+    command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     _approved: &HashSet<Vec<String>>,
+    sandbox: Option<SandboxType>,
 ) -> SafetyCheck {
+    if let Err(err) = check_command_limits(command) {
+        return SafetyCheck::Reject {
+            reason: err.to_string(),
+        };
+    }
+
     let approve_without_sandbox = || SafetyCheck::AutoApprove {
         sandbox_type: SandboxType::None,
     };
@@ -77,7 +142,7 @@ pub fn assess_command_safety(
     if sandbox_policy.is_unrestricted() {
         approve_without_sandbox()
     } else {
-        match get_platform_sandbox() {
+        match sandbox {
             // We have a sandbox, so we can approve the command in all modes
             Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
             None => {
@@ -132,22 +197,6 @@ fn is_write_patch_constrained_to_writable_paths(
         return false;
     }
 
-    // Normalize a path by removing `.` and resolving `..` without touching the
-    // filesystem (works even if the file does not exist).
-    fn normalize(path: &Path) -> Option<PathBuf> {
-        let mut out = PathBuf::new();
-        for comp in path.components() {
-            match comp {
-                Component::ParentDir => {
-                    out.pop();
-                }
-                Component::CurDir => { /* skip */ }
-                other => out.push(other.as_os_str()),
-            }
-        }
-        Some(out)
-    }
-
     // Determine whether `path` is inside **any** writable root. Both `path`
     // and roots are converted to absolute, normalized forms before the
     // prefix check.
@@ -157,16 +206,13 @@ fn is_write_patch_constrained_to_writable_paths(
         } else {
             cwd.join(p)
         };
-        let abs = match normalize(&abs) {
-            Some(v) => v,
-            None => return false,
-        };
+        let abs = normalize_path(&abs);
 
         writable_roots.iter().any(|root| {
             let root_abs = if root.is_absolute() {
                 root.clone()
             } else {
-                normalize(&cwd.join(root)).unwrap_or_else(|| cwd.join(root))
+                normalize_path(&cwd.join(root))
             };
 
             abs.starts_with(&root_abs)
@@ -251,4 +297,113 @@ mod tests {
             &cwd,
         ))
     }
+
+    fn ls() -> Vec<String> {
+        vec!["ls".to_string()]
+    }
+
+    #[test]
+    fn unrestricted_policy_runs_without_sandbox() {
+        let check = assess_command_safety_with(
+            &ls(),
+            AskForApproval::Never,
+            &SandboxPolicy::full_jailbreak(),
+            &HashSet::new(),
+            None,
+        );
+        assert!(matches!(
+            check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        ));
+    }
+
+    #[test]
+    fn available_sandbox_is_auto_approved() {
+        let check = assess_command_safety_with(
+            &ls(),
+            AskForApproval::UnlessAllowListed,
+            &SandboxPolicy::new_read_only_policy(),
+            &HashSet::new(),
+            Some(SandboxType::LinuxSeccomp),
+        );
+        assert!(matches!(
+            check,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::LinuxSeccomp
+            }
+        ));
+    }
+
+    #[test]
+    fn no_sandbox_rejects_when_never_asking() {
+        let check = assess_command_safety_with(
+            &ls(),
+            AskForApproval::Never,
+            &SandboxPolicy::new_read_only_policy(),
+            &HashSet::new(),
+            None,
+        );
+        assert!(matches!(check, SafetyCheck::Reject { .. }));
+    }
+
+    #[test]
+    fn no_sandbox_asks_the_user_otherwise() {
+        for policy in [
+            AskForApproval::UnlessAllowListed,
+            AskForApproval::AutoEdit,
+            AskForApproval::OnFailure,
+        ] {
+            let check = assess_command_safety_with(
+                &ls(),
+                policy,
+                &SandboxPolicy::new_read_only_policy(),
+                &HashSet::new(),
+                None,
+            );
+            assert!(matches!(check, SafetyCheck::AskUser), "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn patch_batch_takes_most_restrictive_decision() {
+        let cwd = std::env::current_dir().unwrap();
+        let parent = cwd.parent().unwrap().to_path_buf();
+        let make_add_change = |p: PathBuf| ApplyPatchAction::new_add_for_test(&p, "".to_string());
+        let roots = [PathBuf::from(".")];
+
+        let all_inside = assess_patches_safety(
+            &[
+                make_add_change(cwd.join("inner.txt")),
+                make_add_change(cwd.join("other.txt")),
+            ],
+            AskForApproval::AutoEdit,
+            &roots,
+            &cwd,
+        );
+        assert!(matches!(
+            all_inside,
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        ));
+
+        let mixed = [
+            make_add_change(cwd.join("inner.txt")),
+            make_add_change(parent.join("outside.txt")),
+        ];
+        assert!(matches!(
+            assess_patches_safety(&mixed, AskForApproval::AutoEdit, &roots, &cwd),
+            SafetyCheck::AskUser
+        ));
+        assert!(matches!(
+            assess_patches_safety(&mixed, AskForApproval::Never, &roots, &cwd),
+            SafetyCheck::Reject { .. }
+        ));
+        assert!(matches!(
+            assess_patches_safety(&[], AskForApproval::AutoEdit, &roots, &cwd),
+            SafetyCheck::Reject { .. }
+        ));
+    }
 }
@@ -6,6 +6,7 @@ use tokio::sync::Notify;
 use tracing::debug;
 
 use crate::config::Config;
+use crate::config_types::ColorMode;
 
 const INITIAL_DELAY_MS: u64 = 200;
 const BACKOFF_FACTOR: f64 = 1.3;
@@ -35,6 +36,37 @@ pub(crate) fn backoff(attempt: u64) -> Duration {
     Duration::from_millis((base as f64 * jitter) as u64)
 }
 
+/// Apply `mode` to captured command output.
+pub fn apply_color_mode(mode: ColorMode, output: Vec<u8>) -> Vec<u8> {
+    match mode {
+        ColorMode::Preserve => output,
+        ColorMode::Strip => strip_ansi_csi(&output),
+    }
+}
+
+/// Remove ANSI CSI sequences (`ESC [` parameters, intermediates, final byte)
+/// from `bytes`. An unterminated sequence at the end is dropped as well.
+pub fn strip_ansi_csi(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == 0x1b && bytes.get(i + 1) == Some(&b'[') {
+            i += 2;
+            while i < bytes.len() && (0x20..=0x3f).contains(&bytes[i]) {
+                i += 1;
+            }
+            // Skip the final byte (0x40..=0x7e) if present.
+            if i < bytes.len() && (0x40..=0x7e).contains(&bytes[i]) {
+                i += 1;
+            }
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
 /// Return `true` if the project folder specified by the `Config` is inside a
 /// Git repository.
 ///
@@ -5,16 +5,32 @@ use std::os::unix::process::ExitStatusExt;
 #[cfg(windows)]
 use std::os::windows::process::ExitStatusExt;
 use std::future::Future;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::io::{self, AsyncRead, AsyncWriteExt, DuplexStream, duplex};
 use tokio::process::{Child, ChildStderr, ChildStdout};
 
+#[cfg(unix)]
+const SIGKILL: i32 = 9;
+
 /// Represents a child process created from internal command results.
 /// This avoids spawning a real OS process while still exposing an API
 /// similar to [`tokio::process::Child`].
+///
+/// By default it exits with status 0 as soon as it is waited on. Use the
+/// `with_*` builders to model a failing or long-running operation; waiting
+/// then honors [`ChildLike::start_kill`] and an optional deadline the way a
+/// real process would.
 pub struct InternalChild {
     stdout: Option<DuplexStream>,
     stderr: Option<DuplexStream>,
     waited: bool,
+    exit_code: i32,
+    started: Instant,
+    run_time: Duration,
+    deadline: Option<Instant>,
+    killed: bool,
+    status: Option<ExitStatus>,
 }
 
 impl InternalChild {
@@ -31,8 +47,53 @@ impl InternalChild {
             stdout: Some(out_read),
             stderr: Some(err_read),
             waited: false,
+            exit_code: 0,
+            started: Instant::now(),
+            run_time: Duration::ZERO,
+            deadline: None,
+            killed: false,
+            status: None,
         }
     }
+
+    /// Exit code reported once the synthetic operation completes.
+    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// How long the synthetic operation takes, measured from creation.
+    pub fn with_run_time(mut self, run_time: Duration) -> Self {
+        self.run_time = run_time;
+        self
+    }
+
+    /// Kill the operation if it is still running at `deadline`.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+}
+
+/// Status of a child terminated by SIGKILL.
+#[cfg(unix)]
+fn killed_status() -> ExitStatus {
+    ExitStatus::from_raw(SIGKILL)
+}
+
+#[cfg(windows)]
+fn killed_status() -> ExitStatus {
+    ExitStatus::from_raw(1)
+}
+
+#[cfg(unix)]
+fn exited_status(code: i32) -> ExitStatus {
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exited_status(code: i32) -> ExitStatus {
+    ExitStatus::from_raw(code as u32)
 }
 
 /// Trait abstracting the minimal interface required by
@@ -81,13 +142,36 @@ impl ChildLike for InternalChild {
     }
 
     fn start_kill(&mut self) -> io::Result<()> {
-        // Nothing to kill
+        if self.status.is_none() {
+            self.killed = true;
+        }
         Ok(())
     }
 
     fn wait_future<'a>(&'a mut self) -> Pin<Box<dyn Future<Output = io::Result<ExitStatus>> + Send + 'a>> {
         self.waited = true;
-        Box::pin(async move { Ok(ExitStatus::from_raw(0)) })
+        Box::pin(async move {
+            if let Some(status) = self.status {
+                return Ok(status);
+            }
+            let finish = self.started + self.run_time;
+            let status = if self.killed {
+                killed_status()
+            } else {
+                match self.deadline {
+                    Some(deadline) if deadline < finish => {
+                        tokio::time::sleep_until(deadline.into()).await;
+                        killed_status()
+                    }
+                    _ => {
+                        tokio::time::sleep_until(finish.into()).await;
+                        exited_status(self.exit_code)
+                    }
+                }
+            };
+            self.status = Some(status);
+            Ok(status)
+        })
     }
 }
 
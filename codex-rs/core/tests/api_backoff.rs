@@ -0,0 +1,57 @@
+#![allow(clippy::expect_used)]
+use std::time::{Duration, Instant};
+
+use codex_core::api::{BackoffConfig, accept_with_retries};
+use tokio::net::TcpListener;
+
+#[test]
+fn delays_grow_until_the_max() {
+    let backoff = BackoffConfig {
+        initial: Duration::from_millis(100),
+        max: Duration::from_millis(500),
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+    let delays: Vec<Duration> = (1..=5).map(|attempt| backoff.delay(attempt)).collect();
+    assert_eq!(
+        delays,
+        [100, 200, 400, 500, 500].map(Duration::from_millis).to_vec()
+    );
+}
+
+#[test]
+fn jitter_stays_within_bounds() {
+    let backoff = BackoffConfig {
+        initial: Duration::from_millis(100),
+        max: Duration::from_secs(10),
+        multiplier: 2.0,
+        jitter: 0.25,
+    };
+    for attempt in 1..=4 {
+        let base = backoff.base_delay(attempt);
+        for _ in 0..50 {
+            let delay = backoff.delay(attempt);
+            assert!(delay >= base.mul_f64(0.75) && delay <= base.mul_f64(1.25), "{delay:?} vs {base:?}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn accept_waits_follow_the_backoff() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let backoff = BackoffConfig {
+        initial: Duration::from_millis(50),
+        max: Duration::from_millis(100),
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+    let start = Instant::now();
+    let (message, stream) = accept_with_retries(listener, 3, backoff).await.expect("accept");
+    let elapsed = start.elapsed();
+
+    assert_eq!(message, "No response on the API");
+    assert!(stream.is_none());
+    // 50ms + 100ms + 100ms (capped).
+    assert!(elapsed >= Duration::from_millis(250), "elapsed {elapsed:?}");
+    assert!(elapsed < Duration::from_secs(2), "elapsed {elapsed:?}");
+}
@@ -0,0 +1,56 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_core::api::{ApiHandshake, BackoffConfig};
+use codex_core::exec::{ApiSandboxConfig, ExecConfig, StdioPolicy, spawn_command_under_api_with};
+use codex_core::protocol::SandboxPolicy;
+
+const ANY: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+#[tokio::test]
+async fn non_loopback_bind_requires_opt_in() {
+    let backoff = BackoffConfig::fixed(Duration::from_millis(10));
+    let err = ApiHandshake::bind_on(ANY, false, 1, backoff)
+        .await
+        .err()
+        .expect("non-loopback bind should be refused");
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+    let handshake = ApiHandshake::bind_on(ANY, true, 1, backoff).await.expect("opted-in bind");
+    assert_eq!(handshake.local_addr().ip(), ANY);
+
+    let loopback = ApiHandshake::bind_on(IpAddr::V4(Ipv4Addr::LOCALHOST), false, 1, backoff)
+        .await
+        .expect("loopback bind");
+    assert!(loopback.local_addr().ip().is_loopback());
+}
+
+#[tokio::test]
+async fn configured_non_loopback_address_is_refused_by_the_api_sandbox() {
+    let config = ExecConfig {
+        api: ApiSandboxConfig {
+            bind_addr: ANY,
+            allow_non_loopback: false,
+            local_fallback: false,
+        },
+        ..common::exec_config()
+    };
+    let result = spawn_command_under_api_with(
+        vec!["ls".to_string()],
+        &SandboxPolicy::new_read_only_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+        Some(100),
+        None,
+        &config,
+    )
+    .await;
+    let err = result.expect_err("API sandbox should refuse to bind");
+    assert!(err.to_string().contains("non-loopback"), "unexpected error: {err}");
+}
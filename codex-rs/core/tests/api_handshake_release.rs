@@ -0,0 +1,55 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use codex_core::api::{ApiHandshake, BackoffConfig};
+use codex_core::exec::{API_SPAWN_FAILURE, StdioPolicy, spawn_command_under_api_with};
+use codex_core::protocol::SandboxPolicy;
+use tokio::net::TcpListener;
+
+/// Rebind `addr`, retrying briefly while the aborted accept task winds down.
+async fn rebind_within(addr: std::net::SocketAddr, limit: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < limit {
+        if TcpListener::bind(addr).await.is_ok() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn dropping_handshake_releases_port() {
+    // Without the abort this would hold the port for a minute of retries.
+    let handshake = ApiHandshake::bind(3, BackoffConfig::fixed(Duration::from_secs(20)))
+        .await
+        .expect("bind");
+    let addr = handshake.local_addr();
+    assert!(TcpListener::bind(addr).await.is_err(), "port should be in use");
+
+    drop(handshake);
+    assert!(rebind_within(addr, Duration::from_millis(500)).await);
+}
+
+#[tokio::test]
+async fn missing_interpreter_returns_without_waiting_for_handshake() {
+    let start = Instant::now();
+    let output = spawn_command_under_api_with(
+        vec!["/nonexistent/bin/python3".to_string()],
+        &SandboxPolicy::new_read_only_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+        None,
+        None,
+        &common::exec_config(),
+    )
+    .await
+    .expect("api exec failed");
+    assert_eq!(output.exit_status.code(), Some(API_SPAWN_FAILURE));
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
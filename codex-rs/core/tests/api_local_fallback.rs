@@ -0,0 +1,57 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_core::exec::{ApiSandboxConfig, ExecConfig, StdioPolicy, spawn_command_under_api_with};
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+#[cfg(unix)]
+async fn run_without_broker(api_local_fallback: bool) -> (String, Option<i32>) {
+    // A stand-in tool that is not an interpreter, so it is normally handed
+    // to the broker rather than spawned.
+    use std::os::unix::fs::PermissionsExt;
+    let dir = TempDir::new().expect("tempdir");
+    let tool = dir.path().join("fallback-tool");
+    std::fs::write(&tool, "#!/bin/sh\necho ran locally\nexit 3\n").expect("write script");
+    std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+
+    let output = spawn_command_under_api_with(
+        vec![tool.to_string_lossy().to_string()],
+        &SandboxPolicy::new_full_auto_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+        Some(5_000),
+        None,
+        &ExecConfig {
+            api: ApiSandboxConfig {
+                local_fallback: api_local_fallback,
+                ..ApiSandboxConfig::default()
+            },
+            ..common::exec_config()
+        },
+    )
+    .await
+    .expect("spawn under api failed");
+    (String::from_utf8_lossy(&output.stdout).to_string(), output.exit_status.code())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn fallback_runs_the_command_locally() {
+    let (stdout, code) = run_without_broker(true).await;
+    assert!(stdout.contains("ran locally"), "stdout: {stdout}");
+    assert!(!stdout.contains("would run"), "stdout: {stdout}");
+    assert_eq!(code, Some(3));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn without_fallback_the_command_is_only_echoed() {
+    let (stdout, _) = run_without_broker(false).await;
+    assert!(stdout.contains("would run"), "stdout: {stdout}");
+    assert!(!stdout.contains("ran locally"), "stdout: {stdout}");
+}
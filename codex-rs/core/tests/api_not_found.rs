@@ -0,0 +1,59 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_core::exec::{API_HANDSHAKE_FAILURE, API_SPAWN_FAILURE, StdioPolicy, spawn_command_under_api_with};
+use codex_core::protocol::SandboxPolicy;
+
+fn path_env() -> HashMap<String, String> {
+    HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())])
+}
+
+async fn run_under_api(command: &[&str]) -> (Option<i32>, String, String) {
+    let output = spawn_command_under_api_with(
+        command.iter().map(|s| s.to_string()).collect(),
+        &SandboxPolicy::new_read_only_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        path_env(),
+        Some(1_000),
+        None,
+        &common::exec_config(),
+    )
+    .await
+    .expect("api exec failed");
+    (
+        output.exit_status.code(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+#[tokio::test]
+async fn missing_interpreter_reports_not_found() {
+    let (code, _stdout, stderr) = run_under_api(&["/nonexistent/bin/python3", "-c", "pass"]).await;
+    assert_eq!(code, Some(API_SPAWN_FAILURE));
+    assert!(stderr.contains("Program not found"), "stderr: {stderr}");
+}
+
+#[tokio::test]
+async fn missing_non_interpreter_reports_not_found() {
+    let (code, stdout, stderr) = run_under_api(&["definitely-not-a-real-program", "--flag"]).await;
+    assert_eq!(code, Some(API_SPAWN_FAILURE));
+    assert!(stdout.is_empty());
+    assert!(
+        stderr.contains("Program not found: definitely-not-a-real-program --flag"),
+        "stderr: {stderr}"
+    );
+}
+
+#[tokio::test]
+async fn existing_non_interpreter_reports_what_would_run() {
+    // Nothing answers on the API, so the command is described, not run.
+    let (code, stdout, _stderr) = run_under_api(&["ls", "-l"]).await;
+    assert_eq!(code, Some(API_HANDSHAKE_FAILURE));
+    assert!(stdout.contains("No response on the API"), "stdout: {stdout}");
+    assert!(stdout.contains("would run: ls -l"), "stdout: {stdout}");
+}
@@ -0,0 +1,19 @@
+use codex_core::exec::{SandboxType, available_sandbox_types};
+
+#[test]
+fn portable_sandboxes_are_always_available() {
+    let types = available_sandbox_types();
+    for always in [SandboxType::None, SandboxType::BlackBox, SandboxType::Api] {
+        assert!(types.contains(&always), "{always:?} missing from {types:?}");
+    }
+    assert!(!types.contains(&SandboxType::Internal));
+}
+
+#[test]
+fn platform_sandboxes_follow_target_os() {
+    let types = available_sandbox_types();
+    assert_eq!(types.contains(&SandboxType::MacosSeatbelt), cfg!(target_os = "macos"));
+    assert_eq!(types.contains(&SandboxType::LinuxSeccomp), cfg!(target_os = "linux"));
+    assert_eq!(types.contains(&SandboxType::Win64Cmd), cfg!(windows));
+    assert_eq!(types.contains(&SandboxType::Win64Ps), cfg!(windows));
+}
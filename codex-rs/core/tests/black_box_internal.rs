@@ -0,0 +1,126 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::black_box::black_box::spawn_command_under_black_box;
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::exec::{
+    ExecParams, SandboxStateGuard, SandboxType, StdioPolicy, enable_black_box_sandbox,
+    process_exec_tool_call_with_config,
+};
+use codex_core::protocol::SandboxPolicy;
+use codex_core::utils::child_ext::ChildLike;
+use internal_commands::{InternalCommandOutput, register_internal_command};
+use tokio::io::AsyncReadExt;
+use tokio::sync::Notify;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+fn register_echo() {
+    register_internal_command("codex_test_black_box_echo", |args, _| {
+        Ok(InternalCommandOutput {
+            stdout: format!("echoed: {}", args.join(" ")),
+            stderr: "note".to_string(),
+        })
+    });
+}
+
+/// `process_exec_tool_call` dispatches internal commands itself, so asking for
+/// the black box does not route them through it.
+#[tokio::test]
+async fn internal_command_requested_under_black_box_runs_internally() {
+    register_echo();
+
+    let _guard = SandboxStateGuard::capture();
+    enable_black_box_sandbox();
+    let params = ExecParams {
+        command: argv(&["codex_test_black_box_echo", "hello"]),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::BlackBox,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("internal exec failed");
+
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "echoed: hello");
+    assert_eq!(output.stderr, "note");
+}
+
+#[tokio::test]
+async fn failing_internal_command_exits_non_zero_from_black_box_spawn() {
+    let (mut child, _) = spawn_command_under_black_box(
+        argv(&["codex_read_doc"]),
+        SandboxPolicy::new_read_only_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        ShellEnvironmentPolicy::default(),
+        None,
+    )
+    .await
+    .expect("internal command should still produce a child");
+
+    let mut stderr = String::new();
+    child
+        .take_stderr()
+        .expect("stderr")
+        .read_to_string(&mut stderr)
+        .await
+        .expect("read stderr");
+    let status = child.wait_future().await.expect("wait");
+
+    assert_eq!(status.code(), Some(1));
+    assert!(stderr.contains("Missing document name"), "stderr: {stderr}");
+}
+
+#[tokio::test]
+async fn internal_command_output_reaches_caller_from_black_box_spawn() {
+    register_echo();
+
+    let (mut child, _) = spawn_command_under_black_box(
+        argv(&["codex_test_black_box_echo", "hello"]),
+        SandboxPolicy::new_read_only_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        ShellEnvironmentPolicy::default(),
+        None,
+    )
+    .await
+    .expect("spawn under black box");
+
+    let mut stdout = String::new();
+    child
+        .take_stdout()
+        .expect("stdout")
+        .read_to_string(&mut stdout)
+        .await
+        .expect("read stdout");
+    let mut stderr = String::new();
+    child
+        .take_stderr()
+        .expect("stderr")
+        .read_to_string(&mut stderr)
+        .await
+        .expect("read stderr");
+    let status = child.wait_future().await.expect("wait");
+
+    assert_eq!(status.code(), Some(0));
+    assert_eq!(stdout, "echoed: hello");
+    assert_eq!(stderr, "note");
+}
@@ -0,0 +1,53 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+use tokio::sync::Notify;
+
+async fn write_file_under_black_box(policy: &SandboxPolicy) -> (TempDir, String) {
+    let dir = TempDir::new().expect("tempdir");
+    let params = ExecParams {
+        command: vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            "echo hi > written.txt".to_string(),
+        ],
+        cwd: dir.path().to_path_buf(),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::BlackBox,
+        Arc::new(Notify::new()),
+        policy,
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("black box exec failed");
+    assert_eq!(output.exit_code, 0);
+    (dir, output.stderr)
+}
+
+#[tokio::test]
+async fn write_to_read_only_cwd_is_flagged() {
+    let (dir, stderr) = write_file_under_black_box(&SandboxPolicy::new_read_only_policy()).await;
+    assert!(dir.path().join("written.txt").exists());
+    assert!(stderr.contains("modified the read-only working directory"), "stderr: {stderr}");
+    assert!(stderr.contains("written.txt"), "stderr: {stderr}");
+}
+
+#[tokio::test]
+async fn write_to_writable_cwd_is_not_flagged() {
+    let (_dir, stderr) = write_file_under_black_box(&SandboxPolicy::new_full_auto_policy()).await;
+    assert!(!stderr.contains("read-only working directory"), "stderr: {stderr}");
+}
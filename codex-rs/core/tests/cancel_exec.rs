@@ -0,0 +1,111 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::error::Result;
+use codex_core::exec::{ExecParams, ExecToolCallOutput, SandboxType, cancel_exec, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+async fn run(script: &str, correlation_id: &str) -> Result<(ExecToolCallOutput, Duration)> {
+    run_with_ctrl_c(script, correlation_id, Arc::new(Notify::new())).await
+}
+
+async fn run_with_ctrl_c(
+    script: &str,
+    correlation_id: &str,
+    ctrl_c: Arc<Notify>,
+) -> Result<(ExecToolCallOutput, Duration)> {
+    let start = Instant::now();
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: Some(10_000),
+        env: HashMap::new(),
+        correlation_id: Some(correlation_id.to_string()),
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        ctrl_c,
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await?;
+    Ok((output, start.elapsed()))
+}
+
+#[tokio::test]
+async fn cancel_exec_interrupts_only_the_matching_call() {
+    let cancelled = tokio::spawn(run("sleep 5", "cancel-exec-long"));
+    let survivor = tokio::spawn(run("sleep 1; echo done", "cancel-exec-survivor"));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert!(cancel_exec("cancel-exec-long"));
+    assert!(!cancel_exec("cancel-exec-unknown"));
+
+    let (cancelled, cancelled_after) = cancelled.await.expect("join").expect("exec failed");
+    assert_ne!(cancelled.exit_code, 0);
+    assert!(cancelled_after < Duration::from_secs(1));
+
+    let (survivor, _) = survivor.await.expect("join").expect("exec failed");
+    assert_eq!(survivor.exit_code, 0);
+    assert!(survivor.stdout.contains("done"));
+
+    // Finished calls are no longer registered.
+    assert!(!cancel_exec("cancel-exec-long"));
+    assert!(!cancel_exec("cancel-exec-survivor"));
+}
+
+#[tokio::test]
+async fn cancel_exec_leaves_calls_sharing_a_ctrl_c_alone() {
+    let ctrl_c = Arc::new(Notify::new());
+    let cancelled = tokio::spawn(run_with_ctrl_c(
+        "sleep 5",
+        "shared-ctrl-c-long",
+        Arc::clone(&ctrl_c),
+    ));
+    let survivor = tokio::spawn(run_with_ctrl_c(
+        "sleep 1; echo done",
+        "shared-ctrl-c-survivor",
+        Arc::clone(&ctrl_c),
+    ));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert!(cancel_exec("shared-ctrl-c-long"));
+
+    let (cancelled, cancelled_after) = cancelled.await.expect("join").expect("exec failed");
+    assert_ne!(cancelled.exit_code, 0);
+    assert!(cancelled_after < Duration::from_secs(1));
+
+    let (survivor, _) = survivor.await.expect("join").expect("exec failed");
+    assert_eq!(survivor.exit_code, 0);
+    assert!(survivor.stdout.contains("done"));
+
+    // No wakeup was left behind on the shared ctrl_c for the next call.
+    let (next, _) = run_with_ctrl_c("echo next", "shared-ctrl-c-next", Arc::clone(&ctrl_c))
+        .await
+        .expect("exec failed");
+    assert_eq!(next.exit_code, 0);
+    assert!(next.stdout.contains("next"));
+
+    // The shared ctrl_c still interrupts a call waiting on it.
+    let interrupted = tokio::spawn(run_with_ctrl_c(
+        "sleep 5",
+        "shared-ctrl-c-fired",
+        Arc::clone(&ctrl_c),
+    ));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    ctrl_c.notify_waiters();
+    let (interrupted, interrupted_after) = interrupted.await.expect("join").expect("exec failed");
+    assert_ne!(interrupted.exit_code, 0);
+    assert!(interrupted_after < Duration::from_secs(1));
+}
@@ -0,0 +1,63 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::config_types::ColorMode;
+use codex_core::exec::{ExecConfig, ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use codex_core::util::apply_color_mode;
+use tokio::sync::Notify;
+
+const COLORED: &[u8] = b"\x1b[1;31merror\x1b[0m: build \x1b[32mok\x1b[m\x1b[2K";
+
+#[test]
+fn strip_removes_csi_sequences() {
+    assert_eq!(apply_color_mode(ColorMode::Strip, COLORED.to_vec()), b"error: build ok");
+}
+
+#[test]
+fn preserve_keeps_escapes() {
+    assert_eq!(apply_color_mode(ColorMode::Preserve, COLORED.to_vec()), COLORED);
+}
+
+#[test]
+fn color_mode_parses_from_flag_values() {
+    assert_eq!("strip".parse::<ColorMode>().expect("parse"), ColorMode::Strip);
+    assert_eq!("Preserve".parse::<ColorMode>().expect("parse"), ColorMode::Preserve);
+    assert!("rainbow".parse::<ColorMode>().is_err());
+}
+
+#[tokio::test]
+async fn captured_stderr_is_stripped_when_configured() {
+    let params = ExecParams {
+        command: vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            "printf '\\033[31mred\\033[0m plain' >&2".to_string(),
+        ],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())]),
+        correlation_id: None,
+    };
+    let config = ExecConfig {
+        output_color: ColorMode::Strip,
+        ..common::exec_config()
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &config,
+    )
+    .await
+    .expect("exec failed");
+    assert_eq!(output.stderr, "red plain");
+}
@@ -0,0 +1,57 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{
+    ExecConfig, ExecParams, OutputStream, SandboxType, process_exec_tool_call_with_config,
+};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn combined_capture_preserves_arrival_order() {
+    let script = "echo out1; sleep 0.3; echo err1 >&2; sleep 0.3; echo out2; sleep 0.3; echo err2 >&2";
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let config = ExecConfig {
+        combined_capture: true,
+        ..common::exec_config()
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &config,
+    )
+    .await
+    .expect("exec failed");
+
+    let combined: Vec<(OutputStream, String)> = output
+        .combined
+        .expect("combined capture")
+        .into_iter()
+        .map(|chunk| (chunk.stream, String::from_utf8_lossy(&chunk.bytes).to_string()))
+        .collect();
+    assert_eq!(
+        combined,
+        vec![
+            (OutputStream::Stdout, "out1\n".to_string()),
+            (OutputStream::Stderr, "err1\n".to_string()),
+            (OutputStream::Stdout, "out2\n".to_string()),
+            (OutputStream::Stderr, "err2\n".to_string()),
+        ]
+    );
+    assert_eq!(output.stderr, "err1\nerr2\n");
+}
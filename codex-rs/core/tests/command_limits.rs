@@ -0,0 +1,76 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::error::CodexErr;
+use codex_core::exec::{ExecParams, SandboxType, check_command_limits_with, process_exec_tool_call_with_config};
+use codex_core::protocol::{AskForApproval, SandboxPolicy};
+use codex_core::safety::{SafetyCheck, assess_command_safety_with};
+use tokio::sync::Notify;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn arg_count_limit_is_inclusive() {
+    // `echo a b` is 3 arguments and 5 + 2 + 2 = 9 bytes with terminators.
+    let command = argv(&["echo", "a", "b"]);
+    assert!(check_command_limits_with(&command, 1024, 3).is_ok());
+    match check_command_limits_with(&command, 1024, 2) {
+        Err(CodexErr::TooManyArgs { count, limit }) => assert_eq!((count, limit), (3, 2)),
+        other => panic!("expected TooManyArgs, got {other:?}"),
+    }
+}
+
+#[test]
+fn byte_limit_is_inclusive() {
+    let command = argv(&["echo", "a", "b"]);
+    assert!(check_command_limits_with(&command, 9, 1024).is_ok());
+    match check_command_limits_with(&command, 8, 1024) {
+        Err(CodexErr::CommandTooLong { bytes, limit }) => assert_eq!((bytes, limit), (9, 8)),
+        other => panic!("expected CommandTooLong, got {other:?}"),
+    }
+}
+
+#[test]
+fn oversized_commands_are_rejected_before_approval() {
+    let command = vec!["x".to_string(); 5000];
+    let check = assess_command_safety_with(
+        &command,
+        AskForApproval::OnFailure,
+        &SandboxPolicy::new_full_auto_policy(),
+        &HashSet::new(),
+        Some(SandboxType::None),
+    );
+    assert!(matches!(check, SafetyCheck::Reject { .. }));
+}
+
+#[tokio::test]
+async fn exec_refuses_commands_over_the_limit() {
+    let params = ExecParams {
+        command: std::iter::once("/bin/echo".to_string())
+            .chain(std::iter::repeat_n("x".to_string(), 5000))
+            .collect(),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let result = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "low",
+        &[],
+        &common::exec_config(),
+    )
+    .await;
+    assert!(matches!(result, Err(CodexErr::TooManyArgs { .. })));
+}
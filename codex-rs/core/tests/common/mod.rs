@@ -0,0 +1,18 @@
+#![allow(clippy::expect_used)]
+
+use std::sync::LazyLock;
+
+use codex_core::exec::ExecConfig;
+use tempfile::TempDir;
+
+/// Holds the templated output of every exec in this test binary.
+static OUTPUT_DIR: LazyLock<TempDir> = LazyLock::new(|| TempDir::new().expect("tempdir"));
+
+/// [`ExecConfig::default`], except that templated output is written to a
+/// temporary directory rather than into the source tree's `scripts/`.
+pub fn exec_config() -> ExecConfig {
+    ExecConfig {
+        templated_output: OUTPUT_DIR.path().join("templated_output.txt"),
+        ..ExecConfig::default()
+    }
+}
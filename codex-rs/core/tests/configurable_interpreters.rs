@@ -0,0 +1,53 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_core::exec::{
+    StdioPolicy, add_interpreter, interpreters_with_extra, is_interpreter, set_interpreters,
+    spawn_command_under_api_with,
+};
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+#[cfg(unix)]
+#[tokio::test]
+async fn configured_interpreter_is_spawned_locally() {
+    set_interpreters(interpreters_with_extra("deno, Lua"));
+    assert!(is_interpreter("deno"));
+    assert!(is_interpreter("/usr/bin/lua"));
+    assert!(is_interpreter("bash"));
+
+    // A stand-in `ruby` so the test does not depend on a real install.
+    use std::os::unix::fs::PermissionsExt;
+    let dir = TempDir::new().expect("tempdir");
+    let ruby = dir.path().join("ruby");
+    std::fs::write(&ruby, "#!/bin/sh\necho ran locally\n").expect("write script");
+    std::fs::set_permissions(&ruby, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let ruby = ruby.to_string_lossy().to_string();
+
+    assert!(!is_interpreter(&ruby));
+    add_interpreter("ruby");
+    assert!(is_interpreter(&ruby));
+
+    let output = spawn_command_under_api_with(
+        vec![ruby],
+        &SandboxPolicy::new_full_auto_policy(),
+        PathBuf::from("."),
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+        Some(5_000),
+        None,
+        &common::exec_config(),
+    )
+    .await
+    .expect("spawn under api failed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("ran locally"), "stdout: {stdout}");
+    assert!(!stdout.contains("would run"), "stdout: {stdout}");
+
+    set_interpreters(vec!["ruby".to_string()]);
+    assert!(is_interpreter("ruby"));
+    assert!(!is_interpreter("bash"));
+}
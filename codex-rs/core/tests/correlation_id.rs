@@ -0,0 +1,62 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecConfig, ExecParams, ExecToolCallOutput, SandboxType, process_exec_tool_call_with_config};
+use codex_core::exec_transcript::ExecTranscriptEntry;
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+use tokio::sync::Notify;
+
+async fn run(correlation_id: Option<&str>, config: &ExecConfig) -> ExecToolCallOutput {
+    let params = ExecParams {
+        command: vec!["/bin/echo".to_string(), "tagged".to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: correlation_id.map(str::to_string),
+    };
+    process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "low",
+        &[],
+        config,
+    )
+    .await
+    .expect("exec failed")
+}
+
+#[tokio::test]
+async fn correlation_id_reaches_output_and_transcript() {
+    let dir = TempDir::new().expect("tempdir");
+    let transcript = dir.path().join("transcript.jsonl");
+    let config = ExecConfig {
+        transcript: Some(transcript.clone()),
+        ..common::exec_config()
+    };
+
+    let provided = run(Some("call-42"), &config).await;
+    assert_eq!(provided.correlation_id, "call-42");
+
+    let generated = run(None, &config).await;
+    assert!(!generated.correlation_id.is_empty());
+    assert_ne!(generated.correlation_id, "call-42");
+    assert_ne!(run(None, &config).await.correlation_id, generated.correlation_id);
+
+    let contents = std::fs::read_to_string(&transcript).expect("read transcript");
+    let entries: Vec<ExecTranscriptEntry> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("parse transcript line"))
+        .collect();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].correlation_id, "call-42");
+    // The generated id is the same one the transcript recorded for the call.
+    assert_eq!(entries[1].correlation_id, generated.correlation_id);
+}
@@ -0,0 +1,56 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::exec::{ExecParams, SandboxType, default_timeout_for, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+#[test]
+fn each_sandbox_type_has_its_default() {
+    assert_eq!(default_timeout_for(SandboxType::Api), Duration::from_secs(60));
+    assert_eq!(default_timeout_for(SandboxType::Internal), Duration::from_secs(1));
+    for local in [
+        SandboxType::None,
+        SandboxType::BlackBox,
+        SandboxType::MacosSeatbelt,
+        SandboxType::LinuxSeccomp,
+        SandboxType::Win64Cmd,
+        SandboxType::Win64Ps,
+    ] {
+        assert_eq!(default_timeout_for(local), Duration::from_secs(10), "{local}");
+    }
+}
+
+#[tokio::test]
+async fn explicit_timeout_overrides_the_default() {
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), "sleep 5".to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: Some(200),
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let start = Instant::now();
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+
+    // Killed after 200ms rather than running for 5s or the 10s default.
+    assert_ne!(output.exit_code, 0);
+    assert!(start.elapsed() < Duration::from_secs(3));
+}
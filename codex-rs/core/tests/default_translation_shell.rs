@@ -0,0 +1,16 @@
+use codex_core::exec::{default_translation_shell, windows_translation_shell};
+
+#[test]
+fn windows_fallback_follows_the_detected_shell() {
+    assert_eq!(windows_translation_shell("powershell"), "powershell");
+    assert_eq!(windows_translation_shell("wsl"), "wsl");
+    assert_eq!(windows_translation_shell("bash for windows"), "wsl");
+    assert_eq!(windows_translation_shell("cmd"), "windows");
+    assert_eq!(windows_translation_shell("unknown shell"), "windows");
+}
+
+#[cfg(not(windows))]
+#[test]
+fn other_platforms_fall_back_to_the_os() {
+    assert_eq!(default_translation_shell(), std::env::consts::OS);
+}
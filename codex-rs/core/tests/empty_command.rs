@@ -0,0 +1,41 @@
+mod common;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::error::CodexErr;
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn empty_command_is_rejected_with_invalid_input() {
+    let params = ExecParams {
+        command: Vec::new(),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let result = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await;
+
+    match result {
+        Err(CodexErr::Io(err)) => {
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(err.to_string(), "command args are empty");
+        }
+        other => panic!("expected InvalidInput error, got {other:?}"),
+    }
+}
@@ -0,0 +1,61 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::exec_env::{reset_env_hook, set_env_hook};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+async fn child_env(env: HashMap<String, String>) -> Vec<String> {
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), "env".to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env,
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+    output.stdout.lines().map(String::from).collect()
+}
+
+#[tokio::test]
+async fn denylisted_variables_are_stripped_and_hook_is_replaceable() {
+    // `LD_PRELOAD` is on the default denylist; an empty value preloads nothing
+    // should it reach the child.
+    let env = HashMap::from([
+        ("LD_PRELOAD".to_string(), String::new()),
+        ("CODEX_TEST_KEPT".to_string(), "1".to_string()),
+    ]);
+
+    let lines = child_env(env.clone()).await;
+    assert!(lines.contains(&"CODEX_TEST_KEPT=1".to_string()));
+    assert!(!lines.iter().any(|line| line.starts_with("LD_PRELOAD=")));
+
+    // A custom hook replaces the default denylist.
+    set_env_hook(Arc::new(|mut env| {
+        env.insert("TERM".to_string(), "dumb".to_string());
+        env
+    }));
+    let lines = child_env(env.clone()).await;
+    reset_env_hook();
+    assert!(lines.contains(&"TERM=dumb".to_string()));
+    assert!(lines.contains(&"LD_PRELOAD=".to_string()));
+
+    let lines = child_env(env).await;
+    assert!(!lines.iter().any(|line| line.starts_with("LD_PRELOAD=")));
+}
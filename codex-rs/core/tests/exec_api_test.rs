@@ -1,8 +1,11 @@
+#![allow(clippy::expect_used)]
+mod common;
+
 use std::collections::HashMap;
 use std::path::PathBuf;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::exec::{
-    spawn_command_under_api, StdioPolicy, API_HANDSHAKE_FAILURE,
+    spawn_command_under_api_with, StdioPolicy, API_HANDSHAKE_FAILURE,
 };
 
 #[tokio::test]
@@ -17,30 +20,49 @@ async fn test_spawn_command_under_api() {
     let stdio_policy = StdioPolicy::RedirectForShellTool;
     let env = HashMap::new();
 
-    // Update expectations for spawn_command_under_api
-    match spawn_command_under_api(command, &sandbox_policy, cwd, stdio_policy, env, None).await {
-        Ok((child, translation_result)) => {
-            let exit_status = child.await.unwrap();
-            assert_eq!(exit_status.code(), Some(API_HANDSHAKE_FAILURE));
-            let stdout = String::from_utf8_lossy(&translation_result.stdout);
-            assert!(stdout.contains("Hello, World!"), "Unexpected output: {}", stdout);
-        }
-        Err(e) => {
-            panic!("Failed to spawn command under API: {}", e);
-        }
-    }
+    // Interpreters run locally; without a broker the handshake failure is
+    // reported through the exit status.
+    let output = spawn_command_under_api_with(
+        command,
+        &sandbox_policy,
+        cwd,
+        stdio_policy,
+        env,
+        None,
+        None,
+        &common::exec_config(),
+    )
+    .await
+    .expect("spawn under api failed");
+
+    assert_eq!(output.exit_status.code(), Some(API_HANDSHAKE_FAILURE));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Hello, World!"), "Unexpected output: {}", stdout);
+    assert!(stdout.contains("No response on the API"), "Unexpected output: {}", stdout);
 }
 
 #[tokio::test]
 async fn test_spawn_command_under_api_no_handshake() {
-    // Command is not an interpreter so no process is spawned.
-    let command = vec!["nonexistent".to_string()];
+    // Command is not an interpreter so no process is spawned. It must exist
+    // on the PATH, otherwise it is reported as not found instead.
+    let command = vec!["ls".to_string()];
     let sandbox_policy = SandboxPolicy::new_full_auto_policy();
     let cwd = PathBuf::from(".");
     let stdio_policy = StdioPolicy::RedirectForShellTool;
     let env = HashMap::new();
 
-    let output = spawn_command_under_api(command, &sandbox_policy, cwd, stdio_policy, env, Some(100)).await.expect("spawn under api failed");
+    let output = spawn_command_under_api_with(
+        command,
+        &sandbox_policy,
+        cwd,
+        stdio_policy,
+        env,
+        Some(100),
+        None,
+        &common::exec_config(),
+    )
+    .await
+    .expect("spawn under api failed");
 
     assert_eq!(output.exit_status.code(), Some(API_HANDSHAKE_FAILURE));
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -0,0 +1,78 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_events};
+use codex_core::exec_events::ExecEvent;
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn events_arrive_started_then_chunks_then_finished() {
+    let command = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "echo out; echo err >&2".to_string(),
+    ];
+    let params = ExecParams {
+        command: command.clone(),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let (tx, mut rx) = mpsc::channel(64);
+    let output = process_exec_tool_call_with_events(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        Some(tx),
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+    assert_eq!(output.exit_code, 0);
+
+    let mut events = Vec::new();
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+
+    assert_eq!(
+        events.first(),
+        Some(&ExecEvent::Started {
+            argv: command,
+            sandbox: "none".to_string(),
+        })
+    );
+    assert!(matches!(events.last(), Some(ExecEvent::Finished { code: 0, .. })));
+
+    let chunks = &events[1..events.len() - 1];
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks.contains(&ExecEvent::Stdout {
+        chunk: "out\n".to_string()
+    }));
+    assert!(chunks.contains(&ExecEvent::Stderr {
+        chunk: "err\n".to_string()
+    }));
+}
+
+#[test]
+fn events_serialize_as_tagged_json() {
+    let event = ExecEvent::Finished {
+        code: 1,
+        duration_ms: 5,
+    };
+    assert_eq!(
+        serde_json::to_value(&event).expect("serialize"),
+        serde_json::json!({"type": "finished", "code": 1, "duration_ms": 5})
+    );
+}
@@ -0,0 +1,48 @@
+#![allow(clippy::expect_used)]
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use codex_core::error::CodexErr;
+use codex_core::exec::ExecParams;
+
+#[test]
+fn unset_fields_take_defaults() {
+    let params = ExecParams::builder()
+        .command(vec!["ls".to_string(), "-l".to_string()])
+        .build()
+        .expect("command is set");
+
+    assert_eq!(params.command, vec!["ls".to_string(), "-l".to_string()]);
+    assert_eq!(params.cwd, std::env::current_dir().expect("cwd"));
+    assert_eq!(params.timeout_ms, None);
+    assert!(params.env.is_empty());
+    assert_eq!(params.correlation_id, None);
+}
+
+#[test]
+fn set_fields_are_kept() {
+    let env = HashMap::from([("FOO".to_string(), "bar".to_string())]);
+    let params = ExecParams::builder()
+        .command(vec!["echo".to_string()])
+        .cwd(PathBuf::from("/tmp"))
+        .timeout_ms(Some(1_500))
+        .env(env.clone())
+        .correlation_id("call-1")
+        .build()
+        .expect("command is set");
+
+    assert_eq!(params.cwd, PathBuf::from("/tmp"));
+    assert_eq!(params.timeout_ms, Some(1_500));
+    assert_eq!(params.env, env);
+    assert_eq!(params.correlation_id.as_deref(), Some("call-1"));
+}
+
+#[test]
+fn empty_command_fails_to_build() {
+    let result = ExecParams::builder().cwd(PathBuf::from("/tmp")).build();
+    match result {
+        Err(CodexErr::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::InvalidInput),
+        other => panic!("expected InvalidInput error, got {other:?}"),
+    }
+}
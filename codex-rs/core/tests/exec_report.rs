@@ -0,0 +1,63 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::exec_report::ExecReport;
+use codex_core::protocol::SandboxPolicy;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::Output;
+use codex_execpolicy::PolicyParser;
+use serde_json::json;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn checked_exec_output_serializes_as_one_document() {
+    let policy = PolicyParser::new(
+        "exec_report",
+        r#"define_program(program="echo", args=["hello"])"#,
+    )
+    .parse()
+    .expect("failed to parse policy");
+    let verdict = Output::from(policy.check(&ExecCall::new("echo", &["hello"])));
+
+    let params = ExecParams {
+        command: vec!["echo".to_string(), "hello".to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: Some("report-1".to_string()),
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+
+    let stdout = output.stdout.clone();
+    assert!(stdout.contains("hello\n"));
+    let report = ExecReport::from(output);
+    let unchecked = serde_json::to_value(&report).expect("serialize");
+    assert_eq!(unchecked["exit_code"], json!(0));
+    assert_eq!(unchecked["stdout"], json!(stdout));
+    assert_eq!(unchecked["stderr"], json!(""));
+    assert_eq!(unchecked["correlation_id"], json!("report-1"));
+    assert!(unchecked["duration_ms"].is_u64());
+    assert!(unchecked.get("policy").is_none());
+
+    let checked = serde_json::to_value(report.with_policy(verdict)).expect("serialize");
+    assert_eq!(checked["exit_code"], json!(0));
+    assert_eq!(checked["stdout"], json!(stdout));
+    assert_eq!(checked["policy"]["result"], json!("match"));
+    assert_eq!(checked["policy"]["match"]["program"], json!("echo"));
+}
@@ -0,0 +1,53 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use codex_core::exec::{ExecConfig, ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::{Notify, Semaphore};
+
+fn sleep_params() -> ExecParams {
+    ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), "sleep 0.4".to_string()],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())]),
+        correlation_id: None,
+    }
+}
+
+async fn run_sleep(config: &ExecConfig) {
+    let output = process_exec_tool_call_with_config(
+        sleep_params(),
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        config,
+    )
+    .await
+    .expect("exec failed");
+    assert_eq!(output.exit_code, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn execs_beyond_permit_count_run_one_at_a_time() {
+    let config = ExecConfig {
+        permits: Arc::new(Semaphore::new(1)),
+        ..common::exec_config()
+    };
+
+    let start = Instant::now();
+    tokio::join!(run_sleep(&config), run_sleep(&config), run_sleep(&config));
+    let elapsed = start.elapsed();
+
+    // Three 0.4s sleeps through a single permit cannot overlap.
+    assert!(elapsed >= Duration::from_millis(1200), "finished in {elapsed:?}");
+    assert_eq!(config.permits.available_permits(), 1);
+}
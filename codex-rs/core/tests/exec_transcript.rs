@@ -0,0 +1,76 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecConfig, ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::exec_transcript::ExecTranscriptEntry;
+use codex_core::protocol::SandboxPolicy;
+use codex_execpolicy::{DEFAULT_WATCHER, PolicyWatcher, ThreatLevel};
+use tempfile::TempDir;
+use tokio::sync::Notify;
+
+const RISK_CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption
+";
+
+async fn run(command: &[&str], config: &ExecConfig) {
+    let params = ExecParams {
+        command: command.iter().map(|s| s.to_string()).collect(),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "low",
+        &[],
+        config,
+    )
+    .await
+    .expect("exec failed");
+}
+
+#[tokio::test]
+async fn each_exec_appends_one_json_line() {
+    let dir = TempDir::new().expect("tempdir");
+    let transcript = dir.path().join("transcript.jsonl");
+    let policy = dir.path().join("policy.star");
+    let risk_csv = dir.path().join("risk.csv");
+    std::fs::write(
+        &policy,
+        r#"define_program(program="ls", args=[ARG_RFILES_OR_CWD])"#,
+    )
+    .expect("write policy");
+    std::fs::write(&risk_csv, RISK_CSV).expect("write risk csv");
+    let watcher = PolicyWatcher::new(policy)
+        .expect("load policy")
+        .with_risk_csv(risk_csv);
+    assert!(DEFAULT_WATCHER.set(watcher).is_ok());
+    let config = ExecConfig {
+        transcript: Some(transcript.clone()),
+        ..common::exec_config()
+    };
+
+    run(&["/bin/echo", "one"], &config).await;
+    run(&["codex_list_docs"], &config).await;
+
+    let contents = std::fs::read_to_string(&transcript).expect("read transcript");
+    let entries: Vec<ExecTranscriptEntry> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("parse transcript line"))
+        .collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].argv, vec!["/bin/echo", "one"]);
+    assert_eq!(entries[0].sandbox_type, "none");
+    assert_eq!(entries[0].exit_code, Some(0));
+    assert_eq!(entries[0].threat_level, Some(ThreatLevel::Low));
+    assert_eq!(entries[0].threat_info, "low");
+    assert_eq!(entries[1].sandbox_type, "internal");
+}
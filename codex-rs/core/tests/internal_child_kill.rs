@@ -0,0 +1,41 @@
+#![allow(clippy::expect_used)]
+use std::time::{Duration, Instant};
+
+use codex_core::utils::child_ext::{BlackBoxChild, ChildLike, InternalChild};
+
+#[tokio::test]
+async fn killed_internal_child_reports_killed_status() {
+    let mut child = BlackBoxChild::Internal(InternalChild::new("out".to_string(), String::new()));
+    child.start_kill().expect("kill");
+    let status = child.wait_future().await.expect("wait");
+    assert!(!status.success());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(9));
+    }
+}
+
+#[tokio::test]
+async fn internal_child_exits_with_configured_code() {
+    let mut child = BlackBoxChild::Internal(
+        InternalChild::new(String::new(), "boom".to_string()).with_exit_code(3),
+    );
+    let status = child.wait_future().await.expect("wait");
+    assert_eq!(status.code(), Some(3));
+
+    // Killing after it has exited does not change the status.
+    child.start_kill().expect("kill");
+    assert_eq!(child.wait_future().await.expect("wait").code(), Some(3));
+}
+
+#[tokio::test]
+async fn deadline_kills_long_running_internal_child() {
+    let start = Instant::now();
+    let mut child = InternalChild::new(String::new(), String::new())
+        .with_run_time(Duration::from_secs(30))
+        .with_deadline(start + Duration::from_millis(50));
+    let status = child.wait_future().await.expect("wait");
+    assert!(!status.success());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
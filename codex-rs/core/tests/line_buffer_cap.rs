@@ -0,0 +1,46 @@
+#![allow(clippy::expect_used)]
+use codex_core::exec::read_line_capped;
+use tokio::io::BufReader;
+
+const CAP: usize = 64 * 1024;
+
+#[tokio::test]
+async fn long_line_without_newline_is_split_at_cap() {
+    let input = vec![b'#'; 5_000_000];
+    let mut reader = BufReader::new(input.as_slice());
+    let mut buffer = Vec::new();
+    let mut total = 0;
+    let mut reads = 0;
+    loop {
+        let n = read_line_capped(&mut reader, &mut buffer, CAP)
+            .await
+            .expect("read failed");
+        if n == 0 {
+            break;
+        }
+        assert!(buffer.len() <= CAP, "line buffer grew to {}", buffer.len());
+        assert!(buffer.capacity() <= 2 * CAP, "line buffer reserved {}", buffer.capacity());
+        total += n;
+        reads += 1;
+        buffer.clear();
+    }
+    assert_eq!(total, 5_000_000);
+    assert_eq!(reads, 5_000_000_usize.div_ceil(CAP));
+}
+
+#[tokio::test]
+async fn newline_still_ends_a_read() {
+    let mut reader = BufReader::new(&b"one\ntwo"[..]);
+    let mut buffer = Vec::new();
+
+    assert_eq!(read_line_capped(&mut reader, &mut buffer, CAP).await.expect("read"), 4);
+    assert_eq!(buffer, b"one\n");
+    buffer.clear();
+    assert_eq!(read_line_capped(&mut reader, &mut buffer, 2).await.expect("read"), 2);
+    assert_eq!(buffer, b"tw");
+    buffer.clear();
+    assert_eq!(read_line_capped(&mut reader, &mut buffer, 2).await.expect("read"), 1);
+    assert_eq!(buffer, b"o");
+    buffer.clear();
+    assert_eq!(read_line_capped(&mut reader, &mut buffer, 2).await.expect("read"), 0);
+}
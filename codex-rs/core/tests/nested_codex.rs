@@ -0,0 +1,121 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::error::CodexErr;
+use codex_core::config_types::NestedCodexMode;
+use codex_core::exec::{
+    CODEX_NESTED_ENV_VAR, ExecConfig, ExecParams, SandboxType, is_nested_codex_invocation,
+    process_exec_tool_call_with_config,
+};
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+use tokio::sync::Notify;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+async fn run(command: Vec<String>) -> codex_core::error::Result<String> {
+    let params = ExecParams {
+        command,
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await?;
+    Ok(output.stdout)
+}
+
+#[test]
+fn codex_binaries_are_flagged_as_nested() {
+    assert!(is_nested_codex_invocation(&argv(&["codex", "exec", "ls"])));
+    assert!(is_nested_codex_invocation(&argv(&["/usr/local/bin/codex-execpolicy", "check"])));
+    assert!(is_nested_codex_invocation(&argv(&["codex.exe"])));
+
+    let current = std::env::current_exe().expect("current exe");
+    assert!(is_nested_codex_invocation(&[current.to_string_lossy().to_string()]));
+
+    assert!(is_nested_codex_invocation(&argv(&["/bin/sh", "-c", "cd src && codex exec ls"])));
+    assert!(is_nested_codex_invocation(&argv(&["bash", "-c", "FOO=1 './codex' --help"])));
+
+    assert!(!is_nested_codex_invocation(&argv(&["codex.sh"])));
+    assert!(!is_nested_codex_invocation(&argv(&["/bin/sh", "-c", "echo codex"])));
+    assert!(!is_nested_codex_invocation(&argv(&["codex-linux-sandbox", "--", "ls"])));
+    assert!(!is_nested_codex_invocation(&argv(&["ls", "codex"])));
+    assert!(!is_nested_codex_invocation(&[]));
+}
+
+#[tokio::test]
+async fn nested_invocation_is_rejected_by_default() {
+    match run(argv(&["codex", "exec", "ls"])).await {
+        Err(CodexErr::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::PermissionDenied),
+        other => panic!("expected PermissionDenied, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn children_carry_the_nested_marker() {
+    let stdout = run(argv(&["/bin/sh", "-c", "env"])).await.expect("exec failed");
+    assert!(stdout.lines().any(|line| line == format!("{CODEX_NESTED_ENV_VAR}=1")));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn flattened_nested_invocation_keeps_the_requested_sandbox() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().expect("tempdir");
+    let fake_codex = dir.path().join("codex");
+    let script = "#!/bin/sh\necho \"nested=$CODEX_NESTED\"\necho hi > written.txt\n";
+    std::fs::write(&fake_codex, script).expect("write fake codex");
+    std::fs::set_permissions(&fake_codex, std::fs::Permissions::from_mode(0o755))
+        .expect("chmod fake codex");
+
+    let params = ExecParams {
+        command: vec![fake_codex.to_string_lossy().to_string(), "exec".to_string()],
+        cwd: dir.path().to_path_buf(),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let config = ExecConfig {
+        nested_invocation: NestedCodexMode::Flatten,
+        ..common::exec_config()
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::BlackBox,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &config,
+    )
+    .await
+    .expect("nested exec failed");
+
+    assert!(output.stdout.contains("nested=1"), "stdout: {}", output.stdout);
+    // Still under the black box: the write to the read-only cwd is flagged.
+    assert!(
+        output.stderr.contains("modified the read-only working directory"),
+        "stderr: {}",
+        output.stderr
+    );
+}
@@ -0,0 +1,117 @@
+#![allow(clippy::expect_used)]
+use std::path::Path;
+
+use codex_core::exec::{create_linux_sandbox_command_args, create_seatbelt_command_args};
+use codex_core::protocol::{SandboxPermission, SandboxPolicy};
+
+fn seatbelt_policy(policy: &SandboxPolicy) -> String {
+    let args = create_seatbelt_command_args(vec!["true".to_string()], policy, Path::new("/tmp"));
+    args[1].clone()
+}
+
+#[test]
+fn allowlist_scopes_outbound_to_listed_hosts() {
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::NetworkOutboundHost {
+            host: "api.openai.com:443".to_string(),
+        },
+    ]);
+    assert_eq!(policy.network_allowlist(), vec!["api.openai.com:443".to_string()]);
+
+    let seatbelt = seatbelt_policy(&policy);
+    // Seatbelt only accepts `*` or `localhost` as the remote host.
+    assert!(seatbelt.contains(r#"(allow network-outbound (remote ip "*:443"))"#));
+    assert!(!seatbelt.contains("api.openai.com"));
+    assert!(!seatbelt.contains("(allow network-outbound)"));
+    assert!(!seatbelt.contains("(allow network-inbound)"));
+
+    let linux = create_linux_sandbox_command_args(vec!["true".to_string()], &policy, Path::new("/tmp"));
+    assert!(linux.contains(&"network-outbound-host=api.openai.com:443".to_string()));
+    assert!(!linux.contains(&"network-full-access".to_string()));
+}
+
+#[test]
+fn allowlist_entries_become_port_scoped_rules() {
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::NetworkOutboundHost {
+            host: "example.com".to_string(),
+        },
+        SandboxPermission::NetworkOutboundHost {
+            host: "localhost:8080".to_string(),
+        },
+        SandboxPermission::NetworkOutboundHost {
+            host: "github.com:443".to_string(),
+        },
+        SandboxPermission::NetworkOutboundHost {
+            host: "api.openai.com:443".to_string(),
+        },
+        SandboxPermission::NetworkOutboundHost {
+            host: "bad:port".to_string(),
+        },
+    ]);
+    let seatbelt = seatbelt_policy(&policy);
+    let remotes: Vec<&str> = seatbelt
+        .lines()
+        .filter(|line| line.contains("(remote ip "))
+        .collect();
+    assert_eq!(
+        remotes,
+        vec![
+            r#"(allow network-outbound (remote ip "*:443"))"#,
+            r#"(allow network-outbound (remote ip "localhost:8080"))"#,
+        ]
+    );
+}
+
+#[test]
+fn host_only_entries_do_not_open_all_outbound_traffic() {
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::NetworkOutboundHost {
+            host: "example.com".to_string(),
+        },
+        SandboxPermission::NetworkOutboundHost {
+            host: "example.com:*".to_string(),
+        },
+    ]);
+    let seatbelt = seatbelt_policy(&policy);
+    assert!(!seatbelt.contains("*:*"), "{seatbelt}");
+    assert!(!seatbelt.contains("(remote ip"), "{seatbelt}");
+    assert!(!seatbelt.contains("(allow network-outbound)"), "{seatbelt}");
+}
+
+/// `sandbox-exec` refuses a profile with any invalid rule, so run it for real.
+#[cfg(target_os = "macos")]
+#[test]
+fn allowlist_profile_is_accepted_by_sandbox_exec() {
+    let policy = SandboxPolicy::from(vec![
+        SandboxPermission::DiskFullReadAccess,
+        SandboxPermission::NetworkOutboundHost {
+            host: "api.openai.com:443".to_string(),
+        },
+        SandboxPermission::NetworkOutboundHost {
+            host: "example.com".to_string(),
+        },
+    ]);
+    let cwd = std::env::current_dir().expect("cwd");
+    let args = create_seatbelt_command_args(vec!["/usr/bin/true".to_string()], &policy, &cwd);
+    let output = std::process::Command::new("/usr/bin/sandbox-exec")
+        .args(&args)
+        .output()
+        .expect("run sandbox-exec");
+    assert!(
+        output.status.success(),
+        "sandbox-exec rejected the profile: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn empty_allowlist_keeps_blanket_rules() {
+    let seatbelt = seatbelt_policy(&SandboxPolicy::full_jailbreak());
+    assert!(seatbelt.contains("(allow network-outbound)"));
+    assert!(!seatbelt.contains("(remote ip"));
+
+    let seatbelt = seatbelt_policy(&SandboxPolicy::new_read_only_policy());
+    assert!(!seatbelt.contains("network-outbound"));
+}
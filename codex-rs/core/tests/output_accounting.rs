@@ -0,0 +1,45 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn output_beyond_cap_reports_truncation_and_totals() {
+    // 20,000 bytes on stderr is well past the 10KiB per-stream cap.
+    let params = ExecParams {
+        command: vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            "head -c 20000 /dev/zero >&2".to_string(),
+        ],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())]),
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stderr_truncated);
+    assert_eq!(output.total_stderr_bytes, 20_000);
+    assert_eq!(output.stderr.len(), 10 * 1024);
+    assert!(!output.stdout_truncated);
+    assert_eq!(output.total_stdout_bytes, 0);
+}
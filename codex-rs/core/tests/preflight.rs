@@ -0,0 +1,61 @@
+#![allow(clippy::expect_used)]
+use std::fs;
+
+use codex_core::preflight::preflight;
+use codex_execpolicy::{Output, PolicyWatcher, ThreatLevel};
+use tempfile::TempDir;
+
+const RISK_CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption,CmdMacOS,CmdLinux,CmdWinCmd,CmdWinPs,CmdWinWsl,api,blackbox
+linux,ls,-l,0,0,0,0,0,ls,ls,dir,Get-ChildItem,ls,none,none
+linux,rm,-r,5,1,5,2,3,rm,rm,del,del,rm,none,none
+";
+
+const POLICY: &str = r#"
+define_program(program="ls", options=[flag("-l")], args=[ARG_RFILES_OR_CWD], system_path=["/bin/ls"])
+define_program(program="rm", options=[flag("-r")], args=[ARG_RFILES], forbidden="rm is never allowed")
+"#;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn preflight_reports_translation_threat_and_verdict() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    let risk_csv = dir.path().join("risk.csv");
+    fs::write(&policy, POLICY)?;
+    fs::write(&risk_csv, RISK_CSV)?;
+    let watcher = PolicyWatcher::new(policy)?.with_risk_csv(risk_csv);
+
+    let report = preflight(&argv(&["ls", "-l"]), Some("linux"), &watcher);
+    assert_eq!(report.translated_argv, argv(&["ls", "-l"]));
+    assert_eq!(report.threat_level, ThreatLevel::Low);
+    assert!(matches!(report.policy_verdict, Output::Match { .. }));
+
+    let report = preflight(&argv(&["rm", "-r", "build"]), Some("linux"), &watcher);
+    assert_eq!(report.threat_level, ThreatLevel::High);
+    match report.policy_verdict {
+        Output::Forbidden { reason, .. } => assert_eq!(reason, "rm is never allowed"),
+        other => panic!("expected a forbidden verdict, got {other:?}"),
+    }
+
+    // Nothing is executed.
+    let marker = dir.path().join("marker");
+    let report = preflight(&argv(&["touch", &marker.to_string_lossy()]), Some("linux"), &watcher);
+    assert!(matches!(report.policy_verdict, Output::Unverified { .. }));
+    assert!(!marker.exists());
+    Ok(())
+}
+
+#[test]
+fn missing_risk_csv_is_treated_as_blocked() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(&policy, POLICY)?;
+    let watcher = PolicyWatcher::new(policy)?.with_risk_csv(dir.path().join("missing.csv"));
+
+    let report = preflight(&argv(&["ls", "-l"]), Some("linux"), &watcher);
+    assert_eq!(report.threat_level, ThreatLevel::High);
+    Ok(())
+}
@@ -0,0 +1,73 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::error::CodexErr;
+use codex_core::exec::{
+    ExecConfig, ExecParams, ExecToolCallOutput, SandboxType, process_exec_tool_call_with_config,
+};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+use translation::initialize;
+
+async fn run_on_foreign_shell(
+    command: &[&str],
+    config: &ExecConfig,
+) -> codex_core::error::Result<ExecToolCallOutput> {
+    let foreign = if cfg!(windows) { "linux" } else { "windows" };
+    initialize(foreign);
+
+    let params = ExecParams {
+        command: command.iter().map(|s| s.to_string()).collect(),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        config,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn untranslated_command_on_foreign_shell_runs_unchanged_by_default() {
+    let config = ExecConfig {
+        require_translation: false,
+        ..common::exec_config()
+    };
+    let output = run_on_foreign_shell(&["/bin/sh", "-c", "echo as-given"], &config)
+        .await
+        .expect("exec failed");
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("as-given\n"), "{}", output.stdout);
+}
+
+#[tokio::test]
+async fn untranslated_command_on_foreign_shell_is_rejected() {
+    let config = ExecConfig {
+        require_translation: true,
+        ..common::exec_config()
+    };
+    let result = run_on_foreign_shell(&["codex-no-such-tool", "--flag"], &config).await;
+
+    match result {
+        Err(CodexErr::Io(err)) => {
+            assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+            assert!(err.to_string().contains("codex-no-such-tool"), "{err}");
+        }
+        other => panic!("expected Unsupported error, got {other:?}"),
+    }
+}
@@ -0,0 +1,39 @@
+use codex_core::exec::{
+    SandboxStateGuard, SandboxType, current_sandbox_capabilities, disable_api_sandbox,
+    disable_black_box_sandbox, enable_api_sandbox, enable_black_box_sandbox,
+};
+use codex_core::protocol::SandboxPolicy;
+
+#[test]
+fn read_only_policy_under_each_detected_sandbox() {
+    let _guard = SandboxStateGuard::capture();
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    disable_api_sandbox();
+    disable_black_box_sandbox();
+    let native = current_sandbox_capabilities(&policy);
+    if cfg!(target_os = "linux") {
+        assert_eq!(native.sandbox_type, SandboxType::LinuxSeccomp);
+    } else if cfg!(target_os = "macos") {
+        assert_eq!(native.sandbox_type, SandboxType::MacosSeatbelt);
+    }
+    if native.sandbox_type != SandboxType::BlackBox {
+        assert!(!native.can_write_cwd);
+        assert!(native.can_read_all);
+        assert!(!native.network_allowed);
+    }
+
+    enable_api_sandbox();
+    let api = current_sandbox_capabilities(&policy);
+    assert_eq!(api.sandbox_type, SandboxType::Api);
+    assert!(!api.can_write_cwd);
+    assert!(api.can_read_all);
+    assert!(!api.network_allowed);
+
+    disable_api_sandbox();
+    enable_black_box_sandbox();
+    let black_box = current_sandbox_capabilities(&policy);
+    assert_eq!(black_box.sandbox_type, SandboxType::BlackBox);
+    assert!(black_box.can_write_cwd);
+    assert!(black_box.network_allowed);
+}
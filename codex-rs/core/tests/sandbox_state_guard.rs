@@ -0,0 +1,23 @@
+use codex_core::exec::{
+    SandboxStateGuard, enable_api_sandbox, is_api_sandbox_enabled, restore_sandbox_state,
+    snapshot_sandbox_state,
+};
+
+#[test]
+fn guard_restores_previous_state() {
+    let before = snapshot_sandbox_state();
+    assert!(!before.api_enabled);
+
+    {
+        let _guard = SandboxStateGuard::capture();
+        enable_api_sandbox();
+        assert!(is_api_sandbox_enabled());
+    }
+
+    assert!(!is_api_sandbox_enabled());
+    assert_eq!(snapshot_sandbox_state(), before);
+
+    enable_api_sandbox();
+    restore_sandbox_state(before);
+    assert!(!is_api_sandbox_enabled());
+}
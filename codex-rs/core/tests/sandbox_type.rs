@@ -0,0 +1,83 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{
+    ExecParams, SandboxStateGuard, SandboxType, enable_black_box_sandbox, process_exec_tool_call_with_config,
+    resolve_sandbox_type,
+};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn internal_commands_resolve_to_internal_and_binaries_to_black_box() {
+    assert_eq!(
+        resolve_sandbox_type(&argv(&["codex_list_docs"]), SandboxType::None),
+        SandboxType::Internal
+    );
+    assert_eq!(resolve_sandbox_type(&argv(&["ls", "-l"]), SandboxType::None), SandboxType::None);
+
+    let (black_box, internal) = {
+        let _guard = SandboxStateGuard::capture();
+        enable_black_box_sandbox();
+        (
+            resolve_sandbox_type(&argv(&["ls", "-l"]), SandboxType::None),
+            resolve_sandbox_type(&argv(&["codex_list_docs"]), SandboxType::None),
+        )
+    };
+
+    assert_eq!(black_box, SandboxType::BlackBox);
+    assert_eq!(internal, SandboxType::Internal);
+}
+
+#[tokio::test]
+async fn internal_command_runs_without_spawning() {
+    let params = ExecParams {
+        command: argv(&["codex_list_docs"]),
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::new(),
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("internal exec failed");
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("risk_csv.csv"), "unexpected stdout: {}", output.stdout);
+}
+
+#[test]
+fn sandbox_type_names_round_trip() {
+    let names = [
+        (SandboxType::None, "none"),
+        (SandboxType::BlackBox, "black-box"),
+        (SandboxType::MacosSeatbelt, "seatbelt"),
+        (SandboxType::LinuxSeccomp, "linux-seccomp"),
+        (SandboxType::Win64Cmd, "win64-cmd"),
+        (SandboxType::Win64Ps, "win64-ps"),
+        (SandboxType::Api, "api"),
+        (SandboxType::Internal, "internal"),
+    ];
+    for (sandbox_type, name) in names {
+        assert_eq!(sandbox_type.to_string(), name);
+        assert_eq!(name.parse::<SandboxType>(), Ok(sandbox_type));
+    }
+    assert!("macos-seatbelt".parse::<SandboxType>().is_err());
+}
@@ -0,0 +1,45 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn leaked_stderr_pipe_does_not_block_completion() {
+    // The backgrounded `sleep` inherits stderr and keeps it open long after
+    // the shell itself has exited.
+    let params = ExecParams {
+        command: vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            "sleep 30 >/dev/null & echo done >&2".to_string(),
+        ],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())]),
+        correlation_id: None,
+    };
+    let start = Instant::now();
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+
+    assert!(start.elapsed() < Duration::from_secs(5), "took {:?}", start.elapsed());
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stderr, "done\n");
+}
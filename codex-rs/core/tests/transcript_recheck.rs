@@ -0,0 +1,73 @@
+#![allow(clippy::expect_used)]
+use std::fs;
+
+use codex_core::exec_transcript::{ExecTranscriptEntry, recheck_transcript, replay_transcript};
+use codex_execpolicy::{Output, PolicyWatcher, ThreatLevel};
+use tempfile::TempDir;
+
+const RISK_CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption,CmdMacOS,CmdLinux,CmdWinCmd,CmdWinPs,CmdWinWsl,api,blackbox
+";
+
+const POLICY: &str = r#"
+define_program(program="ls", options=[flag("-l")], args=[ARG_RFILES_OR_CWD], system_path=["/bin/ls"])
+define_program(program="rm", options=[flag("-r")], args=[ARG_RFILES], forbidden="rm is no longer allowed")
+"#;
+
+fn entry(argv: &[&str], translated_argv: &[&str]) -> ExecTranscriptEntry {
+    ExecTranscriptEntry {
+        ts: 1,
+        argv: argv.iter().map(|s| s.to_string()).collect(),
+        translated_argv: translated_argv.iter().map(|s| s.to_string()).collect(),
+        sandbox_type: "none".to_string(),
+        exit_code: Some(0),
+        duration_ms: 3,
+        threat_level: Some(ThreatLevel::Low),
+        threat_info: String::new(),
+        error: None,
+        correlation_id: String::new(),
+    }
+}
+
+#[test]
+fn recheck_flags_commands_the_current_policy_forbids() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let transcript = dir.path().join("transcript.jsonl");
+    let lines = [
+        entry(&["ls", "-l"], &["ls", "-l"]),
+        entry(&["rm", "-r", "build"], &[]),
+    ]
+    .iter()
+    .map(|entry| serde_json::to_string(entry).expect("serialise entry"))
+    .collect::<Vec<_>>()
+    .join("\n\n");
+    fs::write(&transcript, lines)?;
+
+    let policy = dir.path().join("policy.star");
+    let risk_csv = dir.path().join("risk.csv");
+    fs::write(&policy, POLICY)?;
+    fs::write(&risk_csv, RISK_CSV)?;
+    let watcher = PolicyWatcher::new(policy)?.with_risk_csv(risk_csv);
+
+    let entries = replay_transcript(&transcript)?;
+    assert_eq!(entries.len(), 2);
+    let rechecks = recheck_transcript(entries, &watcher, Some("linux"));
+
+    assert!(!rechecks[0].is_forbidden());
+    assert!(matches!(rechecks[0].policy_verdict, Output::Match { .. }));
+    assert!(rechecks[1].is_forbidden());
+    assert_eq!(rechecks[1].entry.argv, vec!["rm", "-r", "build"]);
+    Ok(())
+}
+
+#[test]
+fn replay_reports_the_malformed_line() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let transcript = dir.path().join("transcript.jsonl");
+    let valid = serde_json::to_string(&entry(&["ls"], &["ls"]))?;
+    fs::write(&transcript, format!("{valid}\nnot json\n"))?;
+
+    let err = replay_transcript(&transcript).expect_err("malformed line");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains(":2:"));
+    Ok(())
+}
@@ -0,0 +1,59 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use codex_core::exec::{ExecConfig, ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+use translation::{DEFAULT_TRANSLATOR, initialize};
+
+#[tokio::test]
+async fn disabled_translation_runs_command_unchanged() {
+    let os = std::env::consts::OS;
+    initialize(os);
+    let command = vec!["echo".to_string(), "untranslated".to_string()];
+    {
+        let mut translator = DEFAULT_TRANSLATOR
+            .get()
+            .expect("translator initialized")
+            .lock()
+            .expect("lock translator");
+        translator.add_translation("echo", HashMap::from([(os.to_string(), "false".to_string())]));
+        let translated = translator.translate_argv(&command, Path::new("."), os, "N/A", &[]);
+        assert_eq!(translated.argv[0], "false", "echo should normally be translated");
+    }
+
+    let params = ExecParams {
+        command,
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())]),
+        correlation_id: None,
+    };
+    let config = ExecConfig {
+        disable_translation: true,
+        ..common::exec_config()
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &config,
+    )
+    .await
+    .expect("exec failed");
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("untranslated"), "stdout: {}", output.stdout);
+    let result = output.translation_result.expect("translation result");
+    assert_eq!(result.original_command, "echo");
+    assert!(result.translated_command.is_none());
+    assert!(result.informational_output.contains("Translation disabled"));
+}
@@ -0,0 +1,45 @@
+#![allow(clippy::expect_used)]
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::{ExecParams, SandboxType, process_exec_tool_call_with_config};
+use codex_core::protocol::SandboxPolicy;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn truncation_does_not_split_multibyte_characters() {
+    // 10,239 ASCII bytes put the 10KiB cap in the middle of the first "é".
+    let params = ExecParams {
+        command: vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            "head -c 10239 /dev/zero | tr '\\000' a >&2; printf '\\303\\251\\303\\251\\303\\251' >&2"
+                .to_string(),
+        ],
+        cwd: PathBuf::from("."),
+        timeout_ms: None,
+        env: HashMap::from([("PATH".to_string(), "/usr/bin:/bin".to_string())]),
+        correlation_id: None,
+    };
+    let output = process_exec_tool_call_with_config(
+        params,
+        SandboxType::None,
+        Arc::new(Notify::new()),
+        &SandboxPolicy::new_read_only_policy(),
+        &None,
+        "N/A",
+        &[],
+        &common::exec_config(),
+    )
+    .await
+    .expect("exec failed");
+
+    assert!(output.stderr_truncated);
+    assert_eq!(output.total_stderr_bytes, 10_239 + 6);
+    assert!(!output.stderr.contains('\u{FFFD}'), "replacement character at the seam");
+    assert_eq!(output.stderr.len(), 10_239);
+    assert!(output.stderr.chars().all(|c| c == 'a'));
+}
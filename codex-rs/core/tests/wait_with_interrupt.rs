@@ -0,0 +1,63 @@
+#![allow(clippy::expect_used)]
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::exec::wait_child_with_interrupt;
+use tokio::process::Command;
+use tokio::sync::Notify;
+
+fn spawn_sleep(script: &str) -> tokio::process::Child {
+    Command::new("/bin/sh")
+        .args(["-c", script])
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("spawn child")
+}
+
+#[tokio::test]
+async fn child_is_killed_after_the_grace_period_once_notified() {
+    let mut child = spawn_sleep("exec sleep 30");
+    let ctrl_c = Notify::new();
+    // Fired before waiting starts: the permit is kept, like an early Ctrl-C.
+    ctrl_c.notify_one();
+
+    let start = Instant::now();
+    let status = wait_child_with_interrupt(&mut child, &ctrl_c, Duration::from_millis(200))
+        .await
+        .expect("wait child");
+    let elapsed = start.elapsed();
+
+    assert!(!status.success());
+    assert!(elapsed >= Duration::from_millis(200));
+    assert!(elapsed < Duration::from_secs(5));
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(9));
+    }
+}
+
+#[tokio::test]
+async fn child_exiting_within_the_grace_period_keeps_its_status() {
+    let mut child = spawn_sleep("sleep 0.2; exit 3");
+    let ctrl_c = Notify::new();
+    ctrl_c.notify_one();
+
+    let status = wait_child_with_interrupt(&mut child, &ctrl_c, Duration::from_secs(5))
+        .await
+        .expect("wait child");
+    assert_eq!(status.code(), Some(3));
+}
+
+#[tokio::test]
+async fn child_runs_to_completion_without_a_notification() {
+    let mut child = spawn_sleep("exit 0");
+    let ctrl_c = Notify::new();
+
+    let status = wait_child_with_interrupt(&mut child, &ctrl_c, Duration::from_millis(10))
+        .await
+        .expect("wait child");
+    assert!(status.success());
+}
@@ -0,0 +1,85 @@
+#![allow(clippy::expect_used)]
+use std::path::Path;
+use std::sync::LazyLock;
+
+use codex_core::exec::SandboxType;
+use codex_core::exec::windows_sandbox_wrapper_path;
+use tempfile::TempDir;
+
+/// Every test in this binary shares one empty scripts dir, so the env var is
+/// only written once.
+static SCRIPTS_DIR: LazyLock<TempDir> = LazyLock::new(|| {
+    let dir = TempDir::new().expect("tempdir");
+    unsafe { std::env::set_var("CODEX_SCRIPTS_DIR", dir.path()) };
+    dir
+});
+
+fn scripts_dir() -> &'static Path {
+    SCRIPTS_DIR.path()
+}
+
+#[test]
+fn wrapper_paths_resolve_against_scripts_dir() {
+    let dir = scripts_dir();
+
+    assert_eq!(
+        windows_sandbox_wrapper_path(SandboxType::Win64Cmd),
+        Some(dir.join("win64_cmd_restricted.bat"))
+    );
+    assert_eq!(
+        windows_sandbox_wrapper_path(SandboxType::Win64Ps),
+        Some(dir.join("win64_ps_restricted.ps1"))
+    );
+    assert_eq!(windows_sandbox_wrapper_path(SandboxType::BlackBox), None);
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn missing_wrapper_is_a_sandbox_error() {
+    use std::collections::HashMap;
+
+    use codex_core::error::CodexErr;
+    use codex_core::error::SandboxErr;
+    use codex_core::exec::StdioPolicy;
+    use codex_core::exec::spawn_command_under_win64_cmd;
+    use codex_core::exec::spawn_command_under_win64_ps;
+    use codex_core::protocol::SandboxPolicy;
+
+    let dir = scripts_dir();
+    let policy = SandboxPolicy::new_read_only_policy();
+    let cwd = std::env::current_dir().expect("cwd");
+
+    let err = spawn_command_under_win64_cmd(
+        vec!["echo".to_string(), "hi".to_string()],
+        &policy,
+        cwd.clone(),
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+        None,
+    )
+    .await
+    .expect_err("wrapper is missing");
+    match err {
+        CodexErr::Sandbox(SandboxErr::WrapperMissing(path)) => {
+            assert_eq!(path, dir.join("win64_cmd_restricted.bat"));
+        }
+        other => panic!("expected WrapperMissing, got {other:?}"),
+    }
+
+    let err = spawn_command_under_win64_ps(
+        vec!["echo".to_string(), "hi".to_string()],
+        &policy,
+        cwd,
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+        None,
+    )
+    .await
+    .expect_err("wrapper is missing");
+    match err {
+        CodexErr::Sandbox(SandboxErr::WrapperMissing(path)) => {
+            assert_eq!(path, dir.join("win64_ps_restricted.ps1"));
+        }
+        other => panic!("expected WrapperMissing, got {other:?}"),
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::ArgType;
+use crate::ExecCall;
+use crate::MatchedArg;
+use crate::ValidExec;
+
+/// Plain list of trusted command prefixes, checked without going through
+/// starlark.
+///
+/// Each non-blank line of the source is a whitespace-separated `program` or
+/// `program arg...` prefix; lines starting with `#` are comments. A call is
+/// allowed when its program equals the entry's program exactly (no `$PATH`
+/// lookup) and its arguments start with the entry's arguments.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AllowList {
+    entries: Vec<Vec<String>>,
+}
+
+impl AllowList {
+    pub fn parse(source: &str) -> Self {
+        let entries = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.split_whitespace().map(str::to_string).collect())
+            .collect();
+        Self { entries }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Ok(Self::parse(&source))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn is_allowed(&self, exec_call: &ExecCall) -> bool {
+        self.matching_entry(exec_call).is_some()
+    }
+
+    /// The [`ValidExec`] reported for an allow-listed call: arguments covered
+    /// by the matching entry are literals, anything after them is
+    /// [`ArgType::Unknown`]. `None` when the call is not allowed.
+    pub fn matched_exec(&self, exec_call: &ExecCall) -> Option<ValidExec> {
+        let entry = self.matching_entry(exec_call)?;
+        let prefix_len = entry.len() - 1;
+        let args = exec_call
+            .args
+            .iter()
+            .enumerate()
+            .map(|(index, value)| MatchedArg {
+                index,
+                r#type: if index < prefix_len {
+                    ArgType::Literal(value.clone())
+                } else {
+                    ArgType::Unknown
+                },
+                value: value.clone(),
+            })
+            .collect();
+        Some(ValidExec::new(&exec_call.program, args, &[]))
+    }
+
+    fn matching_entry(&self, exec_call: &ExecCall) -> Option<&Vec<String>> {
+        self.entries.iter().find(|entry| {
+            let Some((program, prefix)) = entry.split_first() else {
+                return false;
+            };
+            *program == exec_call.program && exec_call.args.starts_with(prefix)
+        })
+    }
+}
@@ -2,6 +2,7 @@ use serde::Serialize;
 
 use crate::arg_matcher::ArgMatcher;
 use crate::arg_matcher::ArgMatcherCardinality;
+use crate::arg_type::ArgType;
 use crate::error::Error;
 use crate::error::Result;
 use crate::valid_exec::MatchedArg;
@@ -52,11 +53,7 @@ pub fn resolve_observed_args_with_patterns(
                 message: "expected exact cardinality".to_string(),
             })?;
         for positional_arg in &prefix[prefix_arg_index..prefix_arg_index + n] {
-            let matched_arg = MatchedArg::new(
-                positional_arg.index,
-                pattern.arg_type(),
-                &positional_arg.value.clone(),
-            )?;
+            let matched_arg = match_positional_arg(program, positional_arg, pattern.arg_type())?;
             matched_args.push(matched_arg);
         }
         prefix_arg_index += n;
@@ -91,22 +88,14 @@ pub fn resolve_observed_args_with_patterns(
                     });
                 } else {
                     for positional_arg in vararg {
-                        let matched_arg = MatchedArg::new(
-                            positional_arg.index,
-                            pattern.arg_type(),
-                            &positional_arg.value.clone(),
-                        )?;
+                        let matched_arg = match_positional_arg(program, positional_arg, pattern.arg_type())?;
                         matched_args.push(matched_arg);
                     }
                 }
             }
             ArgMatcherCardinality::ZeroOrMore => {
                 for positional_arg in vararg {
-                    let matched_arg = MatchedArg::new(
-                        positional_arg.index,
-                        pattern.arg_type(),
-                        &positional_arg.value.clone(),
-                    )?;
+                    let matched_arg = match_positional_arg(program, positional_arg, pattern.arg_type())?;
                     matched_args.push(matched_arg);
                 }
             }
@@ -123,11 +112,7 @@ pub fn resolve_observed_args_with_patterns(
                 message: "expected exact cardinality".to_string(),
             })?;
         for positional_arg in &suffix[suffix_arg_index..suffix_arg_index + n] {
-            let matched_arg = MatchedArg::new(
-                positional_arg.index,
-                pattern.arg_type(),
-                &positional_arg.value.clone(),
-            )?;
+            let matched_arg = match_positional_arg(program, positional_arg, pattern.arg_type())?;
             matched_args.push(matched_arg);
         }
         suffix_arg_index += n;
@@ -187,6 +172,27 @@ fn partition_args(program: &str, arg_patterns: &Vec<ArgMatcher>) -> Result<Parit
     Ok(partitioned_args)
 }
 
+/// Validate `positional_arg` against `arg_type`, reporting a failure as
+/// [`Error::InvalidArg`] so callers know which argument was rejected.
+fn match_positional_arg(
+    program: &str,
+    positional_arg: &PositionalArg,
+    arg_type: ArgType,
+) -> Result<MatchedArg> {
+    MatchedArg::new(positional_arg.index, arg_type.clone(), &positional_arg.value).map_err(|cause| {
+        Error::InvalidArg {
+            program: program.to_string(),
+            index: positional_arg.index,
+            value: positional_arg.value.clone(),
+            message: format!(
+                "argument {} ({:?}) is not a valid {:?}",
+                positional_arg.index, positional_arg.value, arg_type
+            ),
+            cause: Box::new(cause),
+        }
+    })
+}
+
 fn get_range_checked<T>(vec: &[T], range: std::ops::Range<usize>) -> Result<&[T]> {
     if range.start > range.end {
         Err(Error::RangeStartExceedsEnd {
@@ -0,0 +1,66 @@
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::ArgType;
+use crate::ValidExec;
+
+/// Where a command would run, for rules that depend on the paths it touches.
+///
+/// With no `cwd` the context is empty and path rules are skipped, which is
+/// what [`Policy::check`](crate::Policy::check) uses.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CheckContext {
+    pub cwd: Option<PathBuf>,
+    /// Folders that writeable file arguments must stay inside. Relative roots
+    /// are resolved against `cwd`.
+    pub writable_roots: Vec<PathBuf>,
+}
+
+impl CheckContext {
+    pub fn new(cwd: PathBuf, writable_roots: Vec<PathBuf>) -> Self {
+        Self {
+            cwd: Some(cwd),
+            writable_roots,
+        }
+    }
+
+    /// Whether `path` (relative paths are taken from `cwd`) is inside any of
+    /// the writable roots. Always `true` for an empty context.
+    pub fn is_writable(&self, path: &Path) -> bool {
+        let Some(cwd) = &self.cwd else {
+            return true;
+        };
+        let abs = normalize_path(&cwd.join(path));
+        self.writable_roots
+            .iter()
+            .any(|root| abs.starts_with(normalize_path(&cwd.join(root))))
+    }
+
+    /// Return the first writeable file argument of `exec` that escapes the
+    /// writable roots, if any.
+    pub(crate) fn first_unwritable_path<'a>(&self, exec: &'a ValidExec) -> Option<&'a str> {
+        let args = exec.args.iter().map(|arg| (&arg.r#type, &arg.value));
+        let opts = exec.opts.iter().map(|opt| (&opt.r#type, &opt.value));
+        args.chain(opts)
+            .filter(|(arg_type, _)| matches!(arg_type, ArgType::WriteableFile))
+            .map(|(_, value)| value.as_str())
+            .find(|value| !self.is_writable(Path::new(value)))
+    }
+}
+
+/// Normalize a path by removing `.` and resolving `..` without touching the
+/// filesystem (works even if the file does not exist).
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => { /* skip */ }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
@@ -88,6 +88,15 @@ pub enum Error {
     CannotCheckRelativePath {
         file: PathBuf,
     },
+    /// A positional argument was rejected by its `ArgType`. `cause` is the
+    /// underlying validation error.
+    InvalidArg {
+        program: String,
+        index: usize,
+        value: String,
+        message: String,
+        cause: Box<Error>,
+    },
     CannotCanonicalizePath {
         file: String,
         #[serde_as(as = "DisplayFromStr")]
@@ -3,31 +3,45 @@
 #[macro_use]
 extern crate starlark;
 
+mod allow_list;
 mod arg_matcher;
 mod arg_resolver;
 mod arg_type;
+mod check_context;
 mod error;
 mod exec_call;
 mod execv_checker;
 mod opt;
+mod output;
 mod policy;
 mod policy_parser;
 pub mod policy_watcher;
 pub mod threat_state;
 mod program;
 mod sed_command;
+mod tokens;
 mod valid_exec;
+pub mod watch_registry;
 
+pub use allow_list::AllowList;
 pub use arg_matcher::ArgMatcher;
 pub use arg_resolver::PositionalArg;
 pub use arg_type::ArgType;
+pub use check_context::CheckContext;
+pub use check_context::normalize_path;
 pub use error::Error;
 pub use error::Result;
 pub use exec_call::ExecCall;
 pub use execv_checker::ExecvChecker;
 pub use opt::Opt;
+pub use output::Output;
+pub use policy::ForbiddenProgramSummary;
 pub use policy::Policy;
+pub use policy::PolicyDiff;
+pub use policy::PolicySummary;
 pub use policy_parser::PolicyParser;
+pub use policy_parser::PolicyValidationError;
+pub use policy_parser::validate_policy;
 pub use policy_watcher::PolicyWatcher;
 pub use threat_state::{ThreatLevel, ThreatState, ThreatStateWatcher};
 pub use program::Forbidden;
@@ -35,11 +49,15 @@ pub use program::MatchedExec;
 pub use program::NegativeExamplePassedCheck;
 pub use program::PositiveExampleFailedCheck;
 pub use program::ProgramSpec;
+pub use program::ProgramSpecSummary;
 pub use sed_command::parse_sed_command;
 pub use valid_exec::MatchedArg;
 pub use valid_exec::MatchedFlag;
 pub use valid_exec::MatchedOpt;
 pub use valid_exec::ValidExec;
+pub use tokens::estimate_tokens;
+pub use watch_registry::WatchRegistry;
+pub use watch_registry::watched_paths;
 
 use once_cell::sync::OnceCell;
 
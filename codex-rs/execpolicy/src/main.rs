@@ -1,15 +1,24 @@
 use anyhow::Result;
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
 use codex_execpolicy::{ExecCall, ExecArg as LibExecArg};
 use codex_execpolicy::MatchedExec;
+use codex_execpolicy::Output;
+use codex_execpolicy::estimate_tokens;
 use codex_execpolicy::Policy;
 use codex_execpolicy::PolicyParser;
 use codex_execpolicy::ValidExec;
+use codex_execpolicy::ThreatLevel;
+use codex_execpolicy::policy_watcher::{
+    command_risk_score, risk_csv_path, risk_threshold, threat_level_from_tree,
+};
+use codex_execpolicy::threat_state::{load_risk_tree, load_risk_tree_cached};
 use serde::Deserialize;
 use serde::Serialize;
 use serde::de;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
@@ -25,7 +34,6 @@ const OVERSIGHT_DENIAL_EXIT_CODE: i32 = 15;
 
 const TOKENS_PER_MINUTE: usize = 30_000;
 const REQUESTS_PER_MINUTE: usize = 500;
-const RISK_THRESHOLD: usize = 100; // Example threshold, adjust as needed
 
 #[derive(Parser, Deserialize, Debug)]
 #[command(version, about, long_about = None)]
@@ -35,14 +43,53 @@ pub struct Args {
     #[clap(long)]
     pub require_safe: bool,
 
-    /// Path to the policy file.
+    /// Path to the policy file, or `-` to read the policy from stdin.
     #[clap(long, short = 'p')]
     pub policy: Option<PathBuf>,
 
+    /// Skip the risk prefilter and go straight to the policy check. Intended
+    /// for debugging policies while the risk database would reject everything.
+    #[clap(long)]
+    pub no_prefilter: bool,
+
+    /// Average risk score above which the prefilter rejects commands.
+    /// Defaults to `CODEX_RISK_THRESHOLD`, or 0.5 when that is unset.
+    #[clap(long)]
+    pub risk_threshold: Option<f64>,
+
+    /// Assess the command's risk as if it ran on this environment (e.g.
+    /// `linux`, `windows`, `win64cmd`) instead of the host OS. The threat
+    /// level is added to the JSON output.
+    #[clap(long)]
+    pub env: Option<String>,
+
+    /// Risk database consulted by the prefilter and for `--env`; defaults to
+    /// the bundled one.
+    #[clap(long)]
+    pub risk_csv: Option<PathBuf>,
+
+    /// How `check` and `check-json` print their verdict. `json` (compact)
+    /// is meant for scripts, `pretty` and `text` for people.
+    #[clap(long, value_enum, default_value_t = OutputFormat::Json)]
+    #[serde(default)]
+    pub format: OutputFormat,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Single-line JSON.
+    #[default]
+    Json,
+    /// Indented JSON.
+    Pretty,
+    /// One human-readable line, e.g. `FORBIDDEN: cp -r a b — reason`.
+    Text,
+}
+
 #[derive(Clone, Debug, Deserialize, Subcommand)]
 pub enum Command {
     /// Checks the command as if the arguments were the inputs to execv(3).
@@ -58,11 +105,29 @@ pub enum Command {
         #[serde(deserialize_with = "deserialize_from_json")]
         exec: MainExecArg,
     },
+
+    /// Prints the parsed policy's program specs and forbidden patterns as JSON.
+    #[clap(name = "print-policy")]
+    PrintPolicy,
 }
 
-fn prefilter_command(_exec: &LibExecArg) -> bool {
-    let risk_score = current_risk_score();
-    if risk_score > RISK_THRESHOLD {
+/// Reject `exec` when its flags score above `threshold` in the risk database
+/// at `risk_csv`. An unreadable database lets every command through.
+fn prefilter_command(
+    exec: &LibExecArg,
+    threshold: f64,
+    risk_csv: &Path,
+    env: Option<&str>,
+) -> bool {
+    let Ok(tree) = load_risk_tree_cached(risk_csv) else {
+        return true;
+    };
+    let call = ExecCall {
+        program: exec.program.clone(),
+        args: exec.args.clone(),
+    };
+    let risk_score = command_risk_score(&tree, &call, env);
+    if risk_score > threshold {
         eprintln!("Command rejected by prefilter: risk score too high");
         return false;
     }
@@ -74,6 +139,12 @@ fn main() -> Result<()> {
 
     let args = Args::parse();
     let policy = match args.policy {
+        Some(policy) if policy.as_os_str() == "-" => {
+            let mut unparsed_policy = String::new();
+            std::io::stdin().read_to_string(&mut unparsed_policy)?;
+            let parser = PolicyParser::new("<stdin>", &unparsed_policy);
+            parser.parse()
+        }
         Some(policy) => {
             let policy_source = policy.to_string_lossy().to_string();
             let unparsed_policy = std::fs::read_to_string(policy)?;
@@ -96,21 +167,48 @@ fn main() -> Result<()> {
             }
         },
         Command::CheckJson { exec } => exec.0, // Unwrap the newtype
+        Command::PrintPolicy => {
+            println!("{}", serde_json::to_string_pretty(&policy.summary())?);
+            return Ok(());
+        }
     };
 
-    if !prefilter_command(&exec) {
+    let threshold = args.risk_threshold.unwrap_or_else(risk_threshold);
+    let risk_csv = args.risk_csv.unwrap_or_else(risk_csv_path);
+    if !args.no_prefilter && !prefilter_command(&exec, threshold, &risk_csv, args.env.as_deref()) {
         std::process::exit(FORBIDDEN_EXIT_CODE);
     }
 
+    let command_line = std::iter::once(exec.program.as_str())
+        .chain(exec.args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let threat_level = match &args.env {
+        Some(env) => {
+            let tree = load_risk_tree(&risk_csv)?;
+            Some(threat_level_from_tree(&tree, &command_line, Some(env)))
+        }
+        None => None,
+    };
+
     let (output, exit_code) = check_command(&policy, exec, args.require_safe);
-    let json = serde_json::to_string(&output)?;
-    println!("{}", json);
+    let report = Report {
+        output,
+        env: args.env,
+        threat_level,
+    };
+    let rendered = match args.format {
+        OutputFormat::Json => serde_json::to_string(&report)?,
+        OutputFormat::Pretty => serde_json::to_string_pretty(&report)?,
+        OutputFormat::Text => report.to_text(&command_line),
+    };
+    println!("{}", rendered);
     std::process::exit(exit_code);
 }
 
 lazy_static! {
     static ref LAST_EXECUTION: Mutex<Instant> = Mutex::new(Instant::now());
-    static ref EXECUTION_TIMES: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+    static ref EXECUTION_TIMES: Mutex<VecDeque<(Instant, usize)>> = Mutex::new(VecDeque::new());
     static ref REQUEST_COUNT: Mutex<usize> = Mutex::new(0);
 }
 
@@ -127,33 +225,35 @@ fn enforce_rate_limit(mode: RateLimitMode, used: usize) {
     }
 }
 
-fn enforce_rate_limit_internal(_used: usize, per_minute: usize, per_day: usize) {
+fn enforce_rate_limit_internal(used: usize, per_minute: usize, per_day: usize) {
     let mut execution_times = EXECUTION_TIMES.lock().unwrap();
     let now = Instant::now();
 
     // Remove outdated entries (older than 1 minute or 1 day)
     let one_minute_ago = now - Duration::from_secs(60);
     let one_day_ago = now - Duration::from_secs(86400);
-    execution_times.retain(|&time| time >= one_day_ago);
+    execution_times.retain(|&(time, _)| time >= one_day_ago);
 
     // Calculate usage in the last minute and day
-    let last_minute_usage = execution_times
+    let last_minute_usage: usize = execution_times
         .iter()
-        .filter(|&&time| time >= one_minute_ago)
-        .count();
-    let last_day_usage = execution_times.len();
+        .filter(|&&(time, _)| time >= one_minute_ago)
+        .map(|&(_, amount)| amount)
+        .sum();
+    let last_day_usage: usize = execution_times.iter().map(|&(_, amount)| amount).sum();
 
     // Determine the required delay to stay within limits
     let mut required_delay = Duration::ZERO;
-    if last_minute_usage >= per_minute {
-        let oldest_in_minute = execution_times
+    if last_minute_usage + used > per_minute
+        && let Some((oldest_in_minute, _)) = execution_times
             .iter()
-            .find(|&&time| time >= one_minute_ago)
-            .unwrap();
+            .find(|&&(time, _)| time >= one_minute_ago)
+    {
         required_delay = (*oldest_in_minute + Duration::from_secs(60)) - now;
     }
-    if last_day_usage >= per_day {
-        let oldest_in_day = execution_times.front().unwrap();
+    if last_day_usage + used > per_day
+        && let Some((oldest_in_day, _)) = execution_times.front()
+    {
         required_delay = required_delay.max((*oldest_in_day + Duration::from_secs(86400)) - now);
     }
 
@@ -162,8 +262,8 @@ fn enforce_rate_limit_internal(_used: usize, per_minute: usize, per_day: usize)
         std::thread::sleep(required_delay);
     }
 
-    // Record the current execution time
-    execution_times.push_back(now);
+    // Record the current execution and how much of the budget it used
+    execution_times.push_back((now, used));
 }
 
 fn track_request_count() {
@@ -180,16 +280,15 @@ fn check_command(
     // Track the number of requests
     track_request_count();
 
-    // Example usage and limits (to be replaced with actual logic)
-    let mode = RateLimitMode::Requests; // Example: switch to RateLimitMode::Tokens if needed
-    let used = 1; // Placeholder for usage estimation logic
+    let exec_call = ExecCall { program: lib_exec_arg.program, args: lib_exec_arg.args };
+
+    let mode = RateLimitMode::Tokens;
+    let used = estimate_tokens(&exec_call.to_string());
 
 
     // Enforce rate limit before proceeding
     enforce_rate_limit(mode, used);
 
-    let exec_call = ExecCall { program: lib_exec_arg.program, args: lib_exec_arg.args };
-
     // Call policy.check as normal
     match policy.check(&exec_call) {
         Ok(MatchedExec::Match { exec }) => {
@@ -214,32 +313,34 @@ fn check_command(
         }
     }
 }
+/// What gets printed for `check`/`check-json`: the policy verdict, plus the
+/// threat assessment when `--env` was given.
 #[derive(Debug, Serialize)]
-#[serde(tag = "result")]
-pub enum Output {
-    /// The command is verified as safe.
-    #[serde(rename = "safe")]
-    Safe { r#match: ValidExec },
-
-    /// The command has matched a rule in the policy, but the caller should
-    /// decide whether it is "safe" given the files it wants to write.
-    #[serde(rename = "match")]
-    Match { r#match: ValidExec },
-
-    /// The user is forbidden from running the command.
-    #[serde(rename = "forbidden")]
-    Forbidden {
-        reason: String,
-        cause: codex_execpolicy::Forbidden,
-    },
-
-    /// The command is overridden by policy, requiring oversight.
-    #[serde(rename = "overridden")]
-    Overridden { reason: String },
+pub struct Report {
+    #[serde(flatten)]
+    pub output: Output,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threat_level: Option<ThreatLevel>,
+}
 
-    /// The safety of the command could not be verified.
-    #[serde(rename = "unverified")]
-    Unverified { error: codex_execpolicy::Error },
+impl Report {
+    /// One-line summary for `--format text`, naming the verdict, the checked
+    /// command and the reason where there is one.
+    fn to_text(&self, command_line: &str) -> String {
+        let mut line = match &self.output {
+            Output::Safe { .. } => format!("SAFE: {command_line}"),
+            Output::Match { .. } => format!("MATCH: {command_line}"),
+            Output::Forbidden { reason, .. } => format!("FORBIDDEN: {command_line} — {reason}"),
+            Output::Overridden { reason } => format!("OVERRIDDEN: {command_line} — {reason}"),
+            Output::Unverified { error } => format!("UNVERIFIED: {command_line} — {error:?}"),
+        };
+        if let (Some(env), Some(threat_level)) = (&self.env, &self.threat_level) {
+            line.push_str(&format!(" (threat on {env}: {threat_level:?})"));
+        }
+        line
+    }
 }
 
 // Newtype wrapper for ExecArg to satisfy orphan rules for FromStr
@@ -264,11 +365,3 @@ where
         .map_err(|e| serde::de::Error::custom(format!("JSON parse error: {e}")))?;
     Ok(MainExecArg(lib_exec_arg))
 }
-
-
-
-fn current_risk_score() -> usize {
-    // Placeholder for actual risk assessment logic
-    // This function should return a risk score based on the command and environment
-    0
-}
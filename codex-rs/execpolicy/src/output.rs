@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+use crate::Error;
+use crate::Forbidden;
+use crate::MatchedExec;
+use crate::Result;
+use crate::ValidExec;
+
+/// Verdict printed by `codex-execpolicy check`, one variant per outcome.
+#[derive(Debug, Serialize)]
+#[serde(tag = "result")]
+pub enum Output {
+    /// The command is verified as safe.
+    #[serde(rename = "safe")]
+    Safe { r#match: ValidExec },
+
+    /// The command has matched a rule in the policy, but the caller should
+    /// decide whether it is "safe" given the files it wants to write.
+    #[serde(rename = "match")]
+    Match { r#match: ValidExec },
+
+    /// The user is forbidden from running the command.
+    #[serde(rename = "forbidden")]
+    Forbidden { reason: String, cause: Forbidden },
+
+    /// The command is overridden by policy, requiring oversight.
+    #[serde(rename = "overridden")]
+    Overridden { reason: String },
+
+    /// The safety of the command could not be verified.
+    #[serde(rename = "unverified")]
+    Unverified { error: Error },
+}
+
+impl From<Result<MatchedExec>> for Output {
+    /// The verdict for the result of [`Policy::check`](crate::Policy::check).
+    fn from(result: Result<MatchedExec>) -> Self {
+        match result {
+            Ok(MatchedExec::Match { exec }) => Output::Match { r#match: exec },
+            Ok(MatchedExec::Overridden { reason }) => Output::Overridden { reason },
+            Ok(MatchedExec::Forbidden { reason, cause }) => Output::Forbidden { reason, cause },
+            Err(error) => Output::Unverified { error },
+        }
+    }
+}
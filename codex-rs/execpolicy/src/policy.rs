@@ -1,8 +1,13 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
 use multimap::MultiMap;
+use serde::Serialize;
 use regex_lite::Error as RegexError;
 use regex_lite::Regex;
 
 use crate::ExecCall;
+use crate::check_context::CheckContext;
 use crate::Forbidden;
 use crate::MatchedExec;
 use crate::NegativeExamplePassedCheck;
@@ -11,12 +16,66 @@ use crate::error::Error;
 use crate::error::Result;
 use crate::policy_parser::ForbiddenProgramRegex;
 use crate::program::PositiveExampleFailedCheck;
+use crate::program::ProgramSpecSummary;
+use crate::program::starlark_list;
+use crate::program::starlark_string;
+
+/// Serializable view of a [`Policy`], as printed by `codex-execpolicy print-policy`.
+#[derive(Clone, Debug, Serialize)]
+pub struct PolicySummary {
+    /// One entry per `define_program`, sorted by program name.
+    pub programs: Vec<ProgramSpecSummary>,
+    pub forbidden_programs: Vec<ForbiddenProgramSummary>,
+    /// Combined regex built from `forbid_substrings`, if any.
+    pub forbidden_substrings_pattern: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ForbiddenProgramSummary {
+    pub regex: String,
+    pub reason: String,
+}
+
+/// Program-level changes between two policies, see [`Policy::diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct PolicyDiff {
+    pub added_programs: Vec<String>,
+    pub removed_programs: Vec<String>,
+    /// Programs defined in both policies whose specs differ.
+    pub changed_programs: Vec<String>,
+}
+
+impl PolicyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_programs.is_empty()
+            && self.removed_programs.is_empty()
+            && self.changed_programs.is_empty()
+    }
+}
+
+impl fmt::Display for PolicyDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .added_programs
+            .iter()
+            .map(|p| format!("{p} added"))
+            .chain(self.removed_programs.iter().map(|p| format!("{p} removed")))
+            .chain(self.changed_programs.iter().map(|p| format!("{p} changed")))
+            .collect();
+        if entries.is_empty() {
+            write!(f, "no program changes")
+        } else {
+            write!(f, "{}", entries.join(", "))
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Policy {
     programs: MultiMap<String, ProgramSpec>,
     forbidden_program_regexes: Vec<ForbiddenProgramRegex>,
     forbidden_substrings_pattern: Option<Regex>,
+    forbidden_substrings: Vec<String>,
 }
 
 impl Policy {
@@ -39,14 +98,45 @@ impl Policy {
             programs,
             forbidden_program_regexes,
             forbidden_substrings_pattern,
+            forbidden_substrings,
         })
     }
 
     pub fn check(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
+        self.check_with_context(exec_call, &CheckContext::default())
+    }
+
+    /// Like [`Policy::check`], but path arguments are also checked against
+    /// the working directory and writable roots in `ctx`.
+    pub fn check_with_context(&self, exec_call: &ExecCall, ctx: &CheckContext) -> Result<MatchedExec> {
+        if let Some(forbidden) = self.check_forbidden(exec_call) {
+            return Ok(forbidden);
+        }
+
+        let program = &exec_call.program;
+        let mut last_err = Err(Error::NoSpecForProgram {
+            program: program.clone(),
+        });
+        if let Some(spec_list) = self.programs.get_vec(program) {
+            for spec in spec_list {
+                match spec.check_with_context(exec_call, ctx) {
+                    Ok(matched_exec) => return Ok(matched_exec),
+                    Err(err) => {
+                        last_err = Err(err);
+                    }
+                }
+            }
+        }
+        last_err
+    }
+
+    /// The verdict of the policy's forbidden program regexes and forbidden
+    /// substrings for `exec_call`, or `None` when neither applies.
+    pub(crate) fn check_forbidden(&self, exec_call: &ExecCall) -> Option<MatchedExec> {
         let ExecCall { program, args } = &exec_call;
         for ForbiddenProgramRegex { regex, reason } in &self.forbidden_program_regexes {
             if regex.is_match(program) {
-                return Ok(MatchedExec::Forbidden {
+                return Some(MatchedExec::Forbidden {
                     cause: Forbidden::Program {
                         program: program.clone(),
                         exec_call: exec_call.clone(),
@@ -59,7 +149,7 @@ impl Policy {
         for arg in args {
             if let Some(regex) = &self.forbidden_substrings_pattern {
                 if regex.is_match(arg) {
-                    return Ok(MatchedExec::Forbidden {
+                    return Some(MatchedExec::Forbidden {
                         cause: Forbidden::Arg {
                             arg: arg.clone(),
                             exec_call: exec_call.clone(),
@@ -69,21 +159,123 @@ impl Policy {
                 }
             }
         }
+        None
+    }
 
-        let mut last_err = Err(Error::NoSpecForProgram {
-            program: program.clone(),
-        });
-        if let Some(spec_list) = self.programs.get_vec(program) {
-            for spec in spec_list {
-                match spec.check(exec_call) {
-                    Ok(matched_exec) => return Ok(matched_exec),
-                    Err(err) => {
-                        last_err = Err(err);
-                    }
-                }
+    /// Describe every program spec and forbidden pattern in the policy.
+    pub fn summary(&self) -> PolicySummary {
+        let mut programs: Vec<ProgramSpecSummary> = self
+            .programs
+            .flat_iter()
+            .map(|(_program, spec)| spec.summary())
+            .collect();
+        // Stable sort keeps specs for the same program in definition order.
+        programs.sort_by(|a, b| a.program.cmp(&b.program));
+        PolicySummary {
+            programs,
+            forbidden_programs: self
+                .forbidden_program_regexes
+                .iter()
+                .map(|ForbiddenProgramRegex { regex, reason }| ForbiddenProgramSummary {
+                    regex: regex.as_str().to_string(),
+                    reason: reason.clone(),
+                })
+                .collect(),
+            forbidden_substrings_pattern: self
+                .forbidden_substrings_pattern
+                .as_ref()
+                .map(|regex| regex.as_str().to_string()),
+        }
+    }
+
+    /// `(program, reason)` for every `define_program` declared with
+    /// `forbidden=`, sorted by program. Programs blocked by
+    /// `forbid_program_regex` are patterns rather than names; see
+    /// [`PolicySummary::forbidden_programs`] for those.
+    pub fn forbidden_programs(&self) -> Vec<(String, String)> {
+        let mut forbidden: Vec<(String, String)> = self
+            .programs
+            .flat_iter()
+            .filter_map(|(program, spec)| {
+                spec.forbidden_reason()
+                    .map(|reason| (program.clone(), reason.to_string()))
+            })
+            .collect();
+        forbidden.sort();
+        forbidden
+    }
+
+    /// Compare the program specs of `self` (the old policy) with `other`.
+    /// Program names in each list are sorted.
+    pub fn diff(&self, other: &Policy) -> PolicyDiff {
+        let old = self.specs_by_program();
+        let new = other.specs_by_program();
+        let mut diff = PolicyDiff::default();
+        for (program, specs) in &new {
+            match old.get(program) {
+                None => diff.added_programs.push(program.clone()),
+                Some(old_specs) if old_specs != specs => diff.changed_programs.push(program.clone()),
+                Some(_) => {}
             }
         }
-        last_err
+        diff.removed_programs = old
+            .keys()
+            .filter(|program| !new.contains_key(*program))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    fn specs_by_program(&self) -> BTreeMap<String, Vec<ProgramSpecSummary>> {
+        self.programs
+            .iter_all()
+            .map(|(program, specs)| {
+                (program.clone(), specs.iter().map(ProgramSpec::summary).collect())
+            })
+            .collect()
+    }
+
+    /// Starlark source that loads back into an equivalent policy: the
+    /// `forbid_*` calls first, then one `define_program` per spec, sorted by
+    /// program with specs for the same program in definition order.
+    pub fn to_starlark(&self) -> String {
+        let mut out = String::new();
+        if !self.forbidden_substrings.is_empty() {
+            out.push_str(&format!(
+                "forbid_substrings({})\n\n",
+                starlark_list(&self.forbidden_substrings)
+            ));
+        }
+        for ForbiddenProgramRegex { regex, reason } in &self.forbidden_program_regexes {
+            out.push_str(&format!(
+                "forbid_program_regex({}, {})\n\n",
+                starlark_string(regex.as_str()),
+                starlark_string(reason)
+            ));
+        }
+        let mut programs: Vec<&String> = self.programs.keys().collect();
+        programs.sort();
+        for program in programs {
+            for spec in self.programs.get_vec(program).into_iter().flatten() {
+                out.push_str(&spec.to_starlark());
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Hash of [`Policy::to_starlark`], so policies that differ only in
+    /// formatting, comments, option order or the order in which different
+    /// programs are defined get the same value. Uses 64-bit FNV-1a, which is
+    /// stable across runs and builds.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        self.to_starlark()
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
     }
 
     pub fn check_each_good_list_individually(&self) -> Vec<PositiveExampleFailedCheck> {
@@ -21,6 +21,7 @@ use starlark::values::list::UnpackList;
 use starlark::values::none::NoneType;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
 
 pub struct PolicyParser {
     policy_source: String,
@@ -103,6 +104,46 @@ impl PolicyParser {
     }
 }
 
+/// Why a policy failed [`validate_policy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyValidationError {
+    pub source_name: String,
+    /// 1-based line of the error, when starlark reported a location.
+    pub line: Option<usize>,
+    /// 1-based column of the error, when starlark reported a location.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for PolicyValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "{}:{line}:{column}: {}", self.source_name, self.message)
+            }
+            _ => write!(f, "{}: {}", self.source_name, self.message),
+        }
+    }
+}
+
+impl std::error::Error for PolicyValidationError {}
+
+/// Parse and evaluate the policy in `source` without watching or storing it.
+pub fn validate_policy(source_name: &str, source: &str) -> Result<(), PolicyValidationError> {
+    PolicyParser::new(source_name, source)
+        .parse()
+        .map(|_| ())
+        .map_err(|err| {
+            let begin = err.span().map(|span| span.resolve_span().begin);
+            PolicyValidationError {
+                source_name: source_name.to_string(),
+                line: begin.map(|pos| pos.line + 1),
+                column: begin.map(|pos| pos.column + 1),
+                message: err.without_diagnostic().to_string(),
+            }
+        })
+}
+
 #[derive(Clone, Debug)]
 pub struct ForbiddenProgramRegex {
     pub regex: regex_lite::Regex,
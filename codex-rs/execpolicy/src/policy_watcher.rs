@@ -1,18 +1,26 @@
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 
 use anyhow::Context;
-use crate::{Policy, PolicyParser};
+use crate::{AllowList, ExecCall, MatchedExec, Policy, PolicyDiff, PolicyParser};
+use crate::watch_registry::{WATCH_REGISTRY, WatchRegistration};
 use crate::threat_state::{
     ThreatMatrix,
     ThreatAssessment,
     ThreatDeliverable,
+    RiskTree,
     RiskVector,
     ThreatLevel,
     DEFAULT_CATEGORY_WEIGHTS,
-    load_risk_tree,
+    CsvParseMode,
+    load_risk_tree_cached,
+    parse_risk_tree,
+    filter_risk_tree_env,
+    parse_risk_row,
     generate_deliverables_with_weights,
     load_risk_matrix,
     risk_vector_score,
@@ -23,7 +31,7 @@ use crate::threat_state::{
 /// Path to the CSV database containing risk assessment scores.
 ///
 /// The risk matrix is derived from this CSV at runtime.
-fn risk_csv_path() -> PathBuf {
+pub fn risk_csv_path() -> PathBuf {
     let cargo_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     cargo_dir
         .parent()
@@ -31,18 +39,102 @@ fn risk_csv_path() -> PathBuf {
         .join("scripts/risk_csv.csv")
 }
 
-/// Threshold above which policy reloads should be rejected.
-const RISK_THRESHOLD: f64 = 0.5;
+/// How often [`read_policy_with_retry`] tries to read a policy file.
+const POLICY_READ_ATTEMPTS: usize = 5;
 
+/// Pause between attempts of [`read_policy_with_retry`].
+const POLICY_READ_RETRY_DELAY: Duration = Duration::from_millis(50);
 
-/// Load the overall risk score from `RISK_CSV_PATH` by averaging all metrics.
+/// Read the policy at `path`, retrying briefly while it is missing or empty.
+///
+/// Editors that save by writing a temp file and renaming it over the target
+/// leave a short window in which the file is absent or not yet written; a
+/// single read there would drop the update.
+fn read_policy_with_retry(path: &Path) -> std::io::Result<String> {
+    let mut attempt = 1;
+    loop {
+        let result = std::fs::read_to_string(path);
+        match policy_read_retry_delay(attempt, &result) {
+            Some(delay) => std::thread::sleep(delay),
+            None => return result,
+        }
+        attempt += 1;
+    }
+}
+
+/// Async counterpart of [`read_policy_with_retry`] using `tokio::fs`.
+async fn read_policy_with_retry_async(path: &Path) -> std::io::Result<String> {
+    let mut attempt = 1;
+    loop {
+        let result = tokio::fs::read_to_string(path).await;
+        match policy_read_retry_delay(attempt, &result) {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return result,
+        }
+        attempt += 1;
+    }
+}
+
+/// How long to wait before reading the policy again after `attempt` produced
+/// `result`, or `None` to return `result` as is: it is not a missing or empty
+/// file, or the attempts are used up.
+fn policy_read_retry_delay(attempt: usize, result: &std::io::Result<String>) -> Option<Duration> {
+    let transient = match result {
+        Ok(content) => content.trim().is_empty(),
+        Err(err) => err.kind() == std::io::ErrorKind::NotFound,
+    };
+    (transient && attempt < POLICY_READ_ATTEMPTS).then_some(POLICY_READ_RETRY_DELAY)
+}
+
+/// Default threshold above which policy reloads and the CSV prefilter
+/// reject the risk database.
+pub const RISK_THRESHOLD: f64 = 0.5;
+
+/// Environment variable overriding [`RISK_THRESHOLD`], see [`risk_threshold`].
+pub const RISK_THRESHOLD_ENV_VAR: &str = "CODEX_RISK_THRESHOLD";
+
+/// The risk threshold to apply: `CODEX_RISK_THRESHOLD` when it holds a finite
+/// number, [`RISK_THRESHOLD`] otherwise.
+pub fn risk_threshold() -> f64 {
+    std::env::var(RISK_THRESHOLD_ENV_VAR)
+        .ok()
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|threshold| threshold.is_finite())
+        .unwrap_or(RISK_THRESHOLD)
+}
+
+
+/// Load the overall risk score of the bundled risk database by averaging all
+/// metrics. This is the score used by [`PolicyWatcher::reload`],
+/// [`PolicyWatcher::prefilter_csv`] and the `codex-execpolicy` prefilter.
+pub fn current_risk_score() -> f64 {
+    risk_score_from_csv(&risk_csv_path())
+}
+
+/// Average every metric in the risk CSV at `path`.
 /// If the CSV cannot be read, [`DEFAULT_RISK_SCORE`] is returned so that existing
 /// behaviour is preserved.
-fn current_risk_score() -> f64 {
-    let path = risk_csv_path();
-    let Ok(tree) = load_risk_tree(std::path::Path::new(&path)) else {
+pub fn risk_score_from_csv(path: &Path) -> f64 {
+    match load_risk_tree_cached(path) {
+        Ok(tree) => average_risk(&tree),
+        Err(_) => DEFAULT_RISK_SCORE,
+    }
+}
+
+/// Async counterpart of [`risk_score_from_csv`] reading the CSV with
+/// `tokio::fs`. The parsed tree is not cached.
+pub async fn risk_score_from_csv_async(path: &Path) -> f64 {
+    let Ok(content) = tokio::fs::read_to_string(path).await else {
         return DEFAULT_RISK_SCORE;
     };
+    match parse_risk_tree(&content, CsvParseMode::Lenient) {
+        Ok(tree) => average_risk(&tree),
+        Err(_) => DEFAULT_RISK_SCORE,
+    }
+}
+
+/// Mean of every metric in `tree`, or [`DEFAULT_RISK_SCORE`] when it is empty.
+fn average_risk(tree: &RiskTree) -> f64 {
     let mut sum = 0.0;
     let mut count = 0;
     for env in tree.values() {
@@ -58,15 +150,115 @@ fn current_risk_score() -> f64 {
     if count == 0 { DEFAULT_RISK_SCORE } else { sum / count as f64 }
 }
 
+/// Split bundled POSIX short flags into individual flags.
+///
+/// `-la` becomes `["-l", "-a"]`. Long flags (`--all`), a lone `-`, tokens
+/// that are not flags, and short flags carrying non-alphabetic characters
+/// (`-n5`, `-1`) are returned unchanged as a single element.
+pub fn decompose_flags(token: &str) -> Vec<String> {
+    match token.strip_prefix('-') {
+        Some(rest)
+            if rest.len() > 1
+                && !rest.starts_with('-')
+                && rest.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            rest.chars().map(|c| format!("-{c}")).collect()
+        }
+        _ => vec![token.to_string()],
+    }
+}
+
+/// Look up the risk vectors for each flag of `commands` in the `env` rows of
+/// `tree` (the host OS when `None`).
+fn batch_from_tree(tree: &RiskTree, commands: Vec<String>, env: Option<&str>) -> Vec<(String, RiskVector)> {
+    let tree = filter_risk_tree_env(tree, env.unwrap_or(std::env::consts::OS));
+    let mut results = Vec::new();
+
+    for command in commands {
+        let mut parts = command.split_whitespace();
+        if let Some(cmd) = parts.next() {
+            let tokens: Vec<&str> = parts.collect();
+            push_flag_vectors(&tree, cmd, &tokens, &mut results);
+        }
+    }
+
+    results
+}
+
+/// Append the risk vector of every flag in `tokens` found under `cmd` in
+/// `tree`, splitting bundled short flags that are not listed verbatim.
+fn push_flag_vectors(tree: &RiskTree, cmd: &str, tokens: &[&str], results: &mut Vec<(String, RiskVector)>) {
+    for env_map in tree.values() {
+        let Some(cmd_map) = env_map.get(cmd) else {
+            continue;
+        };
+        for token in tokens {
+            if let Some(vec) = cmd_map.get(*token) {
+                results.push((token.to_string(), vec.clone()));
+                continue;
+            }
+            for flag in decompose_flags(token) {
+                if let Some(vec) = cmd_map.get(&flag) {
+                    results.push((flag, vec.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Average metric of the risk vectors `call`'s flags have in `tree`, for
+/// `env` (the host OS when `None`). A command with no listed flags scores
+/// [`DEFAULT_RISK_SCORE`].
+pub fn command_risk_score(tree: &RiskTree, call: &ExecCall, env: Option<&str>) -> f64 {
+    let tree = filter_risk_tree_env(tree, env.unwrap_or(std::env::consts::OS));
+    let tokens: Vec<&str> = call.args.iter().map(String::as_str).collect();
+    let mut batch = Vec::new();
+    push_flag_vectors(&tree, &call.program, &tokens, &mut batch);
+    let values: Vec<f64> = batch.iter().flat_map(|(_, vec)| vec.iter().copied()).collect();
+    if values.is_empty() {
+        DEFAULT_RISK_SCORE
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Evaluate the [`ThreatLevel`] of a single command line against `tree`.
+///
+/// Only the rows for `env` are consulted (the host OS when `None`), so a
+/// command can be assessed for another platform than the one running it.
+pub fn threat_level_from_tree(tree: &RiskTree, command: &str, env: Option<&str>) -> ThreatLevel {
+    let batch = batch_from_tree(tree, vec![command.to_string()], env);
+    let mut matrix = ThreatMatrix::new(batch.len().max(1), 0.0);
+    for (flag, vec) in batch {
+        let risk_score = risk_vector_score(&vec);
+        matrix.add_assessment(ThreatAssessment::new(risk_score, risk_score, vec![flag]));
+    }
+    matrix.evaluate()
+}
+
 /// Watches a policy file and reloads it when modified.
 ///
 /// This is useful for environments where the policy may change at runtime.
 #[derive(Debug)]
 pub struct PolicyWatcher {
     policy: Arc<Mutex<Policy>>,
+    /// Policy as of the last [`PolicyWatcher::reload`] (or construction), so
+    /// reload diffs cover changes picked up by the file watcher in between.
+    last_reload: Mutex<Policy>,
     path: PathBuf,
+    risk_csv: PathBuf,
+    /// Average risk score above which [`PolicyWatcher::reload`] and
+    /// [`PolicyWatcher::prefilter_csv`] reject the risk database.
+    risk_threshold: f64,
+    /// Trusted prefixes accepted by [`PolicyWatcher::check`] before the
+    /// starlark policy is consulted.
+    allow_list: Option<AllowList>,
+    /// How many calls [`PolicyWatcher::check`] passed to the starlark policy.
+    policy_checks: AtomicUsize,
     #[allow(dead_code)]
     watcher: RecommendedWatcher,
+    #[allow(dead_code)]
+    registration: WatchRegistration,
 }
 
 impl PolicyWatcher {
@@ -76,17 +268,20 @@ impl PolicyWatcher {
         let unparsed = std::fs::read_to_string(&path)
             .with_context(|| format!("reading {}", path.display()))?;
         let parser = PolicyParser::new(&path.to_string_lossy(), &unparsed);
-        let policy = Arc::new(Mutex::new(parser.parse().map_err(|e| anyhow::anyhow!(e))?));
+        let parsed = parser.parse().map_err(|e| anyhow::anyhow!(e))?;
+        let last_reload = Mutex::new(parsed.clone());
+        let policy = Arc::new(Mutex::new(parsed));
 
         let policy_clone = Arc::clone(&policy);
         let path_clone = path.clone();
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
             if let Ok(event) = res {
                 if matches!(event.kind, EventKind::Modify(_)) {
-                    if let Ok(content) = std::fs::read_to_string(&path_clone) {
+                    if let Ok(content) = read_policy_with_retry(&path_clone) {
                         if let Ok(parsed) = PolicyParser::new(&path_clone.to_string_lossy(), &content).parse() {
-                            if let Ok(mut lock) = policy_clone.lock() {
-                                *lock = parsed;
+                            let mut current = lock_policy(&policy_clone);
+                            if current.fingerprint() != parsed.fingerprint() {
+                                *current = parsed;
                             }
                         }
                     }
@@ -94,32 +289,126 @@ impl PolicyWatcher {
             }
         })?;
         watcher.watch(&path, RecursiveMode::NonRecursive)?;
+        let registration = WATCH_REGISTRY.register(&path);
+
+        Ok(Self {
+            policy,
+            last_reload,
+            path,
+            risk_csv: risk_csv_path(),
+            risk_threshold: risk_threshold(),
+            allow_list: None,
+            policy_checks: AtomicUsize::new(0),
+            watcher,
+            registration,
+        })
+    }
+
+    /// Use the risk database at `path` instead of the bundled `risk_csv.csv`.
+    pub fn with_risk_csv(mut self, path: PathBuf) -> Self {
+        self.risk_csv = path;
+        self
+    }
+
+    /// Reject the risk database above `threshold` instead of the value from
+    /// [`risk_threshold`].
+    pub fn with_risk_threshold(mut self, threshold: f64) -> Self {
+        self.risk_threshold = threshold;
+        self
+    }
 
-        Ok(Self { policy, path, watcher })
+    /// Accept calls matching `allow_list` in [`PolicyWatcher::check`]
+    /// without evaluating the policy's program specs. The policy's forbidden
+    /// programs and substrings still apply to them.
+    pub fn with_allow_list(mut self, allow_list: AllowList) -> Self {
+        self.allow_list = Some(allow_list);
+        self
+    }
+
+    /// Check `exec_call` against the current policy's forbidden programs and
+    /// substrings, then against the allow-list, falling back to the policy's
+    /// program specs for calls it does not cover.
+    pub fn check(&self, exec_call: &ExecCall) -> crate::Result<MatchedExec> {
+        let policy = lock_policy(&self.policy);
+        if let Some(forbidden) = policy.check_forbidden(exec_call) {
+            return Ok(forbidden);
+        }
+        if let Some(exec) = self
+            .allow_list
+            .as_ref()
+            .and_then(|allow_list| allow_list.matched_exec(exec_call))
+        {
+            return Ok(MatchedExec::Match { exec });
+        }
+        self.policy_checks.fetch_add(1, Ordering::Relaxed);
+        policy.check(exec_call)
+    }
+
+    /// Number of calls [`PolicyWatcher::check`] evaluated with the policy's
+    /// program specs rather than the allow-list.
+    pub fn policy_checks(&self) -> usize {
+        self.policy_checks.load(Ordering::Relaxed)
     }
 
     /// Returns a clone of the current policy.
     pub fn policy(&self) -> Policy {
-        self.policy.lock().expect("lock poisoned").clone()
+        lock_policy(&self.policy).clone()
+    }
+
+    /// Reloads the policy from disk immediately and returns what changed
+    /// since the previous reload.
+    pub fn reload(&self) -> anyhow::Result<PolicyDiff> {
+        // Consult the risk database. If the average risk score exceeds the
+        // threshold, deny the reload request.
+        if risk_score_from_csv(&self.risk_csv) > self.risk_threshold {
+            anyhow::bail!("policy reload denied: risk level too high");
+        }
+
+        let unparsed = read_policy_with_retry(&self.path)
+            .with_context(|| format!("reading {}", self.path.display()))?;
+        self.apply_reloaded(&unparsed)
     }
 
-    /// Reloads the policy from disk immediately.
-    pub fn reload(&self) -> anyhow::Result<()> {
-        // Stub risk assessment check. In the future this should consult the
-        // real risk database. If the risk score exceeds the threshold, deny the
-        // reload request.
-        if current_risk_score() > RISK_THRESHOLD {
+    /// Async counterpart of [`PolicyWatcher::reload`] doing its file IO with
+    /// `tokio::fs`. Dropping the future before it completes leaves the
+    /// current policy untouched.
+    pub async fn reload_async(&self) -> anyhow::Result<PolicyDiff> {
+        if risk_score_from_csv_async(&self.risk_csv).await > self.risk_threshold {
             anyhow::bail!("policy reload denied: risk level too high");
         }
 
-        let unparsed = std::fs::read_to_string(&self.path)
+        let unparsed = read_policy_with_retry_async(&self.path)
+            .await
             .with_context(|| format!("reading {}", self.path.display()))?;
-        let parser = PolicyParser::new(&self.path.to_string_lossy(), &unparsed);
+        self.apply_reloaded(&unparsed)
+    }
+
+    /// Parse freshly read policy source and swap it in, returning what
+    /// changed since the previous reload.
+    fn apply_reloaded(&self, unparsed: &str) -> anyhow::Result<PolicyDiff> {
+        let parser = PolicyParser::new(&self.path.to_string_lossy(), unparsed);
         let parsed = parser.parse().map_err(|e| anyhow::anyhow!(e))?;
-        if let Ok(mut lock) = self.policy.lock() {
-            *lock = parsed;
+
+        // A file that only changed in formatting or comments parses to the
+        // same fingerprint; keep the policies already in place.
+        let fingerprint = parsed.fingerprint();
+        let mut last_reload = lock_policy(&self.last_reload);
+        let diff = if last_reload.fingerprint() == fingerprint {
+            PolicyDiff::default()
+        } else {
+            let diff = last_reload.diff(&parsed);
+            if !diff.is_empty() {
+                log::info!("policy {} reloaded: {diff}", self.path.display());
+            }
+            *last_reload = parsed.clone();
+            diff
+        };
+
+        let mut current = lock_policy(&self.policy);
+        if current.fingerprint() != fingerprint {
+            *current = parsed;
         }
-        Ok(())
+        Ok(diff)
     }
 
     /// Registers a new command in the risk database with a simple risk score.
@@ -134,59 +423,110 @@ impl PolicyWatcher {
         flag: &str,
         risk_score: f64,
     ) -> anyhow::Result<()> {
-        let path = risk_csv_path();
-        let mut content = std::fs::read_to_string(&path).unwrap_or_default();
-        let row = format!(
-            "\n{},{},{},{},{},{},{},{},none,none,none,none,none,none,none",
-            environment,
-            binary,
-            flag,
-            risk_score,
-            risk_score,
-            risk_score,
-            risk_score,
-            risk_score
-        );
-        content.push_str(&row);
-        std::fs::write(&path, content).context("writing to risk database")?;
+        let path = &self.risk_csv;
+        let mut content = std::fs::read_to_string(path).unwrap_or_default();
+        content.push_str(&risk_row(environment, binary, flag, risk_score));
+        std::fs::write(path, content).context("writing to risk database")?;
+        Ok(())
+    }
+
+    /// Async counterpart of [`PolicyWatcher::register_tool`] using
+    /// `tokio::fs`. Dropping the future stops waiting for it, but a write
+    /// already handed to tokio's blocking pool still completes.
+    pub async fn register_tool_async(
+        &self,
+        environment: &str,
+        binary: &str,
+        flag: &str,
+        risk_score: f64,
+    ) -> anyhow::Result<()> {
+        let path = &self.risk_csv;
+        let mut content = tokio::fs::read_to_string(path).await.unwrap_or_default();
+        content.push_str(&risk_row(environment, binary, flag, risk_score));
+        tokio::fs::write(path, content)
+            .await
+            .context("writing to risk database")?;
         Ok(())
     }
 
+    /// Rewrite the risk CSV without rows whose aggregated risk is below
+    /// `min_score`, returning how many rows were removed.
+    ///
+    /// Rows repeating an earlier environment/binary/flag key are superseded
+    /// by the last one, exactly as [`crate::threat_state::load_risk_tree`] reads them, so only
+    /// that last row is kept. Malformed rows are dropped as well; blank lines
+    /// are discarded without being counted. The header and every column of
+    /// the surviving rows are preserved.
+    pub fn compact_csv(&self, min_score: f64) -> anyhow::Result<usize> {
+        let path = &self.risk_csv;
+        let content = std::fs::read_to_string(path).context("reading risk database")?;
+        let mut lines = content.lines();
+        let Some(header) = lines.next() else {
+            return Ok(0);
+        };
+
+        let rows: Vec<(&str, Option<(String, String, String, RiskVector)>)> = lines
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| (line, parse_risk_row(line)))
+            .collect();
+        let mut last_index = std::collections::HashMap::new();
+        for (index, (_, parsed)) in rows.iter().enumerate() {
+            if let Some((env, cmd, flag, _)) = parsed {
+                last_index.insert((env, cmd, flag), index);
+            }
+        }
+
+        let mut compacted = String::from(header);
+        compacted.push('\n');
+        let mut removed = 0;
+        for (index, (line, parsed)) in rows.iter().enumerate() {
+            let keep = match parsed {
+                Some((env, cmd, flag, vec)) => {
+                    last_index.get(&(env, cmd, flag)) == Some(&index)
+                        && risk_vector_score(vec) >= min_score
+                }
+                None => false,
+            };
+            if keep {
+                compacted.push_str(line);
+                compacted.push('\n');
+            } else {
+                removed += 1;
+            }
+        }
+
+        std::fs::write(path, compacted).context("writing risk database")?;
+        Ok(removed)
+    }
+
     /// Performs a prefilter check on the CSV data.
     ///
     /// This is used to reject CSV data that may be too risky to process.
     pub fn prefilter_csv(&self) -> anyhow::Result<()> {
-        let risk_score = current_risk_score();
-        if risk_score > RISK_THRESHOLD {
+        let risk_score = risk_score_from_csv(&self.risk_csv);
+        if risk_score > self.risk_threshold {
             anyhow::bail!("CSV prefilter rejected: risk score too high");
         }
         Ok(())
     }
 
-    /// Decomposes a list of command strings into their base flags and compiles a batch of CSV values.
-    pub fn compile_csv_batch(&self, commands: Vec<String>, env: Option<&str>) -> anyhow::Result<Vec<(String, RiskVector)>> {
-        let path = risk_csv_path();
-        let tree = load_risk_tree(std::path::Path::new(&path))?;
-        let mut results = Vec::new();
-        let environment = env.map(|e| e.to_lowercase()).unwrap_or_else(|| std::env::consts::OS.to_lowercase());
-
-        for command in commands {
-            let mut parts = command.split_whitespace();
-            if let Some(cmd) = parts.next() {
-                let flags: Vec<String> = parts.map(|s| s.to_string()).collect();
-                if let Some(env_map) = tree.get(&environment) {
-                    if let Some(cmd_map) = env_map.get(cmd) {
-                        for flag in &flags {
-                            if let Some(vec) = cmd_map.get(flag) {
-                                results.push((flag.clone(), vec.clone()));
-                            }
-                        }
-                    }
-                }
-            }
+    /// Async counterpart of [`PolicyWatcher::prefilter_csv`].
+    pub async fn prefilter_csv_async(&self) -> anyhow::Result<()> {
+        let risk_score = risk_score_from_csv_async(&self.risk_csv).await;
+        if risk_score > self.risk_threshold {
+            anyhow::bail!("CSV prefilter rejected: risk score too high");
         }
+        Ok(())
+    }
 
-        Ok(results)
+    /// Decomposes a list of command strings into their base flags and compiles a batch of CSV values.
+    ///
+    /// A token that appears verbatim in the CSV is looked up as-is; otherwise
+    /// bundled short flags are split with [`decompose_flags`] so that `ls -la`
+    /// matches the rows for `-l` and `-a`.
+    pub fn compile_csv_batch(&self, commands: Vec<String>, env: Option<&str>) -> anyhow::Result<Vec<(String, RiskVector)>> {
+        let tree = load_risk_tree_cached(&self.risk_csv)?;
+        Ok(batch_from_tree(&tree, commands, env))
     }
 
     /// Combine category scores from threat state into a single risk score.
@@ -201,14 +541,16 @@ impl PolicyWatcher {
     }
 
     /// Processes the dimensionality of a ThreatMatrix based on the CSV data and commands.
-    pub fn process_threat_matrix(&self, commands: Vec<String>) -> ThreatMatrix {
-        let path = risk_csv_path();
-        let mut matrix = match load_risk_matrix(std::path::Path::new(&path)) {
+    ///
+    /// `env` selects the environment whose risk rows are consulted; `None`
+    /// falls back to the host OS.
+    pub fn process_threat_matrix(&self, commands: Vec<String>, env: Option<&str>) -> ThreatMatrix {
+        let mut matrix = match load_risk_matrix(&self.risk_csv) {
             Ok(m) => m,
             Err(_) => ThreatMatrix::new(0, 0.0),
         };
 
-        if let Ok(batch) = self.compile_csv_batch(commands, None) {
+        if let Ok(batch) = self.compile_csv_batch(commands, env) {
             let scored = self.modulate_results(batch);
             for (tool_name, risk_score) in scored {
                 let assessment = ThreatAssessment::new(risk_score, risk_score, vec![tool_name]);
@@ -222,8 +564,30 @@ impl PolicyWatcher {
 
     /// Generates threat deliverables by overlaying the current CSV with historical data.
     pub fn threat_deliverables(&self, csv_path: &PathBuf) -> anyhow::Result<ThreatDeliverable> {
-        let tree = load_risk_tree(csv_path)?;
-        Ok(generate_deliverables_with_weights(tree, &DEFAULT_CATEGORY_WEIGHTS))
+        let tree = load_risk_tree_cached(csv_path)?;
+        Ok(generate_deliverables_with_weights(RiskTree::clone(&tree), &DEFAULT_CATEGORY_WEIGHTS))
+    }
+
+    /// Score `call` against the watcher's risk CSV for `env` (the host OS
+    /// when `None`).
+    ///
+    /// Each flag of the call is looked up as in [`Self::compile_csv_batch`],
+    /// its category metrics are multiplied by the matching entry of `weights`
+    /// (1.0 when missing) and everything is summed. Flags without a row add
+    /// nothing; if the CSV cannot be read [`DEFAULT_RISK_SCORE`] is returned.
+    pub fn score_exec_call(&self, call: &ExecCall, weights: &[f64], env: Option<&str>) -> f64 {
+        let Ok(tree) = load_risk_tree_cached(&self.risk_csv) else {
+            return DEFAULT_RISK_SCORE;
+        };
+        let tree = filter_risk_tree_env(&tree, env.unwrap_or(std::env::consts::OS));
+        let tokens: Vec<&str> = call.args.iter().map(String::as_str).collect();
+        let mut batch = Vec::new();
+        push_flag_vectors(&tree, &call.program, &tokens, &mut batch);
+        batch
+            .iter()
+            .flat_map(|(_, vec)| vec.iter().enumerate())
+            .map(|(i, v)| v * weights.get(i).copied().unwrap_or(1.0))
+            .sum()
     }
 
     /// Evaluate a [`ThreatMatrix`] and return the overall [`ThreatLevel`].
@@ -231,5 +595,83 @@ impl PolicyWatcher {
         matrix.evaluate()
 
     }
+
+    /// Evaluate the [`ThreatLevel`] of a single command line in one call.
+    ///
+    /// The command's flags are looked up in the risk database for `env` (the
+    /// host OS when `None`), scored, collected into a fresh [`ThreatMatrix`]
+    /// and evaluated. The CSV is read through [`load_risk_tree_cached`], so it
+    /// is only parsed again after it changes.
+    ///
+    /// Fails when the CSV cannot be read or parsed. No level is guessed in
+    /// that case; callers gating an exec should treat the command as blocked.
+    pub fn threat_level_for(&self, command: &str, env: Option<&str>) -> anyhow::Result<ThreatLevel> {
+        let tree = load_risk_tree_cached(&self.risk_csv)?;
+        Ok(threat_level_from_tree(&tree, command, env))
+    }
+}
+
+/// CSV row appended by [`PolicyWatcher::register_tool`]: `risk_score` in
+/// every threat category and `none` in every translation column.
+fn risk_row(environment: &str, binary: &str, flag: &str, risk_score: f64) -> String {
+    format!(
+        "\n{},{},{},{},{},{},{},{},none,none,none,none,none,none,none",
+        environment,
+        binary,
+        flag,
+        risk_score,
+        risk_score,
+        risk_score,
+        risk_score,
+        risk_score
+    )
+}
+
+/// Lock `policy`, recovering from poisoning: a thread that panicked while
+/// holding the guard cannot have left a half-written [`Policy`] behind, since
+/// it is only ever replaced whole.
+fn lock_policy(policy: &Mutex<Policy>) -> MutexGuard<'_, Policy> {
+    match policy.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => {
+            log::warn!("policy lock was poisoned by a panicking thread; recovering");
+            poisoned.into_inner()
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::MatchedExec;
+
+    #[test]
+    fn poisoned_policy_lock_is_recovered() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("policy.star");
+        std::fs::write(
+            &path,
+            "define_program(program=\"ls\", args=[], system_path=[\"/bin/ls\"])",
+        )
+        .unwrap();
+        let watcher = PolicyWatcher::new(path).unwrap();
+
+        std::thread::scope(|scope| {
+            let handle = scope.spawn(|| {
+                let _guard = watcher.policy.lock().unwrap();
+                panic!("poisoning the policy lock");
+            });
+            assert!(handle.join().is_err());
+        });
+        assert!(watcher.policy.is_poisoned());
+
+        let exec = ExecCall::new("ls", &[]);
+        assert!(matches!(
+            watcher.policy().check(&exec).unwrap(),
+            MatchedExec::Match { .. }
+        ));
+    }
+}
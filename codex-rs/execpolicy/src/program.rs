@@ -1,9 +1,11 @@
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
 use crate::ArgType;
 use crate::ExecCall;
+use crate::check_context::CheckContext;
 use crate::arg_matcher::ArgMatcher;
 use crate::arg_resolver::PositionalArg;
 use crate::arg_resolver::resolve_observed_args_with_patterns;
@@ -93,6 +95,12 @@ impl ProgramSpec {
     // a program and the args should be checked against the rules to determine
     // if the program should be allowed to run.
     pub fn check(&self, exec_call: &ExecCall) -> Result<MatchedExec> {
+        self.check_with_context(exec_call, &CheckContext::default())
+    }
+
+    /// Like [`ProgramSpec::check`], but a match whose writeable file arguments
+    /// leave `ctx.writable_roots` is reported as forbidden.
+    pub fn check_with_context(&self, exec_call: &ExecCall, ctx: &CheckContext) -> Result<MatchedExec> {
         let mut expecting_option_value: Option<(String, ArgType)> = None;
         let mut args = Vec::<PositionalArg>::new();
         let mut matched_flags = Vec::<MatchedFlag>::new();
@@ -186,13 +194,20 @@ impl ProgramSpec {
             args: matched_args,
             system_path: self.system_path.clone(),
         };
-        match &self.forbidden {
-            Some(reason) => Ok(MatchedExec::Forbidden {
+        if let Some(reason) = &self.forbidden {
+            return Ok(MatchedExec::Forbidden {
                 cause: Forbidden::Exec { exec },
                 reason: reason.clone(),
-            }),
-            None => Ok(MatchedExec::Match { exec }),
+            });
         }
+        if let Some(path) = ctx.first_unwritable_path(&exec) {
+            let reason = format!("path `{path}` is outside the writable roots");
+            return Ok(MatchedExec::Forbidden {
+                cause: Forbidden::Exec { exec },
+                reason,
+            });
+        }
+        Ok(MatchedExec::Match { exec })
     }
 
     pub fn verify_should_match_list(&self) -> Vec<PositiveExampleFailedCheck> {
@@ -234,6 +249,108 @@ impl ProgramSpec {
     }
 }
 
+/// Serializable view of a [`ProgramSpec`], for debugging policies.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ProgramSpecSummary {
+    pub program: String,
+    pub system_path: Vec<String>,
+    /// Options that take no value, sorted.
+    pub flags: Vec<String>,
+    /// Options that take a value, keyed by option name.
+    pub opts: BTreeMap<String, ArgType>,
+    pub required_options: Vec<String>,
+    pub args: Vec<String>,
+    /// Reason attached to `forbidden=`, if the spec forbids the program.
+    pub forbidden: Option<String>,
+}
+
+impl ProgramSpec {
+    pub fn forbidden_reason(&self) -> Option<&str> {
+        self.forbidden.as_deref()
+    }
+
+    /// `define_program(...)` call that recreates this spec. Options are
+    /// sorted by name; keyword arguments left at their defaults are omitted.
+    pub(crate) fn to_starlark(&self) -> String {
+        let mut out = String::from("define_program(\n");
+        out.push_str(&format!("    program={},\n", starlark_string(&self.program)));
+        if !self.system_path.is_empty() {
+            out.push_str(&format!("    system_path={},\n", starlark_list(&self.system_path)));
+        }
+        if self.option_bundling {
+            out.push_str("    option_bundling=True,\n");
+        }
+        if self.combined_format {
+            out.push_str("    combined_format=True,\n");
+        }
+        if !self.allowed_options.is_empty() {
+            let mut options: Vec<&Opt> = self.allowed_options.values().collect();
+            options.sort_by(|a, b| a.opt.cmp(&b.opt));
+            out.push_str("    options=[\n");
+            for opt in options {
+                let name = starlark_string(&opt.opt);
+                match &opt.meta {
+                    OptMeta::Flag => out.push_str(&format!("        flag({name}),\n")),
+                    OptMeta::Value(arg_type) => {
+                        let required = if opt.required { ", required=True" } else { "" };
+                        out.push_str(&format!(
+                            "        opt({name}, {}{required}),\n",
+                            arg_type_to_starlark(arg_type)
+                        ));
+                    }
+                }
+            }
+            out.push_str("    ],\n");
+        }
+        if !self.arg_patterns.is_empty() {
+            let args: Vec<String> = self.arg_patterns.iter().map(arg_matcher_to_starlark).collect();
+            out.push_str(&format!("    args=[{}],\n", args.join(", ")));
+        }
+        if let Some(reason) = &self.forbidden {
+            out.push_str(&format!("    forbidden={},\n", starlark_string(reason)));
+        }
+        for (name, examples) in [
+            ("should_match", &self.should_match),
+            ("should_not_match", &self.should_not_match),
+        ] {
+            if !examples.is_empty() {
+                out.push_str(&format!("    {name}=[\n"));
+                for example in examples {
+                    out.push_str(&format!("        {},\n", starlark_list(example)));
+                }
+                out.push_str("    ],\n");
+            }
+        }
+        out.push_str(")\n");
+        out
+    }
+
+    pub fn summary(&self) -> ProgramSpecSummary {
+        let mut flags = Vec::new();
+        let mut opts = BTreeMap::new();
+        for (name, opt) in &self.allowed_options {
+            match &opt.meta {
+                OptMeta::Flag => flags.push(name.clone()),
+                OptMeta::Value(arg_type) => {
+                    opts.insert(name.clone(), arg_type.clone());
+                }
+            }
+        }
+        flags.sort();
+        let mut required_options: Vec<String> = self.required_options.iter().cloned().collect();
+        required_options.sort();
+        ProgramSpecSummary {
+            program: self.program.clone(),
+            system_path: self.system_path.clone(),
+            flags,
+            opts,
+            required_options,
+            args: self.arg_patterns.iter().map(ToString::to_string).collect(),
+            forbidden: self.forbidden.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct PositiveExampleFailedCheck {
     pub program: String,
@@ -246,3 +363,54 @@ pub struct NegativeExamplePassedCheck {
     pub program: String,
     pub args: Vec<String>,
 }
+
+/// Quote `value` as a starlark string literal.
+pub(crate) fn starlark_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub(crate) fn starlark_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| starlark_string(value)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+fn arg_matcher_to_starlark(matcher: &ArgMatcher) -> String {
+    match matcher {
+        ArgMatcher::Literal(value) => starlark_string(value),
+        ArgMatcher::OpaqueNonFile => "ARG_OPAQUE_VALUE".to_string(),
+        ArgMatcher::ReadableFile => "ARG_RFILE".to_string(),
+        ArgMatcher::WriteableFile => "ARG_WFILE".to_string(),
+        ArgMatcher::ReadableFiles => "ARG_RFILES".to_string(),
+        ArgMatcher::ReadableFilesOrCwd => "ARG_RFILES_OR_CWD".to_string(),
+        ArgMatcher::PositiveInteger => "ARG_POS_INT".to_string(),
+        ArgMatcher::SedCommand => "ARG_SED_COMMAND".to_string(),
+        ArgMatcher::UnverifiedVarargs => "ARG_UNVERIFIED_VARARGS".to_string(),
+    }
+}
+
+/// `opt()` stores the [`ArgType`] of its matcher, so pick the matcher
+/// constant that maps back to the same type.
+fn arg_type_to_starlark(arg_type: &ArgType) -> String {
+    match arg_type {
+        ArgType::Literal(value) => starlark_string(value),
+        ArgType::OpaqueNonFile => "ARG_OPAQUE_VALUE".to_string(),
+        ArgType::ReadableFile => "ARG_RFILE".to_string(),
+        ArgType::WriteableFile => "ARG_WFILE".to_string(),
+        ArgType::PositiveInteger => "ARG_POS_INT".to_string(),
+        ArgType::SedCommand => "ARG_SED_COMMAND".to_string(),
+        ArgType::Unknown => "ARG_UNVERIFIED_VARARGS".to_string(),
+    }
+}
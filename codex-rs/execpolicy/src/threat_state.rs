@@ -1,12 +1,17 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque, btree_map};
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use std::time::SystemTime;
 
 use anyhow::Context;
 use lazy_static::lazy_static;
 use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 
+use crate::watch_registry::{WATCH_REGISTRY, WatchRegistration};
+
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub enum ThreatLevel {
@@ -42,6 +47,8 @@ pub struct ThreatStateWatcher {
     path: PathBuf,
     #[allow(dead_code)]
     watcher: RecommendedWatcher,
+    #[allow(dead_code)]
+    registration: WatchRegistration,
 }
 
 impl ThreatStateWatcher {
@@ -62,7 +69,8 @@ impl ThreatStateWatcher {
             }
         })?;
         watcher.watch(&path, RecursiveMode::NonRecursive)?;
-        Ok(Self { state, path, watcher })
+        let registration = WATCH_REGISTRY.register(&path);
+        Ok(Self { state, path, watcher, registration })
     }
 
     pub fn state(&self) -> ThreatState {
@@ -98,6 +106,19 @@ pub struct ThreatAssessment {
     evaluated_danger: f64,
     /// Metadata or flags associated with the command.
     flags: Vec<String>,
+    /// Per-category risk vector this assessment was derived from, if known.
+    categories: Option<RiskVector>,
+}
+
+/// Aggregate view of a [`ThreatMatrix`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ThreatSummary {
+    /// Number of assessments in the window.
+    pub count: usize,
+    /// See [`ThreatMatrix::average_danger`].
+    pub average_danger: f64,
+    /// See [`ThreatMatrix::evaluate`].
+    pub level: ThreatLevel,
 }
 
 lazy_static! {
@@ -190,19 +211,94 @@ impl ThreatMatrix {
         (*guard).clone()
     }
 
-    /// Evaluate the current matrix and return the [`ThreatLevel`].
-    pub fn evaluate(&self) -> ThreatLevel {
-        let avg = self.average_danger();
-        if avg == DEFAULT_RISK_SCORE {
-            return ThreatLevel::Low;
+    /// Summarize the global historical matrix without cloning its window.
+    pub fn historical_summary() -> ThreatSummary {
+        HISTORICAL_MATRIX.lock().unwrap_or_else(PoisonError::into_inner).summary()
+    }
+
+    /// Count, average danger and level of this matrix.
+    pub fn summary(&self) -> ThreatSummary {
+        ThreatSummary {
+            count: self.window.len(),
+            average_danger: self.average_danger(),
+            level: self.evaluate(),
         }
-        if avg > THREAT_HIGH_THRESHOLD {
-            ThreatLevel::High
-        } else if avg > THREAT_MEDIUM_THRESHOLD {
-            ThreatLevel::Medium
-        } else {
-            ThreatLevel::Low
+    }
+
+    /// Lay the assessments back out as a [`RiskTree`] under `env`/`command`.
+    ///
+    /// Every flag of an assessment becomes a row. Its vector is the
+    /// assessment's category vector when it has one, otherwise the evaluated
+    /// danger repeated for each category. Later assessments win when flags
+    /// repeat.
+    pub fn to_risk_tree(&self, env: &str, command: &str) -> RiskTree {
+        let mut flags = BTreeMap::new();
+        for assessment in &self.window {
+            let vector = assessment
+                .categories
+                .clone()
+                .unwrap_or_else(|| vec![assessment.evaluated_danger; DEFAULT_CATEGORY_WEIGHTS.len()]);
+            for flag in &assessment.flags {
+                flags.insert(flag.clone(), vector.clone());
+            }
         }
+
+        let mut tree = RiskTree::new();
+        if !flags.is_empty() {
+            tree.entry(env.to_string())
+                .or_default()
+                .insert(command.to_string(), flags);
+        }
+        tree
+    }
+
+    /// Evaluate the current matrix and return the [`ThreatLevel`].
+    pub fn evaluate(&self) -> ThreatLevel {
+        threat_level_for_danger(self.average_danger())
+    }
+
+    /// Evaluate each category independently, pairing every name in
+    /// `category_names` with the [`ThreatLevel`] of the matching dimension.
+    ///
+    /// A category is scored as the average of that dimension across the
+    /// assessments. Assessments without a category vector, or whose vector is
+    /// too short, contribute their evaluated danger instead.
+    pub fn evaluate_by_category(&self, category_names: &[&str]) -> Vec<(String, ThreatLevel)> {
+        category_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let avg = if self.window.is_empty() {
+                    DEFAULT_RISK_SCORE
+                } else {
+                    let sum: f64 = self
+                        .window
+                        .iter()
+                        .map(|a| {
+                            a.categories
+                                .as_ref()
+                                .and_then(|categories| categories.get(index).copied())
+                                .unwrap_or(a.evaluated_danger)
+                        })
+                        .sum();
+                    sum / self.window.len() as f64
+                };
+                (name.to_string(), threat_level_for_danger(avg))
+            })
+            .collect()
+    }
+}
+
+fn threat_level_for_danger(avg: f64) -> ThreatLevel {
+    if avg == DEFAULT_RISK_SCORE {
+        return ThreatLevel::Low;
+    }
+    if avg > THREAT_HIGH_THRESHOLD {
+        ThreatLevel::High
+    } else if avg > THREAT_MEDIUM_THRESHOLD {
+        ThreatLevel::Medium
+    } else {
+        ThreatLevel::Low
     }
 }
 
@@ -213,8 +309,15 @@ impl ThreatAssessment {
             original_danger,
             evaluated_danger,
             flags,
+            categories: None,
         }
     }
+
+    /// Attach the per-category risk vector this assessment was scored from.
+    pub fn with_categories(mut self, categories: RiskVector) -> Self {
+        self.categories = Some(categories);
+        self
+    }
 }
 
 /// Vector of threat metrics per flag.
@@ -240,10 +343,17 @@ pub fn risk_vector_score(vec: &RiskVector) -> f64 {
     vec.iter().sum()
 }
 
+/// A [`RiskTree`] in a [`RiskHistory`] window, with when it was added.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoricalTree {
+    pub captured_at: SystemTime,
+    pub tree: RiskTree,
+}
+
 #[derive(Clone, Debug, Default)]
 /// Historical tree storage with a moving window.
 pub struct RiskHistory {
-    window: VecDeque<RiskTree>,
+    window: VecDeque<HistoricalTree>,
     _decay_factor: f64,
     max_size: usize,
 }
@@ -258,12 +368,20 @@ impl RiskHistory {
         Self { window: VecDeque::new(), _decay_factor: decay_factor, max_size }
     }
 
-    /// Add a new risk tree to the historical window.
+    /// Add a new risk tree to the historical window, stamped with the current
+    /// time. Stamps strictly increase in insertion order, even if the clock
+    /// stalls or steps backwards.
     pub fn add_tree(&mut self, tree: RiskTree) {
+        let mut captured_at = SystemTime::now();
+        if let Some(last) = self.window.back()
+            && captured_at <= last.captured_at
+        {
+            captured_at = last.captured_at + Duration::from_nanos(1);
+        }
         if self.window.len() >= self.max_size {
             self.prune_uninteresting();
         }
-        self.window.push_back(tree);
+        self.window.push_back(HistoricalTree { captured_at, tree });
     }
 
     /// Simple decay-based pruning of the oldest element.
@@ -275,7 +393,7 @@ impl RiskHistory {
             .window
             .iter()
             .enumerate()
-            .map(|(i, tree)| {
+            .map(|(i, HistoricalTree { tree, .. })| {
                 let mut sum = 0.0;
                 let mut count = 0;
                 for env in tree.values() {
@@ -301,7 +419,7 @@ impl RiskHistory {
         let mut sums: RiskTree = BTreeMap::new();
         let mut counts: BTreeMap<(String, String, String), usize> = BTreeMap::new();
 
-        let iter = self.window.iter().chain(std::iter::once(current));
+        let iter = self.window.iter().map(|entry| &entry.tree).chain(std::iter::once(current));
 
         for tree in iter {
             for (env, cmd_map) in tree {
@@ -340,12 +458,25 @@ impl RiskHistory {
         sums
     }
 
-    /// Access historical window clone.
-    pub fn history(&self) -> Vec<RiskTree> {
+    /// Access historical window clone, oldest first.
+    pub fn history(&self) -> Vec<HistoricalTree> {
         self.window.iter().cloned().collect()
     }
 }
 
+/// Give every risk vector in `tree` exactly `target_len` metrics, padding
+/// short vectors with `0.0` and truncating long ones, so position-wise
+/// operations such as [`apply_weights`] see uniform dimensions.
+pub fn normalize_tree(tree: &mut RiskTree, target_len: usize) {
+    for cmd_map in tree.values_mut() {
+        for flag_map in cmd_map.values_mut() {
+            for vec in flag_map.values_mut() {
+                vec.resize(target_len, 0.0);
+            }
+        }
+    }
+}
+
 /// Apply categorical weights to all risk vectors in a tree.
 pub fn apply_weights(tree: &RiskTree, weights: &[f64]) -> RiskTree {
     let mut weighted: RiskTree = BTreeMap::new();
@@ -369,50 +500,388 @@ pub fn apply_weights(tree: &RiskTree, weights: &[f64]) -> RiskTree {
     weighted
 }
 
+/// Apply the weight vector of each environment in `env_weights` to that
+/// environment's risk vectors. Environments without an entry are weighted
+/// with [`DEFAULT_CATEGORY_WEIGHTS`].
+pub fn apply_env_weights(tree: &RiskTree, env_weights: &HashMap<String, Vec<f64>>) -> RiskTree {
+    let mut weighted: RiskTree = BTreeMap::new();
+    for (env, cmd_map) in tree {
+        let weights = env_weights
+            .get(env)
+            .map(Vec::as_slice)
+            .unwrap_or(&DEFAULT_CATEGORY_WEIGHTS);
+        let single_env: RiskTree = BTreeMap::from([(env.clone(), cmd_map.clone())]);
+        weighted.extend(apply_weights(&single_env, weights));
+    }
+    weighted
+}
+
+/// How [`merge_risk_trees`] combines a flag present in both trees.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Take the overlay's vector as is.
+    #[default]
+    OverlayWins,
+    /// Take the larger value of each category.
+    Max,
+    /// Take the mean of each category.
+    Average,
+}
+
+/// Layer `overlay` on top of `base`, e.g. an organisation's risk database on
+/// top of the bundled one.
+///
+/// Flags found in only one tree are copied unchanged. For flags in both,
+/// categories are combined according to `mode`; a category missing from the
+/// shorter vector takes the other tree's value.
+pub fn merge_risk_trees(base: &RiskTree, overlay: &RiskTree, mode: MergeMode) -> RiskTree {
+    let mut merged = base.clone();
+    for (env, cmd_map) in overlay {
+        for (cmd, flag_map) in cmd_map {
+            for (flag, overlay_vec) in flag_map {
+                let slot = merged
+                    .entry(env.clone())
+                    .or_default()
+                    .entry(cmd.clone())
+                    .or_default()
+                    .entry(flag.clone());
+                match slot {
+                    btree_map::Entry::Vacant(vacant) => {
+                        vacant.insert(overlay_vec.clone());
+                    }
+                    btree_map::Entry::Occupied(mut occupied) => {
+                        let combined = merge_risk_vectors(occupied.get(), overlay_vec, mode);
+                        occupied.insert(combined);
+                    }
+                }
+            }
+        }
+    }
+    merged
+}
+
+fn merge_risk_vectors(base: &RiskVector, overlay: &RiskVector, mode: MergeMode) -> RiskVector {
+    if mode == MergeMode::OverlayWins {
+        return overlay.clone();
+    }
+    (0..base.len().max(overlay.len()))
+        .map(|i| match (base.get(i), overlay.get(i)) {
+            (Some(b), Some(o)) if mode == MergeMode::Max => b.max(*o),
+            (Some(b), Some(o)) => (b + o) / 2.0,
+            (Some(v), None) | (None, Some(v)) => *v,
+            (None, None) => 0.0,
+        })
+        .collect()
+}
+
+/// Maximum number of parsed CSVs kept by [`load_risk_tree_cached`].
+pub const RISK_TREE_CACHE_CAPACITY: usize = 8;
+
+/// Parsed risk trees keyed by CSV path, reused until the file's modification
+/// time or size changes. Holds at most `capacity` files; the least recently
+/// loaded one is evicted first.
+#[derive(Debug)]
+pub struct RiskTreeCache {
+    entries: VecDeque<RiskTreeCacheEntry>,
+    capacity: usize,
+}
+
+#[derive(Debug)]
+struct RiskTreeCacheEntry {
+    path: PathBuf,
+    modified: SystemTime,
+    len: u64,
+    tree: Arc<RiskTree>,
+}
+
+impl RiskTreeCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    /// Return the tree for `path`, calling `load` only when nothing is cached
+    /// for the file's current modification time and size.
+    pub fn get_or_load_with(
+        &mut self,
+        path: &Path,
+        load: impl FnOnce(&Path) -> anyhow::Result<RiskTree>,
+    ) -> anyhow::Result<Arc<RiskTree>> {
+        let metadata = std::fs::metadata(path).with_context(|| format!("reading {}", path.display()))?;
+        let modified = metadata.modified().with_context(|| format!("reading {}", path.display()))?;
+        let len = metadata.len();
+
+        if let Some(entry) = self.entries.iter().find(|e| e.path == path)
+            && entry.modified == modified
+            && entry.len == len
+        {
+            return Ok(entry.tree.clone());
+        }
+
+        let tree = Arc::new(load(path)?);
+        self.entries.retain(|e| e.path != path);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(RiskTreeCacheEntry {
+            path: path.to_path_buf(),
+            modified,
+            len,
+            tree: tree.clone(),
+        });
+        Ok(tree)
+    }
+}
+
+lazy_static! {
+    /// Process-wide cache behind [`load_risk_tree_cached`].
+    static ref RISK_TREE_CACHE: Mutex<RiskTreeCache> = Mutex::new(RiskTreeCache::new(RISK_TREE_CACHE_CAPACITY));
+}
+
+/// Like [`load_risk_tree`], but reuses the parsed tree while the CSV at
+/// `path` is unchanged.
+pub fn load_risk_tree_cached(path: &Path) -> anyhow::Result<Arc<RiskTree>> {
+    let mut cache = match RISK_TREE_CACHE.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cache.get_or_load_with(path, load_risk_tree)
+}
+
+/// How risk CSV loaders treat a metric field that is not a number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CsvParseMode {
+    /// Fail with a [`MalformedMetric`] naming the line and column.
+    Strict,
+    /// Use `0.0` for the metric so the vector keeps its length.
+    #[default]
+    Lenient,
+}
+
+/// A metric field that does not parse as a number, reported by
+/// [`CsvParseMode::Strict`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MalformedMetric {
+    /// 1-based line in the CSV, counting the header.
+    pub line: usize,
+    /// 1-based field index in the row.
+    pub column: usize,
+    pub value: String,
+}
+
+impl std::fmt::Display for MalformedMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: metric `{}` is not a number",
+            self.line, self.column, self.value
+        )
+    }
+}
+
+impl std::error::Error for MalformedMetric {}
+
 /// Load a risk tree from a CSV file with the format produced by `risk_csv.csv`.
 pub fn load_risk_tree(path: &Path) -> anyhow::Result<RiskTree> {
+    load_risk_tree_with_mode(path, CsvParseMode::default())
+}
+
+/// Like [`load_risk_tree`], with `mode` deciding what happens to metrics
+/// that are not numbers. Empty metric fields are `0.0` in either mode.
+pub fn load_risk_tree_with_mode(path: &Path, mode: CsvParseMode) -> anyhow::Result<RiskTree> {
     let content = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse_risk_tree(&content, mode).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Parse the contents of a risk CSV, header included, as
+/// [`load_risk_tree_with_mode`] does for a file.
+pub(crate) fn parse_risk_tree(content: &str, mode: CsvParseMode) -> Result<RiskTree, MalformedMetric> {
     let mut tree: RiskTree = BTreeMap::new();
 
-    for line in content.lines().skip(1) {
-        let fields: Vec<&str> = line.split(',').collect();
-        if fields.len() < 4 {
+    for (index, line) in content.lines().enumerate().skip(1) {
+        insert_risk_row(&mut tree, line, index + 1, mode)?;
+    }
+    normalize_tree(&mut tree, DEFAULT_CATEGORY_WEIGHTS.len());
+
+    Ok(tree)
+}
+
+/// Like [`load_risk_tree`], but reads the CSV one line at a time so that very
+/// large risk databases are never held in memory as a whole.
+pub fn load_risk_tree_streaming(path: &Path) -> anyhow::Result<RiskTree> {
+    let file = std::fs::File::open(path).with_context(|| format!("reading {}", path.display()))?;
+    let mut tree: RiskTree = BTreeMap::new();
+
+    for (index, line) in BufReader::new(file).lines().enumerate().skip(1) {
+        let line = line.with_context(|| format!("reading {}", path.display()))?;
+        insert_risk_row(&mut tree, &line, index + 1, CsvParseMode::Lenient)
+            .with_context(|| format!("parsing {}", path.display()))?;
+    }
+    normalize_tree(&mut tree, DEFAULT_CATEGORY_WEIGHTS.len());
+
+    Ok(tree)
+}
+
+/// Parse CSV line `line_number` into `tree`. Rows with fewer than four
+/// fields are ignored.
+fn insert_risk_row(
+    tree: &mut RiskTree,
+    line: &str,
+    line_number: usize,
+    mode: CsvParseMode,
+) -> Result<(), MalformedMetric> {
+    let Some((env, cmd, flag, vec)) = parse_risk_row_with_mode(line, line_number, mode)? else {
+        return Ok(());
+    };
+    tree
+        .entry(env)
+        .or_default()
+        .entry(cmd)
+        .or_default()
+        .insert(flag, vec);
+    Ok(())
+}
+
+/// Split one CSV data row into its environment, binary, flag and category
+/// metrics, reading metrics leniently. `None` for rows with fewer than four
+/// fields.
+pub(crate) fn parse_risk_row(line: &str) -> Option<(String, String, String, RiskVector)> {
+    parse_risk_row_with_mode(line, 0, CsvParseMode::Lenient).unwrap_or_default()
+}
+
+fn parse_risk_row_with_mode(
+    line: &str,
+    line_number: usize,
+    mode: CsvParseMode,
+) -> Result<Option<(String, String, String, RiskVector)>, MalformedMetric> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if fields.len() < 4 {
+        return Ok(None);
+    }
+    let env = fields[0].trim().to_string();
+    let cmd = fields[1].trim().to_string();
+    let flag = fields[2].trim().to_string();
+    let mut vec = Vec::new();
+    let metrics_end = 3 + DEFAULT_CATEGORY_WEIGHTS.len();
+    for (index, f) in fields.iter().enumerate().take(metrics_end).skip(3) {
+        let f = f.trim();
+        if f.is_empty() {
+            vec.push(0.0);
             continue;
         }
-        let env = fields[0].trim().to_string();
-        let cmd = fields[1].trim().to_string();
-        let flag = fields[2].trim().to_string();
-        let mut vec = Vec::new();
-        let metrics_end = 3 + DEFAULT_CATEGORY_WEIGHTS.len();
-        for f in &fields[3..metrics_end.min(fields.len())] {
-            if let Ok(num) = f.trim().parse::<f64>() {
-                vec.push(num);
+        match (f.parse::<f64>(), mode) {
+            (Ok(num), _) => vec.push(num),
+            (Err(_), CsvParseMode::Lenient) => vec.push(0.0),
+            (Err(_), CsvParseMode::Strict) => {
+                return Err(MalformedMetric {
+                    line: line_number,
+                    column: index + 1,
+                    value: f.to_string(),
+                });
             }
         }
-        tree
-            .entry(env)
-            .or_default()
-            .entry(cmd)
-            .or_default()
-            .insert(flag, vec);
     }
+    Ok(Some((env, cmd, flag, vec)))
+}
 
-    Ok(tree)
+/// Write `tree` to `path` as a risk CSV readable by [`load_risk_tree`].
+///
+/// Only the environment, binary, flag and category columns are written.
+pub fn write_risk_tree(tree: &RiskTree, path: &Path) -> anyhow::Result<()> {
+    let mut content = String::from(
+        "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption\n",
+    );
+    for (env, cmd_map) in tree {
+        for (cmd, flag_map) in cmd_map {
+            for (flag, vec) in flag_map {
+                let metrics: Vec<String> = vec.iter().map(f64::to_string).collect();
+                content.push_str(&format!("{env},{cmd},{flag},{}\n", metrics.join(",")));
+            }
+        }
+    }
+    std::fs::write(path, content).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Return only the subtree of `tree` belonging to `env`.
+///
+/// Environment names are compared case-insensitively so that `"Linux"` and
+/// `"linux"` select the same rows. An unknown environment yields an empty tree.
+pub fn filter_risk_tree_env(tree: &RiskTree, env: &str) -> RiskTree {
+    let env = env.to_lowercase();
+    tree.iter()
+        .filter(|(name, _)| name.to_lowercase() == env)
+        .map(|(name, cmd_map)| (name.clone(), cmd_map.clone()))
+        .collect()
+}
+
+/// Load a risk tree from `path`, keeping only the rows for `env`.
+pub fn load_risk_tree_for_env(path: &Path, env: &str) -> anyhow::Result<RiskTree> {
+    let tree = load_risk_tree(path)?;
+    Ok(filter_risk_tree_env(&tree, env))
 }
 
 #[derive(Clone, Debug)]
 pub struct ThreatDeliverable {
-    pub historical: Vec<RiskTree>,
+    pub historical: Vec<HistoricalTree>,
     pub projected: RiskTree,
     pub final_tree: RiskTree,
 }
 
+impl ThreatDeliverable {
+    /// Per-flag, per-category difference `final_tree - projected`, showing
+    /// what the weights amplified (positive) or dampened (negative).
+    pub fn weight_delta(&self) -> RiskTree {
+        let mut delta: RiskTree = BTreeMap::new();
+        for (env, cmd_map) in &self.final_tree {
+            for (cmd, flag_map) in cmd_map {
+                for (flag, final_vec) in flag_map {
+                    let projected = self
+                        .projected
+                        .get(env)
+                        .and_then(|cmds| cmds.get(cmd))
+                        .and_then(|flags| flags.get(flag));
+                    let diff = final_vec
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| v - projected.and_then(|p| p.get(i)).copied().unwrap_or(0.0))
+                        .collect();
+                    delta
+                        .entry(env.clone())
+                        .or_default()
+                        .entry(cmd.clone())
+                        .or_default()
+                        .insert(flag.clone(), diff);
+                }
+            }
+        }
+        delta
+    }
+}
+
 /// Generate deliverables based on the current tree and historical data.
 pub fn generate_deliverables_with_weights(current: RiskTree, weights: &[f64]) -> ThreatDeliverable {
-    let mut history = HISTORICAL_TREE.lock().expect("Failed to lock historical tree");
+    generate_deliverables_weighted_by(current, |projected| apply_weights(projected, weights))
+}
+
+/// Like [`generate_deliverables_with_weights`], but each environment of the
+/// tree is weighted with its own vector from `env_weights`, see
+/// [`apply_env_weights`].
+pub fn generate_deliverables_with_env_weights(
+    current: RiskTree,
+    env_weights: &HashMap<String, Vec<f64>>,
+) -> ThreatDeliverable {
+    generate_deliverables_weighted_by(current, |projected| apply_env_weights(projected, env_weights))
+}
+
+/// Record `current` in the historical tree, blend it with the history and
+/// weight the projection with `weigh`.
+fn generate_deliverables_weighted_by(
+    current: RiskTree,
+    weigh: impl FnOnce(&RiskTree) -> RiskTree,
+) -> ThreatDeliverable {
+    let mut history = HISTORICAL_TREE.lock().unwrap_or_else(PoisonError::into_inner);
     history.add_tree(current.clone());
     let projected = history.blend_with_history(&current);
-    let final_tree = apply_weights(&projected, weights);
+    let final_tree = weigh(&projected);
     ThreatDeliverable {
         historical: history.history(),
         projected,
@@ -420,21 +889,24 @@ pub fn generate_deliverables_with_weights(current: RiskTree, weights: &[f64]) ->
     }
 }
 
-/// Convenience wrapper using [`DEFAULT_CATEGORY_WEIGHTS`].
+/// Convenience wrapper using [`DEFAULT_CATEGORY_WEIGHTS`] for every
+/// environment.
 pub fn generate_deliverables(current: RiskTree) -> ThreatDeliverable {
-    generate_deliverables_with_weights(current, &DEFAULT_CATEGORY_WEIGHTS)
+    generate_deliverables_with_env_weights(current, &HashMap::new())
 }
 
 /// Convert a `RiskTree` loaded from the CSV into a `ThreatMatrix`.
 pub fn load_risk_matrix(path: &Path) -> anyhow::Result<ThreatMatrix> {
-    let tree = load_risk_tree(path)?;
+    let tree = load_risk_tree_cached(path)?;
     let mut matrix = ThreatMatrix::new(100, 0.05);
 
     for env in tree.values() {
         for cmd in env.values() {
             for (flag, vec) in cmd {
                 let score = if vec.is_empty() { DEFAULT_RISK_SCORE } else { vec.iter().sum::<f64>() / vec.len() as f64 };
-                matrix.add_assessment(ThreatAssessment::new(score, score, vec![flag.clone()]));
+                matrix.add_assessment(
+                    ThreatAssessment::new(score, score, vec![flag.clone()]).with_categories(vec.clone()),
+                );
             }
         }
     }
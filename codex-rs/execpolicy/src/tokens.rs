@@ -0,0 +1,9 @@
+/// Rough token count for `text`, for rate limiting rather than billing.
+///
+/// Uses the same four-characters-per-token heuristic as the exec output
+/// delay, but never reports fewer tokens than there are words.
+pub fn estimate_tokens(text: &str) -> usize {
+    let by_chars = text.chars().count().div_ceil(4);
+    let by_words = text.split_whitespace().count();
+    by_chars.max(by_words)
+}
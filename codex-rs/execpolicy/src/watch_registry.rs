@@ -0,0 +1,73 @@
+//! Process-wide record of the files the `notify` watchers in this crate are
+//! watching, for answering "why wasn't my change picked up?".
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// Registry every watcher in this crate registers with.
+    pub static ref WATCH_REGISTRY: WatchRegistry = WatchRegistry::default();
+}
+
+/// Watched paths, each with the number of live watchers on it.
+#[derive(Debug, Default)]
+pub struct WatchRegistry {
+    paths: Mutex<BTreeMap<PathBuf, usize>>,
+}
+
+impl WatchRegistry {
+    /// Record that `path` is being watched until the returned registration
+    /// is dropped.
+    pub fn register(&'static self, path: &Path) -> WatchRegistration {
+        *self.lock().entry(path.to_path_buf()).or_default() += 1;
+        WatchRegistration {
+            registry: self,
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Every path with at least one live watcher, sorted.
+    pub fn watched_paths(&self) -> Vec<PathBuf> {
+        self.lock().keys().cloned().collect()
+    }
+
+    fn deregister(&self, path: &Path) {
+        let mut paths = self.lock();
+        if let Some(count) = paths.get_mut(path) {
+            *count -= 1;
+            if *count == 0 {
+                paths.remove(path);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<PathBuf, usize>> {
+        match self.paths.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Keeps a path in its [`WatchRegistry`]; held by the watcher that watches it.
+#[derive(Debug)]
+pub struct WatchRegistration {
+    registry: &'static WatchRegistry,
+    path: PathBuf,
+}
+
+impl Drop for WatchRegistration {
+    fn drop(&mut self) {
+        self.registry.deregister(&self.path);
+    }
+}
+
+/// Paths currently watched by any watcher in this crate, sorted.
+pub fn watched_paths() -> Vec<PathBuf> {
+    WATCH_REGISTRY.watched_paths()
+}
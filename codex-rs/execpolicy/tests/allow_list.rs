@@ -0,0 +1,80 @@
+#![expect(clippy::expect_used)]
+use codex_execpolicy::AllowList;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::PolicyWatcher;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn allow_listed_calls_skip_the_starlark_policy() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        r#"define_program(program="ls", args=[], system_path=["/bin/ls"])"#,
+    )?;
+    let allow_list_path = dir.path().join("allow.txt");
+    fs::write(
+        &allow_list_path,
+        "# trusted tools\n/usr/bin/git status\n\n/usr/bin/true\n",
+    )?;
+    let allow_list = AllowList::load(&allow_list_path)?;
+    assert_eq!(allow_list.len(), 2);
+
+    let watcher = PolicyWatcher::new(policy)?.with_allow_list(allow_list);
+
+    let result = watcher
+        .check(&ExecCall::new("/usr/bin/git", &["status", "--short"]))
+        .expect("allow-listed call");
+    let MatchedExec::Match { exec } = result else {
+        panic!("expected a match, got {result:?}");
+    };
+    assert_eq!(exec.program, "/usr/bin/git");
+    assert_eq!(exec.args.len(), 2);
+    assert!(watcher.check(&ExecCall::new("/usr/bin/true", &[])).is_ok());
+    assert_eq!(watcher.policy_checks(), 0);
+
+    // Not covered by the allow-list: a different subcommand and a bare
+    // program name fall through to the starlark policy.
+    assert!(watcher.check(&ExecCall::new("/usr/bin/git", &["push"])).is_err());
+    assert!(matches!(
+        watcher.check(&ExecCall::new("ls", &[])).expect("policy match"),
+        MatchedExec::Match { .. }
+    ));
+    assert_eq!(watcher.policy_checks(), 2);
+    Ok(())
+}
+
+#[test]
+fn forbidden_patterns_apply_to_allow_listed_calls() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        r#"
+forbid_substrings(["--force"])
+forbid_program_regex("^/usr/bin/curl$", "no network tools")
+"#,
+    )?;
+    let allow_list_path = dir.path().join("allow.txt");
+    fs::write(&allow_list_path, "/usr/bin/git push\n/usr/bin/curl\n")?;
+    let watcher = PolicyWatcher::new(policy)?.with_allow_list(AllowList::load(&allow_list_path)?);
+
+    let result = watcher
+        .check(&ExecCall::new("/usr/bin/git", &["push", "--force"]))
+        .expect("forbidden call");
+    assert!(matches!(result, MatchedExec::Forbidden { .. }), "got {result:?}");
+    let result = watcher
+        .check(&ExecCall::new("/usr/bin/curl", &[]))
+        .expect("forbidden call");
+    assert!(matches!(result, MatchedExec::Forbidden { .. }), "got {result:?}");
+
+    // Allow-listed calls no forbidden pattern matches still skip the program specs.
+    let result = watcher
+        .check(&ExecCall::new("/usr/bin/git", &["push"]))
+        .expect("allow-listed call");
+    assert!(matches!(result, MatchedExec::Match { .. }), "got {result:?}");
+    assert_eq!(watcher.policy_checks(), 0);
+    Ok(())
+}
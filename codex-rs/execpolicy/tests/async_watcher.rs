@@ -0,0 +1,42 @@
+mod common;
+
+use codex_execpolicy::threat_state::load_risk_tree;
+use common::{FULL_HEADER, LS_POLICY};
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn register_tool_async_appends_a_row() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, LS_POLICY, FULL_HEADER)?;
+
+    watcher.register_tool_async("linux", "rsync", "--delete", 0.25).await?;
+
+    let csv = dir.path().join("risk.csv");
+    let content = fs::read_to_string(&csv)?;
+    assert!(content.starts_with(FULL_HEADER));
+    assert!(content.ends_with(
+        "\nlinux,rsync,--delete,0.25,0.25,0.25,0.25,0.25,none,none,none,none,none,none,none"
+    ));
+    let tree = load_risk_tree(&csv)?;
+    assert_eq!(tree["linux"]["rsync"]["--delete"], vec![0.25; 5]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reload_and_prefilter_async_follow_the_risk_threshold() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, LS_POLICY, FULL_HEADER)?;
+    watcher.prefilter_csv_async().await?;
+    fs::write(
+        dir.path().join("policy.star"),
+        r#"define_program(program="cat", args=[ARG_RFILES])"#,
+    )?;
+    let diff = watcher.reload_async().await?;
+    assert_eq!(diff.added_programs, vec!["cat"]);
+
+    watcher.register_tool_async("linux", "rm", "-r", 4.0).await?;
+    assert!(watcher.prefilter_csv_async().await.is_err());
+    assert!(watcher.reload_async().await.is_err());
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const DEL_CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption
+windows,del,/s,5,1,5,2,3
+linux,del,/s,0,0,0,0,0
+";
+
+fn check_with_env(policy: &Path, risk_csv: &Path, env: &str) -> anyhow::Result<serde_json::Value> {
+    let output = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .arg("--policy")
+        .arg(policy)
+        .arg("--no-prefilter")
+        .args(["--env", env])
+        .arg("--risk-csv")
+        .arg(risk_csv)
+        .args(["check", "del", "/s", "build"])
+        .output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+#[test]
+fn env_flag_selects_environment_specific_risk() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        "define_program(program=\"del\", options=[flag(\"/s\")], args=[ARG_RFILES])",
+    )?;
+    let risk_csv = dir.path().join("risk.csv");
+    fs::write(&risk_csv, DEL_CSV)?;
+
+    let windows = check_with_env(&policy, &risk_csv, "windows")?;
+    assert_eq!(windows["result"], "match");
+    assert_eq!(windows["env"], "windows");
+    assert_eq!(windows["threat_level"], "High");
+
+    let linux = check_with_env(&policy, &risk_csv, "linux")?;
+    assert_eq!(linux["result"], "match");
+    assert_eq!(linux["threat_level"], "Low");
+    Ok(())
+}
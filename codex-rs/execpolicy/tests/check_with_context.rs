@@ -0,0 +1,36 @@
+#![expect(clippy::expect_used)]
+use std::path::PathBuf;
+
+use codex_execpolicy::CheckContext;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::Result;
+
+#[test]
+fn rm_is_limited_to_writable_roots() -> Result<()> {
+    let policy = PolicyParser::new(
+        "rm_policy",
+        "define_program(program=\"rm\", options=[flag(\"-r\")], args=[ARG_WFILE])",
+    )
+    .parse()
+    .expect("failed to parse policy");
+    let project = PathBuf::from("/work/project");
+    let ctx = CheckContext::new(project.clone(), vec![project]);
+
+    let inside = ExecCall::new("rm", &["./inside"]);
+    assert!(matches!(
+        policy.check_with_context(&inside, &ctx)?,
+        MatchedExec::Match { .. }
+    ));
+
+    let outside = ExecCall::new("rm", &["../outside"]);
+    match policy.check_with_context(&outside, &ctx)? {
+        MatchedExec::Forbidden { reason, .. } => assert!(reason.contains("../outside")),
+        other => panic!("expected rm ../outside to be forbidden, got {other:?}"),
+    }
+
+    // Without a context the path is not checked.
+    assert!(matches!(policy.check(&outside)?, MatchedExec::Match { .. }));
+    Ok(())
+}
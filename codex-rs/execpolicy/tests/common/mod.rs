@@ -0,0 +1,25 @@
+#![allow(dead_code)]
+
+use codex_execpolicy::PolicyWatcher;
+use std::fs;
+use tempfile::TempDir;
+
+/// Risk CSV header with the five threat categories.
+pub const HEADER: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption";
+
+/// [`HEADER`] followed by the translation columns that
+/// [`PolicyWatcher::register_tool`] fills in.
+pub const FULL_HEADER: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption,CmdMacOS,CmdLinux,CmdWinCmd,CmdWinPs,CmdWinWsl,api,blackbox";
+
+pub const LS_POLICY: &str = r#"define_program(program="ls", args=[], system_path=["/bin/ls"])"#;
+pub const RM_POLICY: &str = r#"define_program(program="rm", args=[], system_path=["/bin/rm"])"#;
+
+/// A watcher over `policy` written to `policy.star` in `dir`, with `risk_csv`
+/// written to `risk.csv` next to it as its risk database.
+pub fn watcher(dir: &TempDir, policy: &str, risk_csv: &str) -> anyhow::Result<PolicyWatcher> {
+    let policy_path = dir.path().join("policy.star");
+    let csv_path = dir.path().join("risk.csv");
+    fs::write(&policy_path, policy)?;
+    fs::write(&csv_path, risk_csv)?;
+    Ok(PolicyWatcher::new(policy_path)?.with_risk_csv(csv_path))
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use codex_execpolicy::threat_state::load_risk_tree;
+use common::{FULL_HEADER, RM_POLICY};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn compact_drops_low_risk_and_superseded_rows() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(
+        &dir,
+        RM_POLICY,
+        &format!(
+            "{FULL_HEADER}\n\
+             linux,rm,-rf /,5,1,5,2,3,rm,rm,del,del,rm,none,none\n\
+             linux,ls,-l,0,0,0,0,0,ls,ls,dir,dir,ls,none,none\n\
+             linux,cp,-r,0,0,0,0,0,cp,cp,copy,copy,cp,none,none\n\
+             linux,rm,-r,5,1,5,2,3,rm,rm,del,del,rm,none,none\n\
+             macos,cat,,0,0,0,0,0,cat,cat,type,type,cat,none,none\n"
+        ),
+    )?;
+    let risk_csv = dir.path().join("risk.csv");
+    // Appended rows: a zero-risk supersession of `cp -r` and a new risky entry.
+    watcher.register_tool("linux", "cp", "-r", 0.0)?;
+    watcher.register_tool("linux", "dd", "of=", 2.0)?;
+
+    let removed = watcher.compact_csv(0.5)?;
+    // ls -l, both cp -r rows and macos cat.
+    assert_eq!(removed, 4);
+
+    let content = fs::read_to_string(&risk_csv)?;
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some(FULL_HEADER));
+    assert_eq!(
+        lines.collect::<Vec<_>>(),
+        vec![
+            "linux,rm,-rf /,5,1,5,2,3,rm,rm,del,del,rm,none,none",
+            "linux,rm,-r,5,1,5,2,3,rm,rm,del,del,rm,none,none",
+            "linux,dd,of=,2,2,2,2,2,none,none,none,none,none,none,none",
+        ]
+    );
+
+    let tree = load_risk_tree(&risk_csv)?;
+    assert!(!tree["linux"].contains_key("ls"));
+    assert!(!tree["linux"].contains_key("cp"));
+    assert!(!tree.contains_key("macos"));
+    assert_eq!(tree["linux"]["dd"]["of="], vec![2.0; 5]);
+
+    // Nothing left to remove.
+    assert_eq!(watcher.compact_csv(0.5)?, 0);
+    Ok(())
+}
+
+#[test]
+fn superseded_row_is_replaced_by_latest() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, RM_POLICY, &format!("{FULL_HEADER}\nlinux,rm,-f,1,1,1,1,1\n"))?;
+    let risk_csv = dir.path().join("risk.csv");
+    watcher.register_tool("linux", "rm", "-f", 3.0)?;
+
+    assert_eq!(watcher.compact_csv(0.0)?, 1);
+    let tree = load_risk_tree(&risk_csv)?;
+    assert_eq!(tree["linux"]["rm"]["-f"], vec![3.0; 5]);
+    Ok(())
+}
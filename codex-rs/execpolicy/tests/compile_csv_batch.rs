@@ -0,0 +1,39 @@
+mod common;
+
+use codex_execpolicy::policy_watcher::decompose_flags;
+use common::{HEADER, LS_POLICY};
+use tempfile::TempDir;
+
+const LS_ROWS: &str = "linux,ls,-l,0,1,0,1,0
+linux,ls,-a,0,2,0,2,0
+linux,ls,--all,0,3,0,3,0
+";
+
+#[test]
+fn decompose_splits_only_bundled_short_flags() {
+    assert_eq!(decompose_flags("-la"), vec!["-l", "-a"]);
+    assert_eq!(decompose_flags("-l"), vec!["-l"]);
+    assert_eq!(decompose_flags("--all"), vec!["--all"]);
+    assert_eq!(decompose_flags("-"), vec!["-"]);
+    assert_eq!(decompose_flags("-n5"), vec!["-n5"]);
+    assert_eq!(decompose_flags("foo"), vec!["foo"]);
+}
+
+#[test]
+fn combined_short_flags_match_individual_rows() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, LS_POLICY, &format!("{HEADER}\n{LS_ROWS}"))?;
+
+    let batch = watcher.compile_csv_batch(vec!["ls -la".to_string()], Some("linux"))?;
+    assert_eq!(
+        batch,
+        vec![
+            ("-l".to_string(), vec![0.0, 1.0, 0.0, 1.0, 0.0]),
+            ("-a".to_string(), vec![0.0, 2.0, 0.0, 2.0, 0.0]),
+        ]
+    );
+
+    let batch = watcher.compile_csv_batch(vec!["ls --all".to_string()], Some("linux"))?;
+    assert_eq!(batch, vec![("--all".to_string(), vec![0.0, 3.0, 0.0, 3.0, 0.0])]);
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use codex_execpolicy::threat_state::{
+    RiskTree, generate_deliverables, generate_deliverables_with_env_weights, risk_vector_score,
+};
+
+fn tree_with_envs(envs: &[&str]) -> RiskTree {
+    let mut tree: RiskTree = BTreeMap::new();
+    for env in envs {
+        tree.entry(env.to_string())
+            .or_default()
+            .entry("rm".to_string())
+            .or_default()
+            .insert("-r".to_string(), vec![1.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+    tree
+}
+
+#[test]
+fn each_env_uses_its_own_weights() {
+    let env_weights = HashMap::from([
+        ("linux".to_string(), vec![1.0, 1.0, 1.0, 1.0, 1.0]),
+        ("windows".to_string(), vec![0.0, 0.0, 3.0, 0.0, 0.0]),
+    ]);
+    let deliverable = generate_deliverables_with_env_weights(
+        tree_with_envs(&["linux", "windows", "macos"]),
+        &env_weights,
+    );
+
+    let score = |env: &str| risk_vector_score(&deliverable.final_tree[env]["rm"]["-r"]);
+    assert_eq!(
+        deliverable.projected["linux"]["rm"]["-r"],
+        deliverable.projected["windows"]["rm"]["-r"]
+    );
+    assert_ne!(score("linux"), score("windows"));
+    assert_eq!(
+        deliverable.final_tree["windows"]["rm"]["-r"],
+        vec![0.0, 0.0, 3.0, 0.0, 0.0]
+    );
+
+    // Environments without weights fall back to the defaults.
+    let defaults = generate_deliverables(tree_with_envs(&["macos"]));
+    assert_eq!(
+        deliverable.final_tree["macos"]["rm"]["-r"],
+        defaults.final_tree["macos"]["rm"]["-r"]
+    );
+}
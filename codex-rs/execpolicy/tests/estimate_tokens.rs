@@ -0,0 +1,19 @@
+use codex_execpolicy::estimate_tokens;
+
+#[test]
+fn estimate_scales_with_input_length() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("ls"), 1);
+
+    let short = "cargo test --workspace ".repeat(10);
+    let long = short.repeat(10);
+    let short_tokens = estimate_tokens(&short);
+    let long_tokens = estimate_tokens(&long);
+    assert_eq!(short_tokens, short.len().div_ceil(4));
+    assert!(long_tokens >= 9 * short_tokens && long_tokens <= 11 * short_tokens);
+}
+
+#[test]
+fn many_short_words_count_at_least_one_token_each() {
+    assert_eq!(estimate_tokens("a b c d e f g h"), 8);
+}
@@ -0,0 +1,32 @@
+use codex_execpolicy::threat_state::{ThreatAssessment, ThreatLevel, ThreatMatrix};
+
+#[test]
+fn categories_are_evaluated_independently() {
+    let mut matrix = ThreatMatrix::new(10, 0.0);
+    matrix.add_assessment(
+        ThreatAssessment::new(1.0, 1.0, vec!["-x".to_string()]).with_categories(vec![0.5, 9.0, 0.5]),
+    );
+    matrix.add_assessment(
+        ThreatAssessment::new(1.0, 1.0, vec!["-y".to_string()]).with_categories(vec![0.5, 7.0, 0.5]),
+    );
+
+    assert_eq!(
+        matrix.evaluate_by_category(&["filesystem", "network", "process"]),
+        vec![
+            ("filesystem".to_string(), ThreatLevel::Low),
+            ("network".to_string(), ThreatLevel::High),
+            ("process".to_string(), ThreatLevel::Low),
+        ]
+    );
+}
+
+#[test]
+fn missing_categories_fall_back_to_evaluated_danger() {
+    let mut matrix = ThreatMatrix::new(10, 0.0);
+    matrix.add_assessment(ThreatAssessment::new(3.0, 3.0, vec!["-r".to_string()]));
+    assert_eq!(
+        matrix.evaluate_by_category(&["filesystem"]),
+        vec![("filesystem".to_string(), ThreatLevel::Medium)]
+    );
+    assert!(ThreatMatrix::new(10, 0.0).evaluate_by_category(&[]).is_empty());
+}
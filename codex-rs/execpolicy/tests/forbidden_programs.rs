@@ -0,0 +1,35 @@
+#![expect(clippy::expect_used)]
+use codex_execpolicy::PolicyParser;
+
+#[test]
+fn forbidden_programs_lists_forbidden_specs_with_reasons() {
+    let unparsed_policy = r#"
+define_program(
+    program="rm",
+    args=[ARG_RFILES],
+    forbidden="rm is never allowed",
+)
+define_program(
+    program="ls",
+    args=[ARG_RFILES_OR_CWD],
+    system_path=["/bin/ls"],
+)
+define_program(
+    program="dd",
+    args=[ARG_OPAQUE_VALUE],
+    forbidden="dd can overwrite devices",
+)
+forbid_program_regex("^shutdown$", "no power management")
+"#;
+    let policy = PolicyParser::new("forbidden_programs", unparsed_policy)
+        .parse()
+        .expect("failed to parse policy");
+
+    assert_eq!(
+        policy.forbidden_programs(),
+        vec![
+            ("dd".to_string(), "dd can overwrite devices".to_string()),
+            ("rm".to_string(), "rm is never allowed".to_string()),
+        ]
+    );
+}
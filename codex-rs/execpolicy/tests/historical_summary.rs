@@ -0,0 +1,28 @@
+use codex_execpolicy::ThreatLevel;
+use codex_execpolicy::threat_state::ThreatAssessment;
+use codex_execpolicy::threat_state::ThreatMatrix;
+
+fn matrix_of(dangers: &[f64]) -> ThreatMatrix {
+    let mut matrix = ThreatMatrix::new(10, 0.05);
+    for danger in dangers {
+        matrix.add_assessment(ThreatAssessment::new(*danger, *danger, vec!["-f".to_string()]));
+    }
+    matrix
+}
+
+#[test]
+fn summary_tracks_historical_updates() {
+    // This binary is the only user of the global matrix, so it starts empty.
+    let empty = ThreatMatrix::historical_summary();
+    assert_eq!(empty.count, 0);
+    assert_eq!(empty.level, ThreatLevel::Low);
+
+    matrix_of(&[1.0, 2.0]).update_historical_matrix();
+    matrix_of(&[3.0, 6.0]).update_historical_matrix();
+
+    let summary = ThreatMatrix::historical_summary();
+    assert_eq!(summary.count, 4);
+    assert_eq!(summary.average_danger, 3.0);
+    assert_eq!(summary.level, ThreatLevel::Medium);
+    assert_eq!(summary, ThreatMatrix::get_historical_matrix().summary());
+}
@@ -0,0 +1,30 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn arg_type_violation_names_the_bad_arg() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        "define_program(program=\"sleep\", args=[ARG_POS_INT], system_path=[\"/bin/sleep\"])",
+    )?;
+    let output = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .arg("--policy")
+        .arg(&policy)
+        .arg("--no-prefilter")
+        .args(["check", "sleep", "soon"])
+        .output()?;
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["result"], "unverified");
+    let error = &json["error"];
+    assert_eq!(error["type"], "InvalidArg");
+    assert_eq!(error["program"], "sleep");
+    assert_eq!(error["index"], 0);
+    assert_eq!(error["value"], "soon");
+    assert_eq!(error["cause"]["type"], "InvalidPositiveInteger");
+    assert!(error["message"].as_str().is_some_and(|m| m.contains("soon")));
+    Ok(())
+}
@@ -40,12 +40,26 @@ define_program(
     );
 
     let invalid_call = ExecCall::new("fake_executable", &["subcommand", "not-a-real-subcommand"]);
+    let result = policy.check(&invalid_call);
+    let Err(Error::InvalidArg {
+        program,
+        index,
+        value,
+        cause,
+        ..
+    }) = result
+    else {
+        panic!("expected an invalid argument, got {result:?}");
+    };
+    assert_eq!(program, "fake_executable");
+    assert_eq!(index, 1);
+    assert_eq!(value, "not-a-real-subcommand");
     assert_eq!(
-        Err(Error::LiteralValueDidNotMatch {
+        *cause,
+        Error::LiteralValueDidNotMatch {
             expected: "sub-subcommand".to_string(),
             actual: "not-a-real-subcommand".to_string()
-        }),
-        policy.check(&invalid_call)
+        }
     );
     Ok(())
 }
@@ -0,0 +1,28 @@
+use codex_execpolicy::threat_state::{
+    ThreatAssessment, ThreatMatrix, load_risk_matrix, load_risk_tree, write_risk_tree,
+};
+use tempfile::TempDir;
+
+#[test]
+fn matrix_round_trips_through_a_risk_tree() -> anyhow::Result<()> {
+    let mut matrix = ThreatMatrix::new(10, 0.0);
+    matrix.add_assessment(ThreatAssessment::new(3.0, 3.0, vec!["-r".to_string(), "-f".to_string()]));
+    matrix.add_assessment(
+        ThreatAssessment::new(1.0, 1.0, vec!["-i".to_string()]).with_categories(vec![0.0, 1.0, 2.0, 1.0, 1.0]),
+    );
+
+    let tree = matrix.to_risk_tree("linux", "rm");
+    let flags = &tree["linux"]["rm"];
+    assert_eq!(flags.keys().collect::<Vec<_>>(), vec!["-f", "-i", "-r"]);
+    assert_eq!(flags["-r"], vec![3.0; 5]);
+    assert_eq!(flags["-i"], vec![0.0, 1.0, 2.0, 1.0, 1.0]);
+
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    write_risk_tree(&tree, &path)?;
+    assert_eq!(load_risk_tree(&path)?, tree);
+
+    let reloaded = load_risk_matrix(&path)?;
+    assert_eq!(reloaded.to_risk_tree("linux", "rm"), tree);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use codex_execpolicy::threat_state::{MergeMode, RiskTree, merge_risk_trees};
+
+fn tree(rows: &[(&str, &str, &str, &[f64])]) -> RiskTree {
+    let mut tree: RiskTree = BTreeMap::new();
+    for (env, cmd, flag, vec) in rows {
+        tree.entry(env.to_string())
+            .or_default()
+            .entry(cmd.to_string())
+            .or_default()
+            .insert(flag.to_string(), vec.to_vec());
+    }
+    tree
+}
+
+fn layers() -> (RiskTree, RiskTree) {
+    let base = tree(&[
+        ("linux", "rm", "-r", &[4.0, 0.0, 2.0]),
+        ("linux", "ls", "-l", &[0.0, 0.0, 0.0]),
+    ]);
+    let overlay = tree(&[
+        ("linux", "rm", "-r", &[2.0, 2.0, 4.0]),
+        ("linux", "curl", "-o", &[1.0, 3.0, 0.0]),
+        ("windows", "del", "/s", &[5.0, 0.0, 0.0]),
+    ]);
+    (base, overlay)
+}
+
+fn assert_disjoint_flags_kept(merged: &RiskTree) {
+    assert_eq!(merged["linux"]["ls"]["-l"], vec![0.0, 0.0, 0.0]);
+    assert_eq!(merged["linux"]["curl"]["-o"], vec![1.0, 3.0, 0.0]);
+    assert_eq!(merged["windows"]["del"]["/s"], vec![5.0, 0.0, 0.0]);
+}
+
+#[test]
+fn overlay_wins_replaces_shared_flags() {
+    let (base, overlay) = layers();
+    let merged = merge_risk_trees(&base, &overlay, MergeMode::OverlayWins);
+    assert_eq!(merged["linux"]["rm"]["-r"], vec![2.0, 2.0, 4.0]);
+    assert_disjoint_flags_kept(&merged);
+}
+
+#[test]
+fn max_takes_the_larger_category() {
+    let (base, overlay) = layers();
+    let merged = merge_risk_trees(&base, &overlay, MergeMode::Max);
+    assert_eq!(merged["linux"]["rm"]["-r"], vec![4.0, 2.0, 4.0]);
+    assert_disjoint_flags_kept(&merged);
+}
+
+#[test]
+fn average_takes_the_mean_category() {
+    let (base, overlay) = layers();
+    let merged = merge_risk_trees(&base, &overlay, MergeMode::Average);
+    assert_eq!(merged["linux"]["rm"]["-r"], vec![3.0, 1.0, 3.0]);
+    assert_disjoint_flags_kept(&merged);
+}
+
+#[test]
+fn shorter_vectors_keep_the_other_trees_categories() {
+    let base = tree(&[("linux", "rm", "-r", &[4.0, 1.0])]);
+    let overlay = tree(&[("linux", "rm", "-r", &[2.0, 3.0, 6.0])]);
+    let merged = merge_risk_trees(&base, &overlay, MergeMode::Average);
+    assert_eq!(merged["linux"]["rm"]["-r"], vec![3.0, 2.0, 6.0]);
+}
@@ -0,0 +1,38 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const FORBIDDEN_EXIT_CODE: i32 = 14;
+
+fn check_rm(extra_args: &[&str]) -> anyhow::Result<std::process::Output> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        "define_program(program=\"rm\", options=[flag(\"-r\"), flag(\"-f\")], args=[ARG_UNVERIFIED_VARARGS], system_path=[\"/bin/rm\"])",
+    )?;
+    let output = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .arg("--policy")
+        .arg(&policy)
+        .args(extra_args)
+        .args(["check", "rm", "-r", "-f", "/"])
+        .output()?;
+    Ok(output)
+}
+
+#[test]
+fn prefilter_blocks_by_default() -> anyhow::Result<()> {
+    let output = check_rm(&[])?;
+    assert_eq!(output.status.code(), Some(FORBIDDEN_EXIT_CODE));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rejected by prefilter"));
+    Ok(())
+}
+
+#[test]
+fn no_prefilter_reaches_policy_check() -> anyhow::Result<()> {
+    let output = check_rm(&["--no-prefilter"])?;
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["result"], "match");
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use codex_execpolicy::threat_state::{
+    DEFAULT_CATEGORY_WEIGHTS, RiskTree, load_risk_tree, load_risk_tree_streaming, normalize_tree,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn vector_lengths(tree: &RiskTree) -> Vec<usize> {
+    tree.values()
+        .flat_map(|cmd_map| cmd_map.values())
+        .flat_map(|flag_map| flag_map.values())
+        .map(Vec::len)
+        .collect()
+}
+
+#[test]
+fn ragged_csv_loads_with_uniform_vectors() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("ragged.csv");
+    fs::write(
+        &path,
+        "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption\n\
+         linux,rm,-r,5,1,5,2,3\n\
+         linux,rm,-f,4,2,,,\n\
+         linux,ls,-l,1\n\
+         macos,cp,-R,2,2,2,2,2,9,9\n",
+    )?;
+
+    let target = DEFAULT_CATEGORY_WEIGHTS.len();
+    for tree in [load_risk_tree(&path)?, load_risk_tree_streaming(&path)?] {
+        assert!(vector_lengths(&tree).iter().all(|len| *len == target));
+        assert_eq!(tree["linux"]["rm"]["-f"], vec![4.0, 2.0, 0.0, 0.0, 0.0]);
+        assert_eq!(tree["linux"]["ls"]["-l"], vec![1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(tree["macos"]["cp"]["-R"], vec![2.0; 5]);
+    }
+    Ok(())
+}
+
+#[test]
+fn normalize_pads_and_truncates() {
+    let mut tree = RiskTree::new();
+    let flags = tree
+        .entry("linux".to_string())
+        .or_default()
+        .entry("rm".to_string())
+        .or_default();
+    flags.insert("-r".to_string(), vec![1.0]);
+    flags.insert("-f".to_string(), vec![1.0, 2.0, 3.0, 4.0]);
+
+    normalize_tree(&mut tree, 3);
+
+    assert_eq!(tree["linux"]["rm"]["-r"], vec![1.0, 0.0, 0.0]);
+    assert_eq!(tree["linux"]["rm"]["-f"], vec![1.0, 2.0, 3.0]);
+    assert_eq!(vector_lengths(&tree), vec![3, 3]);
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn check_with_format(format: Option<&str>, command: &[&str]) -> anyhow::Result<String> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        r#"
+define_program(program="ls", options=[flag("-l")], args=[], system_path=["/bin/ls"])
+define_program(program="cp", args=[ARG_RFILE, ARG_WFILE], forbidden="cp may overwrite files")
+"#,
+    )?;
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"));
+    cmd.arg("--policy").arg(&policy);
+    if let Some(format) = format {
+        cmd.args(["--format", format]);
+    }
+    let output = cmd.arg("check").args(command).output()?;
+    assert_eq!(output.status.code(), Some(0));
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[test]
+fn default_format_is_compact_json() -> anyhow::Result<()> {
+    let stdout = check_with_format(None, &["ls", "-l"])?;
+    assert_eq!(stdout.trim_end().lines().count(), 1);
+    let json: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert_eq!(json["result"], "match");
+    assert_eq!(stdout, check_with_format(Some("json"), &["ls", "-l"])?);
+    Ok(())
+}
+
+#[test]
+fn pretty_format_is_indented_json() -> anyhow::Result<()> {
+    let stdout = check_with_format(Some("pretty"), &["ls", "-l"])?;
+    assert!(stdout.trim_end().lines().count() > 1);
+    let json: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert_eq!(json["result"], "match");
+    Ok(())
+}
+
+#[test]
+fn text_format_is_one_readable_line() -> anyhow::Result<()> {
+    let stdout = check_with_format(Some("text"), &["cp", "a", "b"])?;
+    assert_eq!(stdout, "FORBIDDEN: cp a b — cp may overwrite files\n");
+    assert!(serde_json::from_str::<serde_json::Value>(&stdout).is_err());
+
+    let stdout = check_with_format(Some("text"), &["ls", "-l"])?;
+    assert_eq!(stdout, "MATCH: ls -l\n");
+    Ok(())
+}
@@ -0,0 +1,50 @@
+#![expect(clippy::expect_used)]
+use std::fs;
+use std::time::Duration;
+
+use codex_execpolicy::{ExecCall, MatchedExec, PolicyWatcher};
+use tempfile::TempDir;
+
+#[test]
+fn reload_waits_out_a_rename_over_save() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("policy.star");
+    let risk_csv = dir.path().join("risk.csv");
+    fs::write(&risk_csv, "Environment,Binary,Flag\n")?;
+    fs::write(&path, "define_program(program=\"ls\", args=[], system_path=[\"/bin/ls\"])")?;
+    let watcher = PolicyWatcher::new(path.clone())?.with_risk_csv(risk_csv);
+
+    // Simulate an editor's atomic save: the target disappears, and the new
+    // contents are renamed into place shortly afterwards.
+    fs::remove_file(&path)?;
+    let temp = dir.path().join(".policy.star.swp");
+    fs::write(&temp, "define_program(program=\"cat\", args=[], system_path=[\"/bin/cat\"])")?;
+    let saver = std::thread::spawn({
+        let path = path.clone();
+        move || {
+            std::thread::sleep(Duration::from_millis(80));
+            fs::rename(&temp, &path).expect("rename over policy");
+        }
+    });
+
+    watcher.reload()?;
+    saver.join().expect("saver thread");
+
+    let exec = ExecCall::new("cat", &[]);
+    assert!(matches!(watcher.policy().check(&exec).expect("check"), MatchedExec::Match { .. }));
+    Ok(())
+}
+
+#[test]
+fn missing_policy_still_fails_after_retrying() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("policy.star");
+    let risk_csv = dir.path().join("risk.csv");
+    fs::write(&risk_csv, "Environment,Binary,Flag\n")?;
+    fs::write(&path, "define_program(program=\"ls\", args=[], system_path=[\"/bin/ls\"])")?;
+    let watcher = PolicyWatcher::new(path.clone())?.with_risk_csv(risk_csv);
+
+    fs::remove_file(&path)?;
+    assert!(watcher.reload().is_err());
+    Ok(())
+}
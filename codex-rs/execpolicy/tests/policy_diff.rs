@@ -0,0 +1,43 @@
+mod common;
+
+use codex_execpolicy::PolicyDiff;
+use common::HEADER;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn reload_reports_added_changed_and_removed_programs() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(
+        &dir,
+        r#"
+define_program(program="ls", args=[], system_path=["/bin/ls"])
+define_program(program="cat", args=[ARG_RFILES])
+"#,
+        &format!("{HEADER}\nlinux,ls,-l,0,0,0,0,0\n"),
+    )?;
+    let policy = dir.path().join("policy.star");
+    assert!(watcher.reload()?.is_empty());
+
+    fs::write(
+        &policy,
+        r#"
+define_program(program="ls", options=[flag("-l")], args=[], system_path=["/bin/ls"])
+define_program(program="rm", args=[ARG_WFILE])
+"#,
+    )?;
+    let diff = watcher.reload()?;
+    assert_eq!(
+        diff,
+        PolicyDiff {
+            added_programs: vec!["rm".to_string()],
+            removed_programs: vec!["cat".to_string()],
+            changed_programs: vec!["ls".to_string()],
+        }
+    );
+    assert_eq!(diff.to_string(), "rm added, cat removed, ls changed");
+
+    // Nothing changed since the last reload.
+    assert!(watcher.reload()?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,52 @@
+#![expect(clippy::expect_used)]
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+
+fn parse(source: &str) -> Policy {
+    PolicyParser::new("fingerprint", source)
+        .parse()
+        .expect("failed to parse policy")
+}
+
+#[test]
+fn formatting_does_not_change_the_fingerprint() {
+    let compact = parse(
+        r#"
+define_program(program="ls", options=[flag("-l"), flag("-a")], args=[ARG_RFILES_OR_CWD], system_path=["/bin/ls"])
+define_program(program="head", options=[opt("-n", ARG_POS_INT)], args=[ARG_RFILES])
+forbid_substrings(["--exec"])
+"#,
+    );
+    let spread_out = parse(
+        r#"
+# Same programs, defined in another order with comments and keyword
+# arguments shuffled.
+forbid_substrings(["--exec"])
+
+define_program(
+    program = "head",
+    args = [ARG_RFILES],
+    options = [
+        opt("-n", ARG_POS_INT),  # line count
+    ],
+)
+
+define_program(
+    system_path = ["/bin/ls"],
+    program = "ls",
+    args = [ARG_RFILES_OR_CWD],
+    options = [flag("-a"), flag("-l")],
+)
+"#,
+    );
+    assert_eq!(compact.fingerprint(), spread_out.fingerprint());
+
+    let changed = parse(
+        r#"
+define_program(program="ls", options=[flag("-l")], args=[ARG_RFILES_OR_CWD], system_path=["/bin/ls"])
+define_program(program="head", options=[opt("-n", ARG_POS_INT)], args=[ARG_RFILES])
+forbid_substrings(["--exec"])
+"#,
+    );
+    assert_ne!(compact.fingerprint(), changed.fingerprint());
+}
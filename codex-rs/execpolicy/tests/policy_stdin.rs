@@ -0,0 +1,42 @@
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+#[test]
+fn check_reads_policy_from_stdin() -> anyhow::Result<()> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .args(["--policy", "-", "check", "ls", "-l"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(
+            br#"define_program(program="ls", options=[flag("-l")], args=[], system_path=["/bin/ls"])"#,
+        )?;
+    }
+    let output = child.wait_with_output()?;
+    assert_eq!(output.status.code(), Some(0));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["result"], "match");
+    assert_eq!(json["match"]["program"], "ls");
+    Ok(())
+}
+
+#[test]
+fn stdin_policy_errors_name_stdin() -> anyhow::Result<()> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .args(["--policy", "-", "check", "ls"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(b"define_program(")?;
+    }
+    let output = child.wait_with_output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("<stdin>"));
+    Ok(())
+}
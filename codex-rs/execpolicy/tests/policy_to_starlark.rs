@@ -0,0 +1,59 @@
+#![expect(clippy::expect_used)]
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::PolicyParser;
+
+#[test]
+fn exported_policy_round_trips() {
+    let unparsed_policy = r#"
+define_program(
+    program="ls",
+    system_path=["/bin/ls", "/usr/bin/ls"],
+    option_bundling=True,
+    options=[flag("-l"), flag("-a"), opt("--color", ARG_OPAQUE_VALUE)],
+    args=[ARG_RFILES_OR_CWD],
+    should_match=[["-al"], ["--color", "auto", "src"]],
+    should_not_match=[["--color"]],
+)
+define_program(
+    program="head",
+    options=[opt("-n", ARG_POS_INT, required=True)],
+    args=[ARG_RFILES],
+)
+define_program(
+    program="rm",
+    args=[ARG_WFILE],
+    forbidden="rm \"deletes\" files",
+)
+forbid_substrings(["--exec", "$("])
+forbid_program_regex("^sudo$", "no privilege escalation")
+"#;
+    let policy = PolicyParser::new("original", unparsed_policy)
+        .parse()
+        .expect("failed to parse policy");
+
+    let exported = policy.to_starlark();
+    let reloaded = PolicyParser::new("exported", &exported)
+        .parse()
+        .expect("failed to parse exported policy");
+
+    assert!(policy.diff(&reloaded).is_empty(), "{exported}");
+    assert_eq!(policy.forbidden_programs(), reloaded.forbidden_programs());
+    assert_eq!(reloaded.to_starlark(), exported);
+
+    let commands: [(&str, &[&str]); 6] = [
+        ("ls", &["-al", "src"]),
+        ("ls", &["--color"]),
+        ("head", &["-n", "5", "README.md"]),
+        ("head", &["README.md"]),
+        ("rm", &["foo"]),
+        ("sudo", &["$(whoami)"]),
+    ];
+    for (program, args) in commands {
+        let exec_call = ExecCall::new(program, args);
+        assert_eq!(
+            format!("{:?}", policy.check(&exec_call)),
+            format!("{:?}", reloaded.check(&exec_call)),
+            "{program} {args:?}"
+        );
+    }
+}
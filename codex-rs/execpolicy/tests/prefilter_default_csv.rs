@@ -0,0 +1,80 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const FORBIDDEN_EXIT_CODE: i32 = 14;
+
+const LS_IS_RISKY_CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption
+linux,ls,-l,5,5,5,5,5
+macos,ls,-l,5,5,5,5,5
+windows,ls,-l,5,5,5,5,5
+";
+
+fn check(command: &[&str]) -> anyhow::Result<std::process::Output> {
+    check_with_args(&[], command)
+}
+
+fn check_with_args(
+    extra_args: &[&std::ffi::OsStr],
+    command: &[&str],
+) -> anyhow::Result<std::process::Output> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        r#"
+define_program(program="ls", options=[flag("-l")], args=[ARG_RFILES_OR_CWD], system_path=["/bin/ls"])
+define_program(program="rm", options=[flag("-r"), flag("-f")], args=[ARG_UNVERIFIED_VARARGS])
+"#,
+    )?;
+    let output = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .arg("--policy")
+        .arg(&policy)
+        .args(extra_args)
+        .arg("check")
+        .args(command)
+        .output()?;
+    Ok(output)
+}
+
+#[test]
+fn prefilter_scores_the_checked_command_with_the_bundled_csv() -> anyhow::Result<()> {
+    let output = check(&["ls"])?;
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["result"], "match");
+
+    let output = check(&["ls", "-l", "src"])?;
+    assert_eq!(output.status.code(), Some(0));
+
+    // `rm -r` is listed as high risk in the bundled CSV.
+    let output = check(&["rm", "-r", "build"])?;
+    assert_eq!(output.status.code(), Some(FORBIDDEN_EXIT_CODE));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rejected by prefilter"));
+    Ok(())
+}
+
+#[test]
+fn prefilter_uses_a_custom_csv_without_env() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let risk_csv = dir.path().join("risk.csv");
+    fs::write(&risk_csv, LS_IS_RISKY_CSV)?;
+
+    let output = check_with_args(
+        &["--risk-csv".as_ref(), risk_csv.as_os_str()],
+        &["ls", "-l", "src"],
+    )?;
+    assert_eq!(
+        output.status.code(),
+        Some(FORBIDDEN_EXIT_CODE),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rejected by prefilter"));
+    Ok(())
+}
@@ -0,0 +1,48 @@
+#![expect(clippy::expect_used)]
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn print_policy_lists_defined_programs() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(
+        &policy,
+        r#"
+define_program(
+    program="ls",
+    options=[flag("-l"), opt("--color", ARG_OPAQUE_VALUE)],
+    args=[ARG_RFILES_OR_CWD],
+    system_path=["/bin/ls"],
+)
+define_program(
+    program="rm",
+    args=[ARG_RFILES],
+    forbidden="rm is never allowed",
+)
+forbid_program_regex("^shutdown$", "no power management")
+"#,
+    )?;
+    let output = Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+        .arg("--policy")
+        .arg(&policy)
+        .arg("print-policy")
+        .output()?;
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let programs = json["programs"].as_array().expect("programs array");
+    let names: Vec<&str> = programs.iter().filter_map(|p| p["program"].as_str()).collect();
+    assert_eq!(names, ["ls", "rm"]);
+
+    assert_eq!(programs[0]["flags"], serde_json::json!(["-l"]));
+    assert_eq!(programs[0]["opts"]["--color"], "OpaqueNonFile");
+    assert_eq!(programs[0]["system_path"], serde_json::json!(["/bin/ls"]));
+    assert!(programs[0]["forbidden"].is_null());
+    assert_eq!(programs[1]["forbidden"], "rm is never allowed");
+
+    assert_eq!(json["forbidden_programs"][0]["regex"], "^shutdown$");
+    assert_eq!(json["forbidden_programs"][0]["reason"], "no power management");
+    Ok(())
+}
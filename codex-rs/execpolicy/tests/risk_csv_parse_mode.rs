@@ -0,0 +1,47 @@
+#![expect(clippy::expect_used)]
+use codex_execpolicy::threat_state::{
+    CsvParseMode, MalformedMetric, load_risk_tree, load_risk_tree_with_mode,
+};
+use std::fs;
+use tempfile::TempDir;
+
+const CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption\n\
+                   linux,rm,-r,5,1,5,2,3\n\
+                   linux,rm,-f,4,2,x3,1,1\n";
+
+#[test]
+fn lenient_mode_keeps_vector_length() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    fs::write(&path, CSV)?;
+
+    let tree = load_risk_tree_with_mode(&path, CsvParseMode::Lenient)?;
+    assert_eq!(tree["linux"]["rm"]["-f"], vec![4.0, 2.0, 0.0, 1.0, 1.0]);
+    assert_eq!(tree["linux"]["rm"]["-r"], vec![5.0, 1.0, 5.0, 2.0, 3.0]);
+    // Lenient is the default.
+    assert_eq!(load_risk_tree(&path)?, tree);
+    Ok(())
+}
+
+#[test]
+fn strict_mode_reports_line_and_column() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    fs::write(&path, CSV)?;
+
+    let err = load_risk_tree_with_mode(&path, CsvParseMode::Strict)
+        .expect_err("bad metric should be rejected");
+    let malformed = err
+        .downcast_ref::<MalformedMetric>()
+        .expect("error should carry the malformed metric");
+    assert_eq!(
+        malformed,
+        &MalformedMetric {
+            line: 3,
+            column: 6,
+            value: "x3".to_string(),
+        }
+    );
+    assert!(format!("{err:#}").contains("line 3, column 6"));
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use codex_execpolicy::threat_state::{RiskHistory, RiskTree};
+use std::time::SystemTime;
+
+fn tree(score: f64) -> RiskTree {
+    let mut tree = RiskTree::new();
+    tree.entry("linux".to_string())
+        .or_default()
+        .entry("rm".to_string())
+        .or_default()
+        .insert("-r".to_string(), vec![score; 5]);
+    tree
+}
+
+#[test]
+fn added_trees_are_stamped_in_increasing_order() {
+    let before = SystemTime::now();
+    let mut history = RiskHistory::new(10, 0.05);
+    for score in [1.0, 2.0, 3.0, 4.0] {
+        history.add_tree(tree(score));
+    }
+
+    let entries = history.history();
+    assert_eq!(entries.len(), 4);
+    assert!(entries[0].captured_at >= before);
+    for pair in entries.windows(2) {
+        assert!(pair[0].captured_at < pair[1].captured_at);
+    }
+    let scores: Vec<f64> = entries
+        .iter()
+        .map(|entry| entry.tree["linux"]["rm"]["-r"][0])
+        .collect();
+    assert_eq!(scores, vec![1.0, 2.0, 3.0, 4.0]);
+}
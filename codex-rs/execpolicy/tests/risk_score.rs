@@ -0,0 +1,37 @@
+mod common;
+
+use codex_execpolicy::policy_watcher::{current_risk_score, risk_score_from_csv};
+use codex_execpolicy::threat_state::DEFAULT_RISK_SCORE;
+use common::{HEADER, LS_POLICY};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn bundled_csv_has_positive_score() {
+    assert!(current_risk_score() > 0.0);
+}
+
+#[test]
+fn score_is_the_average_of_all_metrics() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let csv = dir.path().join("risk.csv");
+    fs::write(&csv, format!("{HEADER}\nlinux,rm,-r,1,2,3,4,5\nlinux,ls,-l,0,0,0,0,0\n"))?;
+    assert_eq!(risk_score_from_csv(&csv), 1.5);
+
+    assert_eq!(risk_score_from_csv(&dir.path().join("missing.csv")), DEFAULT_RISK_SCORE);
+    Ok(())
+}
+
+#[test]
+fn prefilter_uses_the_watcher_csv() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, LS_POLICY, &format!("{HEADER}\nlinux,ls,-l,0,0,0,0,0\n"))?;
+    assert!(watcher.prefilter_csv().is_ok());
+    assert!(watcher.reload().is_ok());
+
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, LS_POLICY, &format!("{HEADER}\nlinux,rm,-r,5,5,5,5,5\n"))?;
+    assert!(watcher.prefilter_csv().is_err());
+    assert!(watcher.reload().is_err());
+    Ok(())
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::HEADER;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const POLICY: &str = r#"define_program(program="ls", options=[flag("-l")], args=[], system_path=["/bin/ls"])"#;
+
+#[test]
+fn lowering_the_threshold_rejects_a_safe_csv() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    // Average risk 0.2.
+    let watcher = common::watcher(&dir, POLICY, &format!("{HEADER}\nlinux,ls,-l,0.2,0.2,0.2,0.2,0.2\n"))?;
+    watcher.prefilter_csv()?;
+    watcher.reload()?;
+
+    let watcher = watcher.with_risk_threshold(0.1);
+    assert!(watcher.prefilter_csv().is_err());
+    assert!(watcher.reload().is_err());
+    Ok(())
+}
+
+#[test]
+fn raising_the_threshold_accepts_a_risky_csv() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    // Average risk 3.0.
+    let watcher = common::watcher(&dir, POLICY, &format!("{HEADER}\nlinux,rm,-r,3,3,3,3,3\n"))?;
+    assert!(watcher.prefilter_csv().is_err());
+    assert!(watcher.reload().is_err());
+
+    let watcher = watcher.with_risk_threshold(5.0);
+    watcher.prefilter_csv()?;
+    watcher.reload()?;
+    Ok(())
+}
+
+#[test]
+fn cli_threshold_governs_the_prefilter() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(&policy, POLICY)?;
+    let check = |threshold: &str| {
+        Command::new(env!("CARGO_BIN_EXE_codex-execpolicy"))
+            .arg("--policy")
+            .arg(&policy)
+            .arg(format!("--risk-threshold={threshold}"))
+            .args(["check", "ls", "-l"])
+            .output()
+    };
+
+    let output = check("1000000")?;
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["result"], "match");
+
+    let output = check("-1")?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rejected by prefilter"));
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::cell::Cell;
+use std::path::Path;
+use std::sync::Arc;
+
+use codex_execpolicy::threat_state::RiskTree;
+use codex_execpolicy::threat_state::RiskTreeCache;
+use codex_execpolicy::threat_state::load_risk_tree;
+use codex_execpolicy::threat_state::load_risk_tree_cached;
+use tempfile::TempDir;
+
+const CSV: &str = "Environment,Binary,Flag,Data loss\nlinux,rm,-r,0.9\n";
+
+#[test]
+fn unchanged_csv_is_parsed_once() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    std::fs::write(&path, CSV)?;
+
+    let parses = Cell::new(0);
+    let counting_load = |path: &Path| -> anyhow::Result<RiskTree> {
+        parses.set(parses.get() + 1);
+        load_risk_tree(path)
+    };
+
+    let mut cache = RiskTreeCache::new(4);
+    let first = cache.get_or_load_with(&path, counting_load)?;
+    let second = cache.get_or_load_with(&path, counting_load)?;
+    assert_eq!(parses.get(), 1);
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first["linux"]["rm"]["-r"], vec![0.9, 0.0, 0.0, 0.0, 0.0]);
+
+    std::fs::write(&path, "Environment,Binary,Flag,Data loss\nlinux,rm,-rf,1.0\n")?;
+    let third = cache.get_or_load_with(&path, counting_load)?;
+    assert_eq!(parses.get(), 2);
+    assert!(third["linux"]["rm"].contains_key("-rf"));
+    Ok(())
+}
+
+#[test]
+fn cache_is_bounded() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let paths: Vec<_> = (0..3)
+        .map(|i| {
+            let path = dir.path().join(format!("risk{i}.csv"));
+            std::fs::write(&path, CSV).map(|_| path)
+        })
+        .collect::<std::io::Result<_>>()?;
+
+    let parses = Cell::new(0);
+    let counting_load = |path: &Path| -> anyhow::Result<RiskTree> {
+        parses.set(parses.get() + 1);
+        load_risk_tree(path)
+    };
+
+    let mut cache = RiskTreeCache::new(2);
+    for path in &paths {
+        cache.get_or_load_with(path, counting_load)?;
+    }
+    // The first file was evicted to make room for the third.
+    cache.get_or_load_with(&paths[2], counting_load)?;
+    assert_eq!(parses.get(), 3);
+    cache.get_or_load_with(&paths[0], counting_load)?;
+    assert_eq!(parses.get(), 4);
+    Ok(())
+}
+
+#[test]
+fn shared_cache_reuses_the_parsed_tree() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    std::fs::write(&path, CSV)?;
+
+    let first = load_risk_tree_cached(&path)?;
+    let second = load_risk_tree_cached(&path)?;
+    assert!(Arc::ptr_eq(&first, &second));
+    Ok(())
+}
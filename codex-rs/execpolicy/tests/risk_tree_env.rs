@@ -0,0 +1,29 @@
+use codex_execpolicy::threat_state::{filter_risk_tree_env, load_risk_tree, load_risk_tree_for_env};
+use std::fs;
+use tempfile::TempDir;
+
+const MULTI_ENV_CSV: &str = "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption
+linux,rm,-rf,5,1,5,2,3
+macos,rm,-rf,4,1,4,2,3
+win64cmd,del,/s,5,1,5,2,3
+";
+
+#[test]
+fn filter_keeps_only_requested_env() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    fs::write(&path, MULTI_ENV_CSV)?;
+
+    let tree = load_risk_tree(&path)?;
+    assert_eq!(tree.len(), 3);
+
+    let linux = filter_risk_tree_env(&tree, "linux");
+    assert_eq!(linux.keys().collect::<Vec<_>>(), vec!["linux"]);
+    assert_eq!(linux["linux"]["rm"]["-rf"], vec![5.0, 1.0, 5.0, 2.0, 3.0]);
+
+    let loaded = load_risk_tree_for_env(&path, "Linux")?;
+    assert_eq!(loaded, linux);
+
+    assert!(filter_risk_tree_env(&tree, "plan9").is_empty());
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use codex_execpolicy::threat_state::{load_risk_tree, load_risk_tree_streaming};
+use std::fmt::Write as _;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn streamed_tree_matches_in_memory_tree() -> anyhow::Result<()> {
+    let mut csv = String::from(
+        "Environment,Binary,Flag,Data loss,Unauthorized Access,Data Integrity,Privacy Breach,Service Disruption\n",
+    );
+    for env in ["linux", "macos", "win64cmd"] {
+        for i in 0..20_000 {
+            writeln!(csv, "{env},tool{},-f{i},{},1,2,3,4", i % 97, i % 5)?;
+        }
+    }
+    // Malformed rows are skipped by both loaders.
+    csv.push_str("linux,short\n");
+
+    let dir = TempDir::new()?;
+    let path = dir.path().join("large.csv");
+    fs::write(&path, csv)?;
+
+    let streamed = load_risk_tree_streaming(&path)?;
+    assert_eq!(streamed, load_risk_tree(&path)?);
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(streamed["linux"]["tool0"]["-f0"], vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    Ok(())
+}
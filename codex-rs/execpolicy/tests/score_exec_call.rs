@@ -0,0 +1,41 @@
+mod common;
+
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::threat_state::DEFAULT_RISK_SCORE;
+use common::{HEADER, RM_POLICY};
+use tempfile::TempDir;
+
+#[test]
+fn multi_flag_call_sums_weighted_vectors() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(
+        &dir,
+        RM_POLICY,
+        &format!(
+            "{HEADER}\nlinux,rm,-r,1,1,0,0,0\nlinux,rm,-f,0,2,0,0,1\nlinux,rm,--no-preserve-root,4,0,0,0,0\nmacos,rm,-r,9,9,9,9,9\n"
+        ),
+    )?;
+
+    // `-rf` is split into `-r` and `-f`; `target` has no row.
+    let call = ExecCall::new("rm", &["-rf", "--no-preserve-root", "target"]);
+    let weights = [2.0, 0.5, 1.0, 1.0, 10.0];
+    let score = watcher.score_exec_call(&call, &weights, Some("linux"));
+    // -r: 1*2 + 1*0.5, -f: 2*0.5 + 1*10, --no-preserve-root: 4*2
+    assert_eq!(score, 2.5 + 11.0 + 8.0);
+
+    let unweighted = watcher.score_exec_call(&call, &[], Some("linux"));
+    assert_eq!(unweighted, 2.0 + 3.0 + 4.0);
+
+    let unknown = ExecCall::new("ls", &["-l"]);
+    assert_eq!(watcher.score_exec_call(&unknown, &weights, Some("linux")), 0.0);
+    Ok(())
+}
+
+#[test]
+fn unreadable_csv_scores_the_default() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, RM_POLICY, HEADER)?.with_risk_csv(dir.path().join("missing.csv"));
+    let call = ExecCall::new("rm", &["-r"]);
+    assert_eq!(watcher.score_exec_call(&call, &[], None), DEFAULT_RISK_SCORE);
+    Ok(())
+}
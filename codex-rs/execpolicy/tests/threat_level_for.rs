@@ -0,0 +1,41 @@
+mod common;
+
+use codex_execpolicy::ThreatLevel;
+use common::{HEADER, RM_POLICY};
+use std::fs;
+use tempfile::TempDir;
+
+const RM_ROWS: &str = "linux,rm,-r,5,1,5,2,3
+linux,rm,-f,5,1,5,2,3
+linux,ls,-l,0,0,0,0,0
+";
+
+#[test]
+fn dangerous_command_is_high_and_unknown_is_low() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, RM_POLICY, &format!("{HEADER}\n{RM_ROWS}"))?;
+
+    assert_eq!(watcher.threat_level_for("rm -rf /", Some("linux"))?, ThreatLevel::High);
+    assert_eq!(watcher.threat_level_for("frobnicate --now", Some("linux"))?, ThreatLevel::Low);
+    assert_eq!(watcher.threat_level_for("rm -rf /", Some("windows"))?, ThreatLevel::Low);
+
+    // Rewriting the CSV is picked up by the next lookup.
+    watcher.register_tool("linux", "frobnicate", "--now", 5.0)?;
+    assert_eq!(watcher.threat_level_for("frobnicate --now", Some("linux"))?, ThreatLevel::High);
+    Ok(())
+}
+
+#[test]
+fn unreadable_risk_csv_is_an_error() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let watcher = common::watcher(&dir, RM_POLICY, HEADER)?;
+
+    let missing = watcher.with_risk_csv(dir.path().join("missing.csv"));
+    assert!(missing.threat_level_for("rm -rf /", Some("linux")).is_err());
+
+    let corrupt_csv = dir.path().join("corrupt.csv");
+    fs::write(&corrupt_csv, [0xff, 0xfe, b'\n', 0xc3])?;
+    let corrupt = missing.with_risk_csv(corrupt_csv);
+    assert!(corrupt.threat_level_for("rm -rf /", Some("linux")).is_err());
+    Ok(())
+}
@@ -0,0 +1,34 @@
+#![expect(clippy::expect_used)]
+use codex_execpolicy::validate_policy;
+
+#[test]
+fn valid_policy_passes() {
+    let source = r#"
+define_program(
+    program="ls",
+    options=[flag("-l")],
+    args=[ARG_RFILES_OR_CWD],
+)
+"#;
+    assert_eq!(validate_policy("valid.star", source), Ok(()));
+}
+
+#[test]
+fn syntax_error_reports_location() {
+    let source = "define_program(\n    program=\"ls\",\n    args=[ARG_RFILES_OR_CWD\n)\n";
+    let err = validate_policy("broken.star", source).expect_err("policy should not parse");
+    assert_eq!(err.source_name, "broken.star");
+    assert_eq!(err.line, Some(4));
+    assert!(err.column.is_some());
+    assert!(
+        err.to_string().starts_with("broken.star:4:"),
+        "unexpected message: {err}"
+    );
+}
+
+#[test]
+fn evaluation_error_is_reported() {
+    let err = validate_policy("unknown.star", "define_program(program=\"ls\", args=[ARG_NOPE])\n")
+        .expect_err("unknown matcher should fail");
+    assert!(err.message.contains("ARG_NOPE"), "unexpected message: {err}");
+}
@@ -0,0 +1,32 @@
+use codex_execpolicy::PolicyWatcher;
+use codex_execpolicy::ThreatStateWatcher;
+use codex_execpolicy::watched_paths;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn watchers_register_their_paths_until_dropped() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let policy = dir.path().join("policy.star");
+    fs::write(&policy, "define_program(program=\"ls\", args=[], system_path=[\"/bin/ls\"])")?;
+    let state = dir.path().join("threat_state.json");
+    fs::write(&state, "{}")?;
+
+    let watcher = PolicyWatcher::new(policy.clone())?;
+    let second = PolicyWatcher::new(policy.clone())?;
+    let threat_watcher = ThreatStateWatcher::new(state.clone())?;
+    let paths = watched_paths();
+    assert!(paths.contains(&policy));
+    assert!(paths.contains(&state));
+
+    // Still watched while another watcher holds the same path.
+    drop(watcher);
+    assert!(watched_paths().contains(&policy));
+
+    drop(second);
+    drop(threat_watcher);
+    let paths = watched_paths();
+    assert!(!paths.contains(&policy));
+    assert!(!paths.contains(&state));
+    Ok(())
+}
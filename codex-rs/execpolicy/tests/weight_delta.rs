@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+use codex_execpolicy::threat_state::{RiskTree, ThreatDeliverable, apply_weights};
+
+#[test]
+fn delta_is_zero_only_where_weight_is_one() {
+    let mut projected: RiskTree = BTreeMap::new();
+    projected
+        .entry("linux".to_string())
+        .or_default()
+        .entry("rm".to_string())
+        .or_default()
+        .insert("-r".to_string(), vec![1.0, 2.0, 4.0]);
+    let weights = [1.0, 0.5, 2.0];
+    let deliverable = ThreatDeliverable {
+        historical: Vec::new(),
+        final_tree: apply_weights(&projected, &weights),
+        projected,
+    };
+
+    let delta = deliverable.weight_delta();
+    assert_eq!(delta["linux"]["rm"]["-r"], vec![0.0, -1.0, 4.0]);
+}
@@ -1,15 +1,34 @@
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
 
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use translation::scripts_dir;
 
-/// Return the `scripts` directory for the current crate.
-fn scripts_dir() -> PathBuf {
-    Path::new(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .expect("crate should have parent")
-        .join("scripts")
+
+/// Resolve `name` to a document path inside the `scripts` directory.
+fn resolve_doc_path(name: &str) -> std::io::Result<PathBuf> {
+    resolve_doc_path_in(&scripts_dir(), name)
+}
+
+/// Resolve `name` to a document path inside `dir`.
+///
+/// Only a plain file name is accepted; absolute paths, separators and `..`
+/// are rejected so a document name cannot escape the directory.
+fn resolve_doc_path_in(dir: &Path, name: &str) -> std::io::Result<PathBuf> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(file)), None) => Ok(dir.join(file)),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid document name '{}'", name),
+        )),
+    }
 }
 
 // Define the internal commands
@@ -19,8 +38,11 @@ lazy_static! {
         commands.insert("codex_fetch_docs");
         commands.insert("codex_list_docs");
         commands.insert("codex_read_doc");
+        commands.insert("codex_doc_info");
+        commands.insert("codex_search_docs");
         commands.insert("codex_delete_doc");
         commands.insert("codex_update_doc");
+        commands.insert("codex_append_doc");
         commands.insert("codex_create_doc");
         commands.insert("codex_system_exec");
         commands.insert("codex_reset_translator");
@@ -31,13 +53,57 @@ lazy_static! {
         commands.insert("codex_set_pallette");
         commands.insert("codex_set_sandbox_policy");
         commands.insert("codex_commands");
+        commands.insert("codex_command_help");
         commands
     };
 }
 
+/// One-line description of each built-in command, shown by [`codex_help`].
+const COMMAND_DESCRIPTIONS: &[(&str, &str)] = &[
+    ("codex_fetch_docs", "Print the documents with their contents, truncating large ones"),
+    ("codex_list_docs", "List the documents in the scripts directory"),
+    ("codex_read_doc", "Print the contents of a document"),
+    ("codex_doc_info", "Print the size and modification time of a document as JSON"),
+    ("codex_search_docs", "List documents and line numbers matching a query, ignoring case"),
+    ("codex_delete_doc", "Delete a document"),
+    ("codex_update_doc", "Replace the contents of a document, creating it if needed"),
+    ("codex_append_doc", "Append text to a document, creating it if needed"),
+    ("codex_create_doc", "Create a document with the given contents"),
+    ("codex_system_exec", "Run a system command directly"),
+    ("codex_reset_translator", "Reinitialize the command translator for a shell"),
+    ("codex_user_exec_dialog", "Ask the user to run a command (not implemented)"),
+    ("codex_user_fork_exec", "Hand a command over to the user (not implemented)"),
+    ("codex_help", "List the internal commands with their descriptions"),
+    ("codex_truncatoin_mode", "Enable or disable output truncation"),
+    ("codex_set_pallette", "Change the color palette"),
+    ("codex_set_sandbox_policy", "Change the sandbox policy"),
+    ("codex_commands", "List the names of the internal commands"),
+    ("codex_command_help", "Print each internal command with its description as JSON"),
+];
+
+/// Signature shared by built-in and registered internal commands.
+pub type InternalCommandFn = fn(args: &[String], cwd: PathBuf) -> std::io::Result<InternalCommandOutput>;
+
+/// Internal commands registered at runtime by the host application.
+static REGISTERED_COMMANDS: OnceCell<Mutex<HashMap<String, InternalCommandFn>>> = OnceCell::new();
+
+fn registered_commands() -> std::sync::MutexGuard<'static, HashMap<String, InternalCommandFn>> {
+    let registry = REGISTERED_COMMANDS.get_or_init(|| Mutex::new(HashMap::new()));
+    match registry.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Register `func` as the internal command `name`, replacing any previous
+/// registration under that name. Built-in commands keep precedence.
+pub fn register_internal_command(name: &str, func: InternalCommandFn) {
+    registered_commands().insert(name.to_string(), func);
+}
+
 // Function to check if a command is internal
 pub fn is_internal_command(command: &str) -> bool {
-    INTERNAL_COMMANDS.contains(command)
+    INTERNAL_COMMANDS.contains(command) || registered_commands().contains_key(command)
 }
 
 // Trait for external dependencies
@@ -61,10 +127,16 @@ pub fn interact_with_dependency(
     }
 }
 
-/// Return a list of document file names available under the `scripts` directory.
+/// Return the document file names available under the `scripts` directory,
+/// sorted so the listing does not depend on the filesystem's `read_dir` order.
 pub fn codex_list_docs() -> std::io::Result<Vec<String>> {
+    codex_list_docs_in(&scripts_dir())
+}
+
+/// Like [`codex_list_docs`], for the documents in `dir`.
+pub fn codex_list_docs_in(dir: &Path) -> std::io::Result<Vec<String>> {
     let mut docs = Vec::new();
-    for entry in std::fs::read_dir(scripts_dir())? {
+    for entry in std::fs::read_dir(dir)? {
         let entry = entry?;
         if entry.file_type()?.is_file() {
             if let Some(name) = entry.file_name().to_str() {
@@ -72,32 +144,173 @@ pub fn codex_list_docs() -> std::io::Result<Vec<String>> {
             }
         }
     }
+    docs.sort();
     Ok(docs)
 }
 
 /// Read the contents of a document in the `scripts` directory.
 pub fn codex_read_doc(name: &str) -> std::io::Result<String> {
-    std::fs::read_to_string(scripts_dir().join(name))
+    codex_read_doc_in(&scripts_dir(), name)
+}
+
+/// Like [`codex_read_doc`], for a document in `dir`.
+pub fn codex_read_doc_in(dir: &Path, name: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(resolve_doc_path_in(dir, name)?)
+}
+
+/// Size and modification time of a document, as reported by
+/// [`codex_doc_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DocMetadata {
+    pub name: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Last modification time in seconds since the Unix epoch.
+    pub modified: u64,
+}
+
+/// Return the metadata of a document in the `scripts` directory without
+/// reading its contents.
+pub fn codex_doc_metadata(name: &str) -> std::io::Result<DocMetadata> {
+    codex_doc_metadata_in(&scripts_dir(), name)
+}
+
+/// Like [`codex_doc_metadata`], for a document in `dir`.
+pub fn codex_doc_metadata_in(dir: &Path, name: &str) -> std::io::Result<DocMetadata> {
+    let metadata = std::fs::metadata(resolve_doc_path_in(dir, name)?)?;
+    if !metadata.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("'{}' is not a document", name),
+        ));
+    }
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    Ok(DocMetadata {
+        name: name.to_string(),
+        size: metadata.len(),
+        modified,
+    })
 }
 
-/// Return all docs as a vector of `(name, contents)` tuples.
+/// Return all docs as a vector of `(name, contents)` tuples, in the order of
+/// [`codex_list_docs`].
 pub fn codex_fetch_docs() -> std::io::Result<Vec<(String, String)>> {
+    codex_fetch_docs_in(&scripts_dir())
+}
+
+/// Like [`codex_fetch_docs`], for the documents in `dir`.
+pub fn codex_fetch_docs_in(dir: &Path) -> std::io::Result<Vec<(String, String)>> {
     let mut docs = Vec::new();
-    for name in codex_list_docs()? {
-        let contents = codex_read_doc(&name).unwrap_or_default();
+    for name in codex_list_docs_in(dir)? {
+        let contents = codex_read_doc_in(dir, &name).unwrap_or_default();
         docs.push((name, contents));
     }
     Ok(docs)
 }
 
+/// Most documents printed by the `codex_fetch_docs` internal command.
+pub const DEFAULT_FETCH_DOCS_MAX_FILES: usize = 256;
+
+/// Most bytes of each document printed by the `codex_fetch_docs` internal
+/// command.
+pub const DEFAULT_FETCH_DOCS_MAX_BYTES: usize = 64 * 1024;
+
+/// Appended to a document cut short by [`codex_fetch_docs_limited`].
+pub const DOC_TRUNCATION_MARKER: &str = "\n[... truncated ...]";
+
+/// Like [`codex_fetch_docs`], but returns at most `max_files` docs and reads
+/// at most `max_bytes_per_file` bytes of each. Longer docs are cut at that
+/// many bytes and end with [`DOC_TRUNCATION_MARKER`].
+pub fn codex_fetch_docs_limited(
+    max_files: usize,
+    max_bytes_per_file: usize,
+) -> std::io::Result<Vec<(String, String)>> {
+    codex_fetch_docs_limited_in(&scripts_dir(), max_files, max_bytes_per_file)
+}
+
+/// Like [`codex_fetch_docs_limited`], for the documents in `dir`.
+pub fn codex_fetch_docs_limited_in(
+    dir: &Path,
+    max_files: usize,
+    max_bytes_per_file: usize,
+) -> std::io::Result<Vec<(String, String)>> {
+    let mut docs = Vec::new();
+    for name in codex_list_docs_in(dir)?.into_iter().take(max_files) {
+        let contents = read_doc_prefix(dir, &name, max_bytes_per_file).unwrap_or_default();
+        docs.push((name, contents));
+    }
+    Ok(docs)
+}
+
+fn read_doc_prefix(dir: &Path, name: &str, max_bytes: usize) -> std::io::Result<String> {
+    let file = std::fs::File::open(resolve_doc_path_in(dir, name)?)?;
+    let mut bytes = Vec::new();
+    // One byte past the cap tells a doc of exactly `max_bytes` from a longer one.
+    file.take(max_bytes as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() <= max_bytes {
+        return Ok(String::from_utf8_lossy(&bytes).into_owned());
+    }
+    bytes.truncate(max_bytes);
+    let mut contents = String::from_utf8_lossy(&bytes).into_owned();
+    contents.push_str(DOC_TRUNCATION_MARKER);
+    Ok(contents)
+}
+
+/// Search every doc for `query`, ignoring case.
+///
+/// Returns the name of each matching doc, sorted, with the 1-based numbers of
+/// the lines that contain the query.
+pub fn codex_search_docs(query: &str) -> std::io::Result<Vec<(String, Vec<usize>)>> {
+    codex_search_docs_in(&scripts_dir(), query)
+}
+
+/// Like [`codex_search_docs`], for the documents in `dir`.
+pub fn codex_search_docs_in(dir: &Path, query: &str) -> std::io::Result<Vec<(String, Vec<usize>)>> {
+    let query = query.to_lowercase();
+    let mut matches: Vec<(String, Vec<usize>)> = codex_fetch_docs_in(dir)?
+        .into_iter()
+        .filter_map(|(name, contents)| {
+            let lines: Vec<usize> = contents
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.to_lowercase().contains(&query))
+                .map(|(index, _)| index + 1)
+                .collect();
+            (!lines.is_empty()).then_some((name, lines))
+        })
+        .collect();
+    matches.sort();
+    Ok(matches)
+}
+
 /// Delete a document from the `scripts` directory.
 pub fn codex_delete_doc(name: &str) -> std::io::Result<()> {
-    std::fs::remove_file(scripts_dir().join(name))
+    std::fs::remove_file(resolve_doc_path(name)?)
 }
 
 /// Update (or create) a document with new contents.
 pub fn codex_update_doc(name: &str, contents: &str) -> std::io::Result<()> {
-    std::fs::write(scripts_dir().join(name), contents)
+    std::fs::write(resolve_doc_path(name)?, contents)
+}
+
+/// Append `contents` to a document, creating it if it does not exist.
+///
+/// The bytes are written exactly as given; include a newline in `contents`
+/// if the chunks should be separated.
+pub fn codex_append_doc(name: &str, contents: &str) -> std::io::Result<()> {
+    codex_append_doc_in(&scripts_dir(), name, contents)
+}
+
+/// Like [`codex_append_doc`], for a document in `dir`.
+pub fn codex_append_doc_in(dir: &Path, name: &str, contents: &str) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(resolve_doc_path_in(dir, name)?)?;
+    file.write_all(contents.as_bytes())
 }
 
 /// Create a new document with the provided contents.
@@ -125,11 +338,32 @@ pub fn codex_user_fork_exec() -> Result<(), String> {
     Err("user_fork_exec is not implemented".to_string())
 }
 
-/// Return a help string listing all internal commands.
+/// Return a help string listing all internal commands with their descriptions.
 pub fn codex_help() -> String {
-    let mut cmds: Vec<&str> = INTERNAL_COMMANDS.iter().copied().collect();
-    cmds.sort();
-    format!("Available internal commands:\n{}", cmds.join("\n"))
+    let help = codex_command_help();
+    let width = help.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let lines: Vec<String> = help
+        .iter()
+        .map(|(name, description)| format!("{name:<width$}  {description}"))
+        .collect();
+    format!("Available internal commands:\n{}", lines.join("\n"))
+}
+
+/// Every built-in command paired with its description, sorted by name.
+/// Commands without a description get an empty string.
+pub fn codex_command_help() -> Vec<(&'static str, &'static str)> {
+    let mut help: Vec<(&'static str, &'static str)> = INTERNAL_COMMANDS
+        .iter()
+        .map(|name| {
+            let description = COMMAND_DESCRIPTIONS
+                .iter()
+                .find(|(described, _)| described == name)
+                .map_or("", |(_, description)| *description);
+            (*name, description)
+        })
+        .collect();
+    help.sort();
+    help
 }
 
 /// Stub for enabling/disabling truncation mode.
@@ -153,18 +387,11 @@ pub fn codex_commands() -> Vec<&'static str> {
 }
 
 /// Retrieve the function corresponding to an internal command string.
-/// Returns `None` if the command is not internal.
-pub fn get_internal_command_function(
-    command: &str,
-) -> Option<fn(args: &[String], cwd: PathBuf) -> std::io::Result<InternalCommandOutput>> {
-    match command {
-        "codex_fetch_docs" => Some(|_, _| {
-            let docs = codex_fetch_docs()?;
-            Ok(InternalCommandOutput {
-                stdout: format!("{:?}", docs),
-                stderr: String::new(),
-            })
-        }),
+/// Built-ins are matched first, then commands added with
+/// [`register_internal_command`]. Returns `None` if the command is not internal.
+pub fn get_internal_command_function(command: &str) -> Option<InternalCommandFn> {
+    let builtin: Option<InternalCommandFn> = match command {
+        "codex_fetch_docs" => Some(|_, _| fetch_docs_command(&scripts_dir())),
         "codex_list_docs" => Some(|_, _| {
             let docs = codex_list_docs()?;
             Ok(InternalCommandOutput {
@@ -183,8 +410,91 @@ pub fn get_internal_command_function(
                 Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Missing document name"))
             }
         }),
+        "codex_doc_info" => Some(|args, _| doc_info_command(&scripts_dir(), args)),
+        "codex_append_doc" => Some(|args, _| append_doc_command(&scripts_dir(), args)),
+        "codex_search_docs" => Some(|args, _| search_docs_command(&scripts_dir(), args)),
+        "codex_command_help" => Some(|_, _| {
+            Ok(InternalCommandOutput {
+                stdout: serde_json::to_string(&codex_command_help())?,
+                stderr: String::new(),
+            })
+        }),
         // ... Add other internal commands here ...
         _ => None,
+    };
+    builtin.or_else(|| registered_commands().get(command).copied())
+}
+
+/// `codex_fetch_docs` for the documents in `dir`, capped at
+/// [`DEFAULT_FETCH_DOCS_MAX_FILES`] and [`DEFAULT_FETCH_DOCS_MAX_BYTES`].
+fn fetch_docs_command(dir: &Path) -> std::io::Result<InternalCommandOutput> {
+    let docs =
+        codex_fetch_docs_limited_in(dir, DEFAULT_FETCH_DOCS_MAX_FILES, DEFAULT_FETCH_DOCS_MAX_BYTES)?;
+    Ok(InternalCommandOutput {
+        stdout: format!("{:?}", docs),
+        stderr: String::new(),
+    })
+}
+
+/// `codex_doc_info <name>` for the documents in `dir`.
+fn doc_info_command(dir: &Path, args: &[String]) -> std::io::Result<InternalCommandOutput> {
+    if let Some(name) = args.first() {
+        let metadata = codex_doc_metadata_in(dir, name)?;
+        Ok(InternalCommandOutput {
+            stdout: serde_json::to_string(&metadata)?,
+            stderr: String::new(),
+        })
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Missing document name"))
+    }
+}
+
+/// `codex_append_doc [--newline] <name> <contents...>` for the documents in
+/// `dir`; `--newline` terminates the appended chunk with a line break.
+fn append_doc_command(dir: &Path, args: &[String]) -> std::io::Result<InternalCommandOutput> {
+    let (newline, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--newline" => (true, rest),
+        _ => (false, args),
+    };
+    if let Some((name, words)) = args.split_first() {
+        let mut contents = words.join(" ");
+        if newline {
+            contents.push('\n');
+        }
+        codex_append_doc_in(dir, name, &contents)?;
+        Ok(InternalCommandOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Missing document name"))
+    }
+}
+
+/// `codex_search_docs <query...>` for the documents in `dir`.
+fn search_docs_command(dir: &Path, args: &[String]) -> std::io::Result<InternalCommandOutput> {
+    if args.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Missing search query"));
+    }
+    let matches = codex_search_docs_in(dir, &args.join(" "))?;
+    Ok(InternalCommandOutput {
+        stdout: serde_json::to_string(&matches)?,
+        stderr: String::new(),
+    })
+}
+
+/// Run an internal command given as a full argv (`command[0]` is the command
+/// name). Returns `NotFound` when the name has no registered implementation.
+pub fn run_internal_command(command: &[String], cwd: PathBuf) -> std::io::Result<InternalCommandOutput> {
+    let (name, args) = command.split_first().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "command args are empty")
+    })?;
+    match get_internal_command_function(name) {
+        Some(func) => func(args, cwd),
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("internal command '{}' is not implemented", name),
+        )),
     }
 }
 
@@ -197,6 +507,7 @@ pub struct InternalCommandOutput {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
 
     #[test]
     fn list_docs_returns_files() {
@@ -212,9 +523,143 @@ mod tests {
         assert!(!content.is_empty());
     }
 
+    #[test]
+    fn docs_are_listed_in_sorted_order() {
+        let dir = TempDir::new().expect("tempdir");
+        for name in ["zeta.md", "alpha.md", "Mid.txt", "beta.md", "10_notes.md", "2_notes.md"] {
+            std::fs::write(dir.path().join(name), name).expect("write doc");
+        }
+        std::fs::create_dir(dir.path().join("a_subdir")).expect("create subdir");
+
+        let expected = ["10_notes.md", "2_notes.md", "Mid.txt", "alpha.md", "beta.md", "zeta.md"];
+        assert_eq!(codex_list_docs_in(dir.path()).expect("list docs"), expected);
+
+        let fetched = codex_fetch_docs_in(dir.path()).expect("fetch docs");
+        let names: Vec<&str> = fetched.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, expected);
+        assert!(fetched.iter().all(|(name, contents)| name == contents));
+    }
+
+    #[test]
+    fn fetch_docs_limited_caps_file_count_and_size() {
+        let dir = TempDir::new().expect("tempdir");
+        let large = "x".repeat(DEFAULT_FETCH_DOCS_MAX_BYTES * 2);
+        std::fs::write(dir.path().join("a_large.md"), &large).expect("write doc");
+        std::fs::write(dir.path().join("b_exact.md"), "12345").expect("write doc");
+        std::fs::write(dir.path().join("c_small.md"), "small").expect("write doc");
+
+        let docs = codex_fetch_docs_limited_in(dir.path(), 2, 5).expect("fetch docs");
+        assert_eq!(
+            docs,
+            vec![
+                ("a_large.md".to_string(), format!("xxxxx{DOC_TRUNCATION_MARKER}")),
+                ("b_exact.md".to_string(), "12345".to_string()),
+            ]
+        );
+
+        // The internal command applies the default byte cap.
+        let output = fetch_docs_command(dir.path()).expect("run codex_fetch_docs");
+        assert!(!output.stdout.contains(&large));
+        assert!(output.stdout.contains(&large[..DEFAULT_FETCH_DOCS_MAX_BYTES]));
+        assert!(output.stdout.contains("c_small.md"));
+    }
+
+    #[test]
+    fn doc_metadata_reports_size_and_mtime() {
+        let dir = TempDir::new().expect("tempdir");
+        let name = "doc.txt";
+        std::fs::write(dir.path().join(name), "some contents").expect("write doc");
+
+        let metadata = codex_doc_metadata_in(dir.path(), name).expect("doc metadata");
+        assert_eq!(metadata.name, name);
+        assert_eq!(metadata.size, "some contents".len() as u64);
+        assert!(metadata.modified > 0);
+
+        let output = doc_info_command(dir.path(), &[name.to_string()]).expect("run codex_doc_info");
+        let json: serde_json::Value = serde_json::from_str(&output.stdout).expect("json");
+        assert_eq!(json["size"], metadata.size);
+        assert_eq!(json["modified"], metadata.modified);
+    }
+
+    #[test]
+    fn doc_names_cannot_escape_scripts_dir() {
+        for name in ["../Cargo.toml", "/etc/passwd", "sub/doc.txt", ".", ""] {
+            let err = codex_doc_metadata(name).expect_err(name);
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput, "{name}");
+        }
+    }
+
+    #[test]
+    fn append_doc_keeps_chunks_in_order() {
+        let dir = TempDir::new().expect("tempdir");
+        let name = "log.txt";
+        codex_append_doc_in(dir.path(), name, "first").expect("append first");
+        codex_append_doc_in(dir.path(), name, "second").expect("append second");
+        append_doc_command(
+            dir.path(),
+            &[
+                "--newline".to_string(),
+                name.to_string(),
+                "third".to_string(),
+                "chunk".to_string(),
+            ],
+        )
+        .expect("run codex_append_doc");
+
+        let contents = std::fs::read_to_string(dir.path().join(name)).expect("read doc");
+        assert_eq!(contents, "firstsecondthird chunk\n");
+    }
+
+    #[test]
+    fn search_docs_reports_matching_lines() {
+        let dir = TempDir::new().expect("tempdir");
+        let name = "notes.txt";
+        std::fs::write(dir.path().join(name), "alpha\nZebraQuux here\nbeta\nzebraquux again\n")
+            .expect("write doc");
+        std::fs::write(dir.path().join("other.txt"), "nothing to see\n").expect("write doc");
+
+        let matches = codex_search_docs_in(dir.path(), "zebraquux").expect("search docs");
+        assert_eq!(matches, vec![(name.to_string(), vec![2, 4])]);
+
+        let output = search_docs_command(dir.path(), &["ZEBRAQUUX".to_string()])
+            .expect("run codex_search_docs");
+        let json: serde_json::Value = serde_json::from_str(&output.stdout).expect("json");
+        assert_eq!(json, serde_json::json!([[name, [2, 4]]]));
+    }
+
+    #[test]
+    fn registered_command_is_dispatched() {
+        register_internal_command("codex_test_echo", |args, _| {
+            Ok(InternalCommandOutput {
+                stdout: args.join(" "),
+                stderr: String::new(),
+            })
+        });
+        assert!(is_internal_command("codex_test_echo"));
+
+        let command = vec!["codex_test_echo".to_string(), "hello".to_string(), "world".to_string()];
+        let output = run_internal_command(&command, PathBuf::from(".")).expect("run registered command");
+        assert_eq!(output.stdout, "hello world");
+    }
+
     #[test]
     fn commands_contains_help() {
         let cmds = codex_commands();
         assert!(cmds.contains(&"codex_help"));
     }
+
+    #[test]
+    fn every_command_has_a_description() {
+        let help = codex_command_help();
+        assert_eq!(help.len(), INTERNAL_COMMANDS.len());
+        for (name, description) in &help {
+            assert!(!description.is_empty(), "{name} has no description");
+        }
+
+        assert!(codex_help().contains("codex_doc_info"));
+        let output = run_internal_command(&["codex_command_help".to_string()], PathBuf::from("."))
+            .expect("run codex_command_help");
+        let json: serde_json::Value = serde_json::from_str(&output.stdout).expect("json");
+        assert_eq!(json[0], serde_json::json!([help[0].0, help[0].1]));
+    }
 }
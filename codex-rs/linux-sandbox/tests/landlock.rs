@@ -4,17 +4,27 @@
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
+use codex_core::exec::ExecConfig;
 use codex_core::exec::ExecParams;
 use codex_core::exec::SandboxType;
-use codex_core::exec::process_exec_tool_call;
+use codex_core::exec::process_exec_tool_call_with_config;
 use codex_core::exec_env::create_env;
 use codex_core::protocol::SandboxPolicy;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::NamedTempFile;
+use tempfile::TempDir;
 use tokio::sync::Notify;
 
+/// Default exec settings, with templated output kept out of the source tree.
+fn exec_config(output_dir: &TempDir) -> ExecConfig {
+    ExecConfig {
+        templated_output: output_dir.path().join("templated_output.txt"),
+        ..ExecConfig::default()
+    }
+}
+
 // At least on GitHub CI, the arm64 tests appear to need longer timeouts.
 
 #[cfg(not(target_arch = "aarch64"))]
@@ -44,13 +54,15 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         cwd: std::env::current_dir().expect("cwd should exist"),
         timeout_ms: Some(timeout_ms),
         env: create_env_from_core_vars(),
+        correlation_id: None,
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy_with_writable_roots(writable_roots);
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
     let ctrl_c = Arc::new(Notify::new());
-    let res = process_exec_tool_call(
+    let output_dir = TempDir::new().unwrap();
+    let res = process_exec_tool_call_with_config(
         params,
         SandboxType::LinuxSeccomp,
         ctrl_c,
@@ -58,6 +70,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         &codex_linux_sandbox_exe,
         "N/A",
         &[],
+        &exec_config(&output_dir),
     )
     .await
     .unwrap();
@@ -136,13 +149,15 @@ async fn assert_network_blocked(cmd: &[&str]) {
         // do not stall the suite.
         timeout_ms: Some(NETWORK_TIMEOUT_MS),
         env: create_env_from_core_vars(),
+        correlation_id: None,
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
     let ctrl_c = Arc::new(Notify::new());
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
     let codex_linux_sandbox_exe: Option<PathBuf> = Some(PathBuf::from(sandbox_program));
-    let result = process_exec_tool_call(
+    let output_dir = TempDir::new().unwrap();
+    let result = process_exec_tool_call_with_config(
         params,
         SandboxType::LinuxSeccomp,
         ctrl_c,
@@ -150,6 +165,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         &codex_linux_sandbox_exe,
         "N/A",
         &[],
+        &exec_config(&output_dir),
     )
     .await;
 
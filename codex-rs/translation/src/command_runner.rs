@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io;
+use std::io::Read;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// What a finished [`CommandRunner::run`] produced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Runs external programs on behalf of the translator, so tests can swap in
+/// [`MockRunner`] instead of spawning real binaries.
+pub trait CommandRunner: Debug + Send + Sync {
+    /// Run `program` with `args` and wait for it to exit. A child still
+    /// running after `timeout` is killed and reported as
+    /// [`io::ErrorKind::TimedOut`].
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<RunOutput>;
+}
+
+/// Spawns real processes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<RunOutput> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = child.stdout.take().map(read_pipe_in_background);
+        let stderr = child.stderr.take().map(read_pipe_in_background);
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            match child.try_wait()? {
+                Some(status) => break status,
+                None if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                None => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+                }
+            }
+        };
+
+        let collect = |handle: Option<std::thread::JoinHandle<Vec<u8>>>| {
+            handle.and_then(|h| h.join().ok()).unwrap_or_default()
+        };
+        Ok(RunOutput {
+            success: status.success(),
+            stdout: collect(stdout),
+            stderr: collect(stderr),
+        })
+    }
+}
+
+/// Drain `pipe` on a separate thread so a chatty child cannot block on a full
+/// pipe while we wait for it to exit.
+fn read_pipe_in_background<R: Read + Send + 'static>(mut pipe: R) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Answers from canned outputs and records every call. Programs without a
+/// canned output fail with [`io::ErrorKind::NotFound`].
+#[derive(Debug, Default)]
+pub struct MockRunner {
+    outputs: HashMap<String, RunOutput>,
+    command_outputs: HashMap<Vec<String>, RunOutput>,
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+impl MockRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer runs of `program` with `output`.
+    pub fn with_output(mut self, program: &str, output: RunOutput) -> Self {
+        self.outputs.insert(program.to_string(), output);
+        self
+    }
+
+    /// Answer runs of exactly `command` (program followed by its arguments)
+    /// with `output`, ahead of any output registered for the program alone.
+    pub fn with_command_output(mut self, command: &[&str], output: RunOutput) -> Self {
+        self.command_outputs
+            .insert(command.iter().map(|arg| arg.to_string()).collect(), output);
+        self
+    }
+
+    /// Every call so far, as program followed by its arguments.
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        match self.calls.lock() {
+            Ok(calls) => calls.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+}
+
+impl CommandRunner for MockRunner {
+    fn run(&self, program: &str, args: &[&str], _timeout: Duration) -> io::Result<RunOutput> {
+        let call: Vec<String> = std::iter::once(program)
+            .chain(args.iter().copied())
+            .map(str::to_string)
+            .collect();
+        let output = self
+            .command_outputs
+            .get(&call)
+            .or_else(|| self.outputs.get(program))
+            .cloned();
+        match self.calls.lock() {
+            Ok(mut calls) => calls.push(call),
+            Err(poisoned) => poisoned.into_inner().push(call),
+        }
+        output
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no output for {program}")))
+    }
+}
@@ -1,14 +1,26 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::process::Command;
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::command_runner::{CommandRunner, SystemRunner};
 
 const MAX_TRANSLATION_WARNINGS: usize = 3; // Define constant for max warnings
 
+/// How long [`CommandTranslator::probe_system`] waits for `--help` output.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Clone)]
 pub struct CommandTranslator {
     translations: HashMap<String, CommandTranslation>,
     max_warnings: usize,
+    /// Runs the `--help` probes of [`CommandTranslator::probe_system`].
+    runner: Arc<dyn CommandRunner>,
+    /// Outputs of [`CommandTranslator::probe_system_cached`], by command.
+    probe_cache: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,14 +29,77 @@ pub struct CommandTranslation {
     warnings: usize,
 }
 
+use serde::Deserialize;
 use serde::Serialize;
 use serde_json;
 
+/// How [`CommandTranslator::translate_command`] handled a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationStatus {
+    /// A mapping for the target OS was found and applied. Past the warning
+    /// cap the informational output says so as well.
+    Translated,
+    /// The translator knows nothing about the command.
+    NoMapping,
+    /// Translation is switched off globally; the command is passed through
+    /// unchanged.
+    Disabled,
+    /// The command is known, but has no mapping for the target OS, so the
+    /// original command is used.
+    FallbackToOriginal,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CommandTranslationResult {
     pub original_command: String,
     pub translated_command: Option<String>,
     pub informational_output: String,
+    pub status: TranslationStatus,
+    /// OS key whose mapping supplied `translated_command`; differs from the
+    /// requested OS when a fallback (see [`os_fallbacks`]) was used.
+    pub resolved_os: Option<String>,
+}
+
+impl CommandTranslationResult {
+    /// Result for a command that bypassed the translator entirely.
+    pub fn disabled(command: &str) -> Self {
+        Self {
+            original_command: command.to_string(),
+            translated_command: None,
+            informational_output: format!(
+                "Your command was: {command}\nTranslation disabled; command passed through unchanged."
+            ),
+            status: TranslationStatus::Disabled,
+            resolved_os: None,
+        }
+    }
+}
+
+/// Snapshot of what a [`CommandTranslator`] has learned, from
+/// [`CommandTranslator::export_state`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslatorState {
+    /// Keyed by source command.
+    pub translations: BTreeMap<String, TranslationEntry>,
+}
+
+/// One command in a [`TranslatorState`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationEntry {
+    /// Target command keyed by OS.
+    pub os_mappings: BTreeMap<String, String>,
+    /// How often the command has been translated, counted towards the
+    /// warning cap.
+    pub warnings: usize,
+}
+
+/// A full command vector after translation, together with the translation
+/// details for its program.
+#[derive(Debug, Clone)]
+pub struct TranslatedArgv {
+    pub argv: Vec<String>,
+    pub result: CommandTranslationResult,
 }
 
 impl CommandTranslator {
@@ -32,6 +107,8 @@ impl CommandTranslator {
         let mut translator = Self {
             translations: HashMap::new(),
             max_warnings: MAX_TRANSLATION_WARNINGS,
+            runner: Arc::new(SystemRunner),
+            probe_cache: HashMap::new(),
         };
         translator.load_translations_from_file();
         translator.load_translations_from_risk_csv();
@@ -39,9 +116,7 @@ impl CommandTranslator {
     }
 
     fn load_translations_from_file(&mut self) {
-        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
-        let scripts_dir = manifest_dir.parent().expect("crate should have parent").join("scripts");
-        let file_path = scripts_dir.join("command_translations.json");
+        let file_path = crate::scripts_dir().join("command_translations.json");
 
         if let Ok(contents) = fs::read_to_string(&file_path) {
             if let Ok(map) = serde_json::from_str::<HashMap<String, HashMap<String, String>>>(&contents) {
@@ -55,45 +130,57 @@ impl CommandTranslator {
     }
 
     fn load_translations_from_risk_csv(&mut self) {
-        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
-        let scripts_dir = manifest_dir.parent().expect("crate should have parent").join("scripts");
-        let file_path = scripts_dir.join("risk_csv.csv");
+        let file_path = crate::scripts_dir().join("risk_csv.csv");
 
         if let Ok(contents) = fs::read_to_string(&file_path) {
             for line in contents.lines().skip(1) {
-                let fields: Vec<&str> = line.split(',').collect();
-                if fields.len() < 13 {
-                    continue;
-                }
-                let binary = fields[1].trim();
-                let mut map = HashMap::new();
-                let macos = fields[8].trim();
-                if macos != "none" && !macos.is_empty() {
-                    map.insert("macos".to_string(), macos.to_string());
-                }
-                let linux = fields[9].trim();
-                if linux != "none" && !linux.is_empty() {
-                    map.insert("linux".to_string(), linux.to_string());
-                }
-                let win_cmd = fields[10].trim();
-                if win_cmd != "none" && !win_cmd.is_empty() {
-                    map.insert("windows".to_string(), win_cmd.to_string());
-                }
-                let win_ps = fields[11].trim();
-                if win_ps != "none" && !win_ps.is_empty() {
-                    map.insert("powershell".to_string(), win_ps.to_string());
-                }
-                let win_wsl = fields[12].trim();
-                if win_wsl != "none" && !win_wsl.is_empty() {
-                    map.insert("wsl".to_string(), win_wsl.to_string());
-                }
-                if !map.is_empty() {
-                    self.add_translation(binary, map);
-                }
+                self.add_translation_from_csv_row(line);
             }
         }
     }
 
+    /// Load the translation columns of a risk CSV at `path`, reading it one
+    /// line at a time instead of buffering the whole file.
+    pub fn load_translations_from_risk_csv_streaming(&mut self, path: &Path) -> std::io::Result<()> {
+        let file = fs::File::open(path)?;
+        for line in BufReader::new(file).lines().skip(1) {
+            self.add_translation_from_csv_row(&line?);
+        }
+        Ok(())
+    }
+
+    fn add_translation_from_csv_row(&mut self, line: &str) {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < 13 {
+            return;
+        }
+        let binary = fields[1].trim();
+        let mut map = HashMap::new();
+        let macos = fields[8].trim();
+        if macos != "none" && !macos.is_empty() {
+            map.insert("macos".to_string(), macos.to_string());
+        }
+        let linux = fields[9].trim();
+        if linux != "none" && !linux.is_empty() {
+            map.insert("linux".to_string(), linux.to_string());
+        }
+        let win_cmd = fields[10].trim();
+        if win_cmd != "none" && !win_cmd.is_empty() {
+            map.insert("windows".to_string(), win_cmd.to_string());
+        }
+        let win_ps = fields[11].trim();
+        if win_ps != "none" && !win_ps.is_empty() {
+            map.insert("powershell".to_string(), win_ps.to_string());
+        }
+        let win_wsl = fields[12].trim();
+        if win_wsl != "none" && !win_wsl.is_empty() {
+            map.insert("wsl".to_string(), win_wsl.to_string());
+        }
+        if !map.is_empty() {
+            self.add_translation(binary, map);
+        }
+    }
+
     fn insert_default_map(&mut self) {
         let mut mappings = HashMap::new();
         mappings.insert("linux".to_string(), "ls".to_string());
@@ -200,6 +287,42 @@ impl CommandTranslator {
         );
     }
 
+    /// Every translation together with its warning count.
+    pub fn export_state(&self) -> TranslatorState {
+        TranslatorState {
+            translations: self
+                .translations
+                .iter()
+                .map(|(command, translation)| {
+                    (
+                        command.clone(),
+                        TranslationEntry {
+                            os_mappings: translation.os_mappings.clone().into_iter().collect(),
+                            warnings: translation.warnings,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Replace all translations and warning counts with `state`.
+    pub fn import_state(&mut self, state: TranslatorState) {
+        self.translations = state
+            .translations
+            .into_iter()
+            .map(|(command, entry)| {
+                (
+                    command,
+                    CommandTranslation {
+                        os_mappings: entry.os_mappings.into_iter().collect(),
+                        warnings: entry.warnings,
+                    },
+                )
+            })
+            .collect();
+    }
+
     pub fn translate_command(
         &mut self,
         command: &str,
@@ -212,27 +335,47 @@ impl CommandTranslator {
 
         let informational_output;
         let translated_command;
+        let status;
+        let mut resolved_os = None;
 
         if let Some(translation) = self.translations.get_mut(command) {
-            translated_command = translation.os_mappings.get(os).cloned();
             translation.warnings += 1;
-
-            if translation.warnings > self.max_warnings {
-                informational_output = format!(
-                    "Automatic translation disabled for '{}'. Add the command to the database if successful.\n{}\n{}",
-                    command, threat_statement, weights_statement
-                );
+            let over_cap = translation.warnings > self.max_warnings;
+
+            let found = os_fallbacks(os).iter().find_map(|key| {
+                translation
+                    .os_mappings
+                    .get(*key)
+                    .map(|target| (key.to_string(), target.clone()))
+            });
+            translated_command = found.as_ref().map(|(_, target)| target.clone());
+            resolved_os = found.map(|(key, _)| key);
+            status = match translated_command {
+                Some(_) => TranslationStatus::Translated,
+                None => TranslationStatus::FallbackToOriginal,
+            };
+            let header = if over_cap {
+                format!(
+                    "Translation of '{}' exceeded its warning cap. Add the command to the database if successful.",
+                    command
+                )
             } else {
-                informational_output = format!(
-                    "Your command was: {}\n{}\n{}\nTranslated Command: {}",
-                    command,
-                    threat_statement,
-                    weights_statement,
-                    translated_command.clone().unwrap_or_else(|| "<none>".to_string())
-                );
-            }
+                format!("Your command was: {}", command)
+            };
+            informational_output = format!(
+                "{}\n{}\n{}\nTranslated Command: {}{}",
+                header,
+                threat_statement,
+                weights_statement,
+                translated_command.clone().unwrap_or_else(|| "<none>".to_string()),
+                match &resolved_os {
+                    Some(resolved) if resolved != os => format!("\nResolved OS: {resolved} (fallback for {os})"),
+                    _ => String::new(),
+                }
+            );
         } else {
             translated_command = None;
+            status = TranslationStatus::NoMapping;
             informational_output = format!(
                 "Your command was: {}\n{}\n{}\nNo translation available.",
                 command, threat_statement, weights_statement
@@ -243,28 +386,218 @@ impl CommandTranslator {
             original_command: command.to_string(),
             translated_command,
             informational_output,
+            status,
+            resolved_os,
         }
     }
 
-    pub fn probe_system(&self, command: &str) -> String {
-        match Command::new(command).arg("--help").output() {
-            Ok(output) => {
-                if output.status.success() {
-                    String::from_utf8_lossy(&output.stdout).to_string()
-                } else {
-                    format!("Error: {}", String::from_utf8_lossy(&output.stderr))
-                }
+    /// Translate a whole command vector for `os`.
+    ///
+    /// The longest known program prefix is looked up first, so a two-token
+    /// entry such as `net user` wins over `net`. A multi-token target is split
+    /// into separate argv entries and the remaining arguments follow it, with
+    /// path-like arguments converted to the target's separator. Relative
+    /// arguments are looked up in `cwd`, the directory the command will run
+    /// in. When no translation exists `argv` is returned unchanged.
+    pub fn translate_argv(
+        &mut self,
+        argv: &[String],
+        cwd: &Path,
+        os: &str,
+        threat_info: &str,
+        threat_weights: &[f64],
+    ) -> TranslatedArgv {
+        let program_len = match argv {
+            [first, second, ..] if self.translations.contains_key(&format!("{first} {second}")) => 2,
+            _ => 1.min(argv.len()),
+        };
+        let program = argv[..program_len].join(" ");
+        let result = self.translate_command(&program, os, threat_info, threat_weights);
+
+        let argv = match &result.translated_command {
+            Some(target) => {
+                let windows = matches!(os, "windows" | "powershell");
+                target
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .chain(argv[program_len..].iter().map(|arg| {
+                        if !looks_like_path(arg, cwd) {
+                            arg.clone()
+                        } else if windows {
+                            to_windows_path(arg)
+                        } else {
+                            to_unix_path(arg)
+                        }
+                    }))
+                    .collect()
             }
-            Err(e) => format!("Failed to execute '{} --help': {}", command, e),
+            None => argv.to_vec(),
+        };
+
+        TranslatedArgv { argv, result }
+    }
+
+    /// Map a translated command back to the source command that produces it
+    /// on `os`, for display to a user thinking in host terms.
+    ///
+    /// Several sources may map to the same target. In that case the first one
+    /// in lexical order is returned, preferring a source that differs from
+    /// `translated` over an identity mapping, and every candidate gets a
+    /// warning so the collision shows up in [`CommandTranslator::all_warnings`].
+    pub fn reverse_translate(&mut self, translated: &str, os: &str) -> Option<String> {
+        let mut sources: Vec<(&String, &mut CommandTranslation)> = self
+            .translations
+            .iter_mut()
+            .filter(|(_, t)| t.os_mappings.get(os).is_some_and(|target| target == translated))
+            .collect();
+        sources.sort_by_key(|(source, _)| (source.as_str() == translated, source.as_str()));
+        if sources.len() > 1 {
+            for (_, translation) in &mut sources {
+                translation.warnings += 1;
+            }
+        }
+        sources.first().map(|(source, _)| source.to_string())
+    }
+
+    /// Probe with `runner` instead of spawning real processes.
+    pub fn with_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.runner = runner;
+        self.probe_cache.clear();
+        self
+    }
+
+    pub fn probe_system(&self, command: &str) -> String {
+        probe_with_runner(self.runner.as_ref(), command, DEFAULT_PROBE_TIMEOUT)
+    }
+
+    /// Like [`CommandTranslator::probe_system`], but each command is only
+    /// probed once; later calls return the remembered output.
+    pub fn probe_system_cached(&mut self, command: &str) -> String {
+        if let Some(output) = self.probe_cache.get(command) {
+            return output.clone();
         }
+        let output = self.probe_system(command);
+        self.probe_cache.insert(command.to_string(), output.clone());
+        output
+    }
+
+    /// Run `<command> --help` and return its output, killing the child if it
+    /// has not exited within `timeout`.
+    ///
+    /// This does not touch translator state, so callers holding the global
+    /// translator should release the lock before probing.
+    pub fn probe_system_with_timeout(command: &str, timeout: Duration) -> String {
+        probe_with_runner(&SystemRunner, command, timeout)
+    }
+
+    /// Mappings for `os` whose target program is not installed here, as
+    /// `(command, target)` pairs sorted by command.
+    ///
+    /// Each target's program is looked up with `which` (`where` on Windows)
+    /// through the translator's runner.
+    pub fn verify_translations(&self, os: &str) -> Vec<(String, String)> {
+        let lookup = if cfg!(windows) { "where" } else { "which" };
+        let mut missing: Vec<(String, String)> = self
+            .translations
+            .iter()
+            .filter_map(|(command, translation)| {
+                let target = translation.os_mappings.get(os)?;
+                let program = target.split_whitespace().next()?;
+                let found = self
+                    .runner
+                    .run(lookup, &[program], DEFAULT_PROBE_TIMEOUT)
+                    .is_ok_and(|output| output.success);
+                (!found).then(|| (command.clone(), target.clone()))
+            })
+            .collect();
+        missing.sort();
+        missing
     }
 
     pub fn get_warnings(&self, command: &str) -> usize {
         self.translations.get(command).map_or(0, |t| t.warnings)
     }
+
+    /// Every command with a non-zero warning count, most warned first; ties
+    /// are ordered by command.
+    pub fn all_warnings(&self) -> Vec<(String, usize)> {
+        let mut warnings: Vec<(String, usize)> = self
+            .translations
+            .iter()
+            .filter(|(_, translation)| translation.warnings > 0)
+            .map(|(command, translation)| (command.clone(), translation.warnings))
+            .collect();
+        warnings.sort_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+        warnings
+    }
+}
+
+/// OS keys tried, in order, when translating for `os`. Shells without a
+/// column of their own borrow the one of their platform.
+pub fn os_fallbacks(os: &str) -> Vec<&str> {
+    match os {
+        "powershell" => vec!["powershell", "windows"],
+        "wsl" => vec!["wsl", "linux"],
+        _ => vec![os],
+    }
+}
+
+fn probe_with_runner(runner: &dyn CommandRunner, command: &str, timeout: Duration) -> String {
+    match runner.run(command, &["--help"], timeout) {
+        Ok(output) if output.success => String::from_utf8_lossy(&output.stdout).to_string(),
+        Ok(output) => format!("Error: {}", String::from_utf8_lossy(&output.stderr)),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            format!("Probe timed out after {:?}: '{} --help'", timeout, command)
+        }
+        Err(e) => format!("Failed to execute '{} --help': {}", command, e),
+    }
+}
+
+/// Whether `arg` should get its separators rewritten by
+/// [`CommandTranslator::translate_argv`]: it starts with a drive letter,
+/// `./`, `../` or `~/` (either separator), is a rooted path with several
+/// components (see [`is_rooted_path`]), or names an existing file relative to
+/// `cwd`. Regexes, sed scripts and URLs that merely contain a slash are left
+/// alone.
+fn looks_like_path(arg: &str, cwd: &Path) -> bool {
+    if arg.starts_with('-')
+        || !(arg.contains('/') || arg.contains('\\'))
+        || arg.contains("://")
+    {
+        return false;
+    }
+    let relative = ["./", "../", "~/", ".\\", "..\\", "~\\"]
+        .iter()
+        .any(|prefix| arg.starts_with(prefix));
+    let bytes = arg.as_bytes();
+    let drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'/' | b'\\');
+    relative || drive || is_rooted_path(arg) || cwd.join(to_unix_path(arg)).exists()
+}
+
+/// Whether `arg` reads as a rooted path such as `/usr/bin` or
+/// `\Windows\System32`: after the leading separator come at least two
+/// components, none of them empty or holding regex operators.
+///
+/// A single component (`\bfoo`) is more likely a regex escape, and a
+/// trailing separator (`/s/a/b/`) is how sed scripts and `/regex/` literals
+/// end, so those only count when they exist.
+fn is_rooted_path(arg: &str) -> bool {
+    let separators = ['/', '\\'];
+    if !arg.starts_with(separators) || arg.ends_with(separators) {
+        return false;
+    }
+    let rest = arg.trim_start_matches(separators);
+    rest.contains(separators)
+        && rest.split(separators).all(|component| {
+            !component.is_empty() && !component.contains(['+', '(', ')', '{', '}', '^', '|'])
+        })
 }
 
-/// Converts a path with backslashes to forward slashes.
 pub fn to_unix_path(path: &str) -> String {
     path.replace('\\', "/")
 }
@@ -274,12 +607,48 @@ pub fn to_windows_path(path: &str) -> String {
     path.replace('/', "\\")
 }
 
-/// Normalizes a path to the current operating system's format.
+/// Expand a leading `~` or `~user` segment of `path` to that user's home
+/// directory.
+///
+/// `~user` is looked up in the system's user database on unix and left
+/// unexpanded on other platforms, as is a user that does not exist. Anything
+/// else, including a `~` that does not start the path, is returned unchanged.
+pub fn expand_user(path: &str) -> String {
+    let Some(rest) = path.strip_prefix('~') else {
+        return path.to_string();
+    };
+    let split = rest.find(['/', '\\']).unwrap_or(rest.len());
+    let (user, tail) = rest.split_at(split);
+    let home = if user.is_empty() {
+        dirs::home_dir()
+    } else {
+        user_home_dir(user)
+    };
+    match home {
+        Some(home) => format!("{}{tail}", home.to_string_lossy()),
+        None => path.to_string(),
+    }
+}
+
+/// Home directory of the account named `user`.
+#[cfg(unix)]
+fn user_home_dir(user: &str) -> Option<PathBuf> {
+    nix::unistd::User::from_name(user).ok().flatten().map(|user| user.dir)
+}
+
+#[cfg(not(unix))]
+fn user_home_dir(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// Normalizes a path to the current operating system's format, expanding a
+/// leading `~` with [`expand_user`].
 pub fn normalize_path(path: &str) -> PathBuf {
+    let path = expand_user(path);
     let converted_path = if cfg!(windows) {
-        to_windows_path(path)
+        to_windows_path(&path)
     } else {
-        to_unix_path(path)
+        to_unix_path(&path)
     };
     Path::new(&converted_path).to_path_buf()
 }
@@ -289,7 +658,7 @@ pub fn normalize_command_paths(command: &str) -> String {
     command
         .split_whitespace()
         .map(|token| {
-            if token.contains('/') || token.contains('\\') {
+            if token.contains('/') || token.contains('\\') || token.starts_with('~') {
                 normalize_path(token).to_string_lossy().into_owned()
             } else {
                 token.to_string()
@@ -298,3 +667,50 @@ pub fn normalize_command_paths(command: &str) -> String {
         .collect::<Vec<_>>()
         .join(" ")
 }
+
+/// Expand environment variable references in `arg` using `env`.
+///
+/// The cmd target (`windows`) uses the `%VAR%` form; every other target,
+/// PowerShell included, uses `$VAR` and `${VAR}`. Text inside single quotes is a literal and
+/// is left alone, as are references to variables missing from `env`.
+pub fn expand_env_vars(arg: &str, env: &HashMap<String, String>, os: &str) -> String {
+    let windows = os == "windows";
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    let mut in_quotes = false;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes
+            && ((c == '%' && windows) || (c == '$' && !windows))
+            && let Some((name, len)) = env_reference(rest, windows)
+            && let Some(value) = env.get(name)
+        {
+            out.push_str(value);
+            rest = &rest[len..];
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Parse a variable reference at the start of `s`, returning the variable
+/// name and the length of the whole reference.
+fn env_reference(s: &str, windows: bool) -> Option<(&str, usize)> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    if windows {
+        let end = s[1..].find('%')? + 1;
+        let name = &s[1..end];
+        (!name.is_empty() && name.chars().all(is_name)).then_some((name, end + 1))
+    } else if let Some(braced) = s.strip_prefix("${") {
+        let end = braced.find('}')?;
+        let name = &braced[..end];
+        (!name.is_empty() && name.chars().all(is_name)).then_some((name, end + 3))
+    } else {
+        let len = s[1..].find(|c: char| !is_name(c)).unwrap_or(s.len() - 1);
+        (len > 0).then_some((&s[1..len + 1], len + 1))
+    }
+}
@@ -1,5 +1,18 @@
+pub mod command_runner;
 pub mod command_translation;
+mod scripts;
+pub use command_runner::CommandRunner;
+pub use command_runner::MockRunner;
+pub use command_runner::RunOutput;
+pub use command_runner::SystemRunner;
 pub use command_translation::CommandTranslator;
+pub use command_translation::TranslatedArgv;
+pub use command_translation::TranslationEntry;
+pub use command_translation::TranslationStatus;
+pub use command_translation::TranslatorState;
+pub use scripts::CODEX_SCRIPTS_DIR_ENV_VAR;
+pub use scripts::resolve_scripts_dir;
+pub use scripts::scripts_dir;
 use once_cell::sync::OnceCell;
 use std::sync::Mutex;
 
@@ -0,0 +1,48 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Environment variable that overrides the location of the `scripts` directory.
+pub const CODEX_SCRIPTS_DIR_ENV_VAR: &str = "CODEX_SCRIPTS_DIR";
+
+/// Locate the `scripts` directory holding docs, translations, the risk CSV
+/// and helper scripts.
+///
+/// `CODEX_SCRIPTS_DIR` wins when set. Otherwise `scripts` under the per-user
+/// Codex home (`$CODEX_HOME`, or `~/.codex`) is used if it exists, so an
+/// installed binary can find its data. The directory next to the source tree
+/// this crate was compiled from is the last resort.
+pub fn scripts_dir() -> PathBuf {
+    resolve_scripts_dir(
+        std::env::var_os(CODEX_SCRIPTS_DIR_ENV_VAR),
+        std::env::var_os("CODEX_HOME"),
+    )
+}
+
+/// Like [`scripts_dir`], with the `CODEX_SCRIPTS_DIR` and `CODEX_HOME` values
+/// passed in rather than read from the environment. Empty values count as
+/// unset.
+pub fn resolve_scripts_dir(scripts_dir: Option<OsString>, codex_home: Option<OsString>) -> PathBuf {
+    if let Some(dir) = scripts_dir.filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = user_scripts_dir(codex_home).filter(|dir| dir.is_dir()) {
+        return dir;
+    }
+    compiled_scripts_dir()
+}
+
+fn user_scripts_dir(codex_home: Option<OsString>) -> Option<PathBuf> {
+    let codex_home = match codex_home.filter(|home| !home.is_empty()) {
+        Some(home) => PathBuf::from(home),
+        None => dirs::home_dir()?.join(".codex"),
+    };
+    Some(codex_home.join("scripts"))
+}
+
+fn compiled_scripts_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("crate should have parent")
+        .join("scripts")
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use translation::CommandTranslator;
+
+#[test]
+fn all_warnings_lists_warned_commands_most_warned_first() {
+    let mut translator = CommandTranslator::new();
+    for command in ["lsd", "bat", "unused"] {
+        translator.add_translation(
+            command,
+            HashMap::from([("plan9".to_string(), command.to_string())]),
+        );
+    }
+    assert!(translator.all_warnings().is_empty());
+
+    translator.translate_command("bat", "plan9", "N/A", &[]);
+    for _ in 0..3 {
+        translator.translate_command("lsd", "plan9", "N/A", &[]);
+    }
+
+    assert_eq!(
+        translator.all_warnings(),
+        vec![("lsd".to_string(), 3), ("bat".to_string(), 1)]
+    );
+    assert_eq!(translator.get_warnings("unused"), 0);
+
+    // Equal counts are ordered by command.
+    for _ in 0..2 {
+        translator.translate_command("bat", "plan9", "N/A", &[]);
+    }
+    assert_eq!(
+        translator.all_warnings(),
+        vec![("bat".to_string(), 3), ("lsd".to_string(), 3)]
+    );
+}
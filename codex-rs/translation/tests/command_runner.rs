@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use translation::CommandTranslator;
+use translation::MockRunner;
+use translation::RunOutput;
+
+#[test]
+fn cached_probe_runs_the_mock_once() {
+    let runner = Arc::new(MockRunner::new().with_output(
+        "frobnicate",
+        RunOutput {
+            success: true,
+            stdout: b"usage: frobnicate [-x]\n".to_vec(),
+            stderr: Vec::new(),
+        },
+    ));
+    let mut translator = CommandTranslator::new().with_runner(runner.clone());
+
+    assert_eq!(translator.probe_system_cached("frobnicate"), "usage: frobnicate [-x]\n");
+    assert_eq!(translator.probe_system_cached("frobnicate"), "usage: frobnicate [-x]\n");
+    assert_eq!(runner.calls(), vec![vec!["frobnicate".to_string(), "--help".to_string()]]);
+}
+
+#[test]
+fn mock_failures_are_reported_like_real_ones() {
+    let runner = Arc::new(MockRunner::new().with_output(
+        "broken",
+        RunOutput {
+            success: false,
+            stdout: Vec::new(),
+            stderr: b"no help here".to_vec(),
+        },
+    ));
+    let translator = CommandTranslator::new().with_runner(runner.clone());
+
+    assert_eq!(translator.probe_system("broken"), "Error: no help here");
+    assert!(translator.probe_system("missing").starts_with("Failed to execute 'missing --help'"));
+    assert_eq!(runner.calls().len(), 2);
+}
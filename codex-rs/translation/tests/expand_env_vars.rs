@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use translation::command_translation::expand_env_vars;
+
+fn env() -> HashMap<String, String> {
+    HashMap::from([
+        ("HOME".to_string(), "/home/codex".to_string()),
+        ("TEMP".to_string(), "C:\\Temp".to_string()),
+    ])
+}
+
+#[test]
+fn expands_home_on_unix() {
+    let env = env();
+    assert_eq!(expand_env_vars("$HOME/notes", &env, "linux"), "/home/codex/notes");
+    assert_eq!(expand_env_vars("${HOME}x", &env, "macos"), "/home/codexx");
+    assert_eq!(expand_env_vars("'$HOME'", &env, "linux"), "'$HOME'");
+    assert_eq!(expand_env_vars("$MISSING", &env, "linux"), "$MISSING");
+    assert_eq!(expand_env_vars("%TEMP%", &env, "linux"), "%TEMP%");
+}
+
+#[test]
+fn expands_temp_on_windows() {
+    let env = env();
+    assert_eq!(expand_env_vars("%TEMP%\\out.txt", &env, "windows"), "C:\\Temp\\out.txt");
+    assert_eq!(expand_env_vars("'%TEMP%'", &env, "windows"), "'%TEMP%'");
+    assert_eq!(expand_env_vars("100%", &env, "windows"), "100%");
+    assert_eq!(expand_env_vars("$HOME", &env, "windows"), "$HOME");
+    assert_eq!(expand_env_vars("%TEMP%", &env, "powershell"), "%TEMP%");
+}
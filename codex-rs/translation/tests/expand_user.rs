@@ -0,0 +1,40 @@
+use translation::command_translation::{expand_user, normalize_command_paths, normalize_path};
+
+fn home() -> String {
+    dirs::home_dir().expect("home dir").to_string_lossy().into_owned()
+}
+
+#[test]
+fn leading_tilde_expands_to_home() {
+    assert_eq!(expand_user("~"), home());
+    assert_eq!(expand_user("~/foo"), format!("{}/foo", home()));
+    assert_eq!(expand_user("~\\foo"), format!("{}\\foo", home()));
+    assert_eq!(normalize_path("~/foo"), normalize_path(&format!("{}/foo", home())));
+    assert_eq!(
+        normalize_command_paths("cat ~/notes.txt"),
+        format!("cat {}", normalize_path(&format!("{}/notes.txt", home())).display())
+    );
+}
+
+#[test]
+fn tilde_elsewhere_is_left_alone() {
+    assert_eq!(expand_user("foo~bar"), "foo~bar");
+    assert_eq!(expand_user("a/~/b"), "a/~/b");
+    assert_eq!(normalize_command_paths("echo foo~bar"), "echo foo~bar");
+}
+
+#[test]
+fn unknown_user_is_left_alone() {
+    assert_eq!(expand_user("~no-such-user-xyz/foo"), "~no-such-user-xyz/foo");
+}
+
+#[cfg(unix)]
+#[test]
+fn tilde_user_expands_to_that_users_home() {
+    let root = nix::unistd::User::from_name("root")
+        .expect("look up root")
+        .expect("root exists");
+    let root_home = root.dir.to_string_lossy().into_owned();
+    assert_eq!(expand_user("~root"), root_home);
+    assert_eq!(expand_user("~root/x"), format!("{root_home}/x"));
+}
@@ -0,0 +1,30 @@
+#![cfg(unix)]
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::time::{Duration, Instant};
+
+use tempfile::TempDir;
+use translation::CommandTranslator;
+
+#[test]
+fn hanging_probe_is_killed_after_timeout() -> std::io::Result<()> {
+    let dir = TempDir::new()?;
+    let script = dir.path().join("hang");
+    fs::write(&script, "#!/bin/sh\nsleep 30\n")?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let start = Instant::now();
+    let output = CommandTranslator::probe_system_with_timeout(
+        &script.to_string_lossy(),
+        Duration::from_millis(200),
+    );
+    assert!(output.starts_with("Probe timed out"), "unexpected output: {output}");
+    assert!(start.elapsed() < Duration::from_secs(10));
+    Ok(())
+}
+
+#[test]
+fn quick_probe_returns_help_output() {
+    let output = CommandTranslator::probe_system_with_timeout("sh", Duration::from_secs(5));
+    assert!(!output.starts_with("Probe timed out"), "unexpected output: {output}");
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use translation::CommandTranslator;
+use translation::TranslationStatus;
+
+#[test]
+fn fallback_key_is_reported() {
+    let mut translator = CommandTranslator::new();
+    translator.add_translation(
+        "resolved-os-tool",
+        HashMap::from([("windows".to_string(), "dir".to_string())]),
+    );
+
+    let result = translator.translate_command("resolved-os-tool", "powershell", "N/A", &[]);
+    assert_eq!(result.status, TranslationStatus::Translated);
+    assert_eq!(result.translated_command.as_deref(), Some("dir"));
+    assert_eq!(result.resolved_os.as_deref(), Some("windows"));
+    assert!(result.informational_output.contains("Resolved OS: windows (fallback for powershell)"));
+
+    let result = translator.translate_command("resolved-os-tool", "windows", "N/A", &[]);
+    assert_eq!(result.resolved_os.as_deref(), Some("windows"));
+    assert!(!result.informational_output.contains("Resolved OS"));
+
+    let result = translator.translate_command("resolved-os-tool", "linux", "N/A", &[]);
+    assert_eq!(result.status, TranslationStatus::FallbackToOriginal);
+    assert_eq!(result.resolved_os, None);
+}
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use translation::CommandTranslator;
+
+#[test]
+fn dir_on_windows_reverses_to_ls() {
+    let mut translator = CommandTranslator::new();
+    assert_eq!(translator.reverse_translate("dir", "windows"), Some("ls".to_string()));
+    assert_eq!(translator.reverse_translate("no-such-target", "windows"), None);
+}
+
+#[test]
+fn ambiguous_targets_prefer_a_real_source() {
+    let mut translator = CommandTranslator::new();
+    for source in ["zz-list", "list", "aa-list"] {
+        translator.add_translation(source, HashMap::from([("plan9".to_string(), "list".to_string())]));
+    }
+    assert_eq!(translator.reverse_translate("list", "plan9"), Some("aa-list".to_string()));
+}
+
+#[test]
+fn ambiguous_targets_are_flagged_in_warnings() {
+    let mut translator = CommandTranslator::new();
+    for source in ["zz-list", "aa-list"] {
+        translator.add_translation(source, HashMap::from([("plan9".to_string(), "list".to_string())]));
+    }
+    translator.add_translation("cat", HashMap::from([("plan9".to_string(), "print".to_string())]));
+
+    assert_eq!(translator.reverse_translate("print", "plan9"), Some("cat".to_string()));
+    assert_eq!(translator.get_warnings("cat"), 0);
+
+    assert_eq!(translator.reverse_translate("list", "plan9"), Some("aa-list".to_string()));
+    let warnings = translator.all_warnings();
+    assert!(warnings.contains(&("aa-list".to_string(), 1)), "{warnings:?}");
+    assert!(warnings.contains(&("zz-list".to_string(), 1)), "{warnings:?}");
+}
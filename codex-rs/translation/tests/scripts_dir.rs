@@ -0,0 +1,43 @@
+use std::ffi::OsString;
+
+use tempfile::TempDir;
+use translation::resolve_scripts_dir;
+
+#[test]
+fn scripts_dir_override_wins() {
+    let dir = TempDir::new().expect("tempdir");
+    let home = TempDir::new().expect("tempdir");
+    std::fs::create_dir(home.path().join("scripts")).expect("create scripts");
+
+    let resolved = resolve_scripts_dir(
+        Some(dir.path().as_os_str().to_owned()),
+        Some(home.path().as_os_str().to_owned()),
+    );
+    assert_eq!(resolved, dir.path());
+}
+
+#[test]
+fn codex_home_scripts_used_when_present() {
+    let home = TempDir::new().expect("tempdir");
+    std::fs::create_dir(home.path().join("scripts")).expect("create scripts");
+
+    let resolved = resolve_scripts_dir(
+        Some(OsString::new()),
+        Some(home.path().as_os_str().to_owned()),
+    );
+    assert_eq!(resolved, home.path().join("scripts"));
+}
+
+#[test]
+fn missing_codex_home_scripts_fall_back_to_source_tree() {
+    let home = TempDir::new().expect("tempdir");
+
+    let resolved = resolve_scripts_dir(None, Some(home.path().as_os_str().to_owned()));
+    assert_eq!(
+        resolved,
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .expect("parent")
+            .join("scripts")
+    );
+}
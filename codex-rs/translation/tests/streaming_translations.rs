@@ -0,0 +1,21 @@
+use std::fs;
+
+use tempfile::TempDir;
+use translation::CommandTranslator;
+
+#[test]
+fn streamed_risk_csv_adds_translations() -> std::io::Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("risk.csv");
+    fs::write(
+        &path,
+        "Environment,Binary,Flag,a,b,c,d,e,CmdMacOS,CmdLinux,CmdWinCmd,CmdWinPs,CmdWinWsl\n\
+         linux,frob,-x,1,1,1,1,1,frob,frob,frobw,Invoke-Frob,frob\n",
+    )?;
+
+    let mut translator = CommandTranslator::new();
+    translator.load_translations_from_risk_csv_streaming(&path)?;
+    let result = translator.translate_command("frob", "powershell", "N/A", &[]);
+    assert_eq!(result.translated_command.as_deref(), Some("Invoke-Frob"));
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use tempfile::TempDir;
+use translation::CommandTranslator;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn ls_translates_to_windows_argv() {
+    let mut translator = CommandTranslator::new();
+    let cwd = Path::new(".");
+    let translated = translator.translate_argv(&argv(&["ls", "-la"]), cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["dir", "-la"]));
+    assert_eq!(translated.result.original_command, "ls");
+    assert_eq!(translated.result.translated_command.as_deref(), Some("dir"));
+}
+
+#[test]
+fn path_arguments_follow_the_target_separator() {
+    let mut translator = CommandTranslator::new();
+    let cwd = Path::new(".");
+    // `src/lib.rs` exists relative to the crate directory the test runs in.
+    let translated = translator.translate_argv(&argv(&["cat", "src/lib.rs"]), cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["type", "src\\lib.rs"]));
+
+    let command = argv(&["cat", "./a/b", "C:/x/y", "missing/file"]);
+    let translated = translator.translate_argv(&command, cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["type", ".\\a\\b", "C:\\x\\y", "missing/file"]));
+}
+
+#[test]
+fn existing_files_are_looked_up_in_the_given_cwd() {
+    let mut translator = CommandTranslator::new();
+    let dir = TempDir::new().expect("tempdir");
+    std::fs::create_dir(dir.path().join("notes")).expect("create dir");
+    std::fs::write(dir.path().join("notes/todo.txt"), "").expect("write file");
+
+    let command = argv(&["cat", "notes/todo.txt"]);
+    let translated = translator.translate_argv(&command, dir.path(), "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["type", "notes\\todo.txt"]));
+
+    let translated = translator.translate_argv(&command, Path::new("."), "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["type", "notes/todo.txt"]));
+}
+
+#[test]
+fn rooted_arguments_need_more_than_a_leading_separator() {
+    let mut translator = grep_translator();
+    let cwd = Path::new(".");
+    let command = argv(&["grep", "\\bfoo", "/s/a/b/"]);
+    let translated = translator.translate_argv(&command, cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["findstr", "\\bfoo", "/s/a/b/"]));
+
+    let command = argv(&["grep", "\\Windows\\System32", "/usr/share/dict"]);
+    let translated = translator.translate_argv(&command, cwd, "linux", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["grep", "/Windows/System32", "/usr/share/dict"]));
+}
+
+fn grep_translator() -> CommandTranslator {
+    let mut translator = CommandTranslator::new();
+    translator.add_translation(
+        "grep",
+        HashMap::from([
+            ("windows".to_string(), "findstr".to_string()),
+            ("linux".to_string(), "grep".to_string()),
+        ]),
+    );
+    translator
+}
+
+#[test]
+fn regex_arguments_are_not_rewritten() {
+    let mut translator = grep_translator();
+    let cwd = Path::new(".");
+    let command = argv(&["grep", "a\\.b", "s/a/b/"]);
+    let translated = translator.translate_argv(&command, cwd, "linux", "N/A", &[]);
+    assert_eq!(translated.argv, command);
+
+    let translated = translator.translate_argv(&argv(&["grep", "a\\.b"]), cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["findstr", "a\\.b"]));
+}
+
+#[test]
+fn url_arguments_are_not_rewritten() {
+    let mut translator = grep_translator();
+    let cwd = Path::new(".");
+    let url = "https://example.com/path/to/page";
+    let translated = translator.translate_argv(&argv(&["grep", url]), cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, argv(&["findstr", url]));
+}
+
+#[test]
+fn two_token_programs_and_unknown_commands() {
+    let mut translator = CommandTranslator::new();
+    let cwd = Path::new(".");
+    let translated = translator.translate_argv(&argv(&["net", "user", "bob"]), cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.result.original_command, "net user");
+    assert_eq!(translated.argv, argv(&["net", "user", "bob"]));
+
+    let unknown = argv(&["frobnicate", "a/b"]);
+    let translated = translator.translate_argv(&unknown, cwd, "windows", "N/A", &[]);
+    assert_eq!(translated.argv, unknown);
+    assert!(translated.result.translated_command.is_none());
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use translation::CommandTranslator;
+use translation::TranslationStatus;
+use translation::command_translation::CommandTranslationResult;
+
+#[test]
+fn known_command_is_translated() {
+    let mut translator = CommandTranslator::new();
+    let result = translator.translate_command("ls", "windows", "N/A", &[]);
+    assert_eq!(result.status, TranslationStatus::Translated);
+    assert_eq!(result.translated_command.as_deref(), Some("dir"));
+}
+
+#[test]
+fn unknown_command_has_no_mapping() {
+    let mut translator = CommandTranslator::new();
+    let result = translator.translate_command("frobnicate", "windows", "N/A", &[]);
+    assert_eq!(result.status, TranslationStatus::NoMapping);
+    assert!(result.translated_command.is_none());
+}
+
+#[test]
+fn missing_os_falls_back_to_original() {
+    let mut translator = CommandTranslator::new();
+    translator.add_translation(
+        "frob",
+        HashMap::from([("linux".to_string(), "frob".to_string())]),
+    );
+    let result = translator.translate_command("frob", "windows", "N/A", &[]);
+    assert_eq!(result.status, TranslationStatus::FallbackToOriginal);
+    assert!(result.translated_command.is_none());
+}
+
+#[test]
+fn exceeding_the_warning_cap_still_translates() {
+    let mut translator = CommandTranslator::new();
+    let mut statuses = Vec::new();
+    for _ in 0..4 {
+        statuses.push(translator.translate_command("ls", "windows", "N/A", &[]).status);
+    }
+    assert_eq!(
+        statuses,
+        vec![
+            TranslationStatus::Translated,
+            TranslationStatus::Translated,
+            TranslationStatus::Translated,
+            TranslationStatus::Translated,
+        ]
+    );
+
+    let result = translator.translate_command("ls", "windows", "N/A", &[]);
+    assert_eq!(result.translated_command.as_deref(), Some("dir"));
+    assert!(result.informational_output.contains("exceeded its warning cap"));
+    assert_eq!(translator.get_warnings("ls"), 5);
+
+    let translated = translator.translate_argv(&["ls".to_string()], Path::new("."), "windows", "N/A", &[]);
+    assert_eq!(translated.argv, vec!["dir".to_string()]);
+    assert_eq!(translated.result.status, TranslationStatus::Translated);
+    assert!(translated.result.informational_output.contains("exceeded its warning cap"));
+}
+
+#[test]
+fn globally_disabled_translation_is_marked_disabled() {
+    let result = CommandTranslationResult::disabled("ls");
+    assert_eq!(result.status, TranslationStatus::Disabled);
+}
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use translation::CommandTranslator;
+use translation::TranslationStatus;
+use translation::TranslatorState;
+
+#[test]
+fn exported_state_restores_translations_and_warnings() {
+    let mut translator = CommandTranslator::new();
+    translator.add_translation(
+        "lsd",
+        HashMap::from([("plan9".to_string(), "ls".to_string())]),
+    );
+    translator.translate_command("lsd", "plan9", "N/A", &[]);
+    let state = translator.export_state();
+    assert_eq!(state.translations["lsd"].warnings, 1);
+    assert_eq!(state.translations["lsd"].os_mappings["plan9"], "ls");
+
+    // Survives a serde round trip.
+    let json = serde_json::to_string(&state).expect("serialize state");
+    let state: TranslatorState = serde_json::from_str(&json).expect("deserialize state");
+
+    translator.add_translation(
+        "extra",
+        HashMap::from([("plan9".to_string(), "x".to_string())]),
+    );
+    for _ in 0..5 {
+        translator.translate_command("lsd", "plan9", "N/A", &[]);
+    }
+    assert!(
+        translator
+            .translate_command("lsd", "plan9", "N/A", &[])
+            .informational_output
+            .contains("exceeded its warning cap")
+    );
+    assert_ne!(translator.export_state(), state);
+
+    translator.import_state(state.clone());
+    assert_eq!(translator.export_state(), state);
+    assert!(!translator.export_state().translations.contains_key("extra"));
+    // The warning count is back at one, so lsd is under the cap again.
+    let result = translator.translate_command("lsd", "plan9", "N/A", &[]);
+    assert_eq!(result.status, TranslationStatus::Translated);
+    assert!(!result.informational_output.contains("exceeded its warning cap"));
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use translation::CommandTranslator;
+use translation::MockRunner;
+use translation::RunOutput;
+
+#[test]
+fn missing_targets_are_reported() {
+    let lookup = if cfg!(windows) { "where" } else { "which" };
+    let found = RunOutput {
+        success: true,
+        stdout: b"/usr/bin/present-tool\n".to_vec(),
+        stderr: Vec::new(),
+    };
+    // Every other lookup fails, as `which` does for unknown programs.
+    let runner = Arc::new(
+        MockRunner::new()
+            .with_output(lookup, RunOutput::default())
+            .with_command_output(&[lookup, "present-tool"], found),
+    );
+    let mut translator = CommandTranslator::new().with_runner(runner.clone());
+    translator.add_translation(
+        "verify-present",
+        HashMap::from([("plan9".to_string(), "present-tool -x".to_string())]),
+    );
+    translator.add_translation(
+        "verify-missing",
+        HashMap::from([("plan9".to_string(), "codex-no-such-binary".to_string())]),
+    );
+
+    assert_eq!(
+        translator.verify_translations("plan9"),
+        vec![("verify-missing".to_string(), "codex-no-such-binary".to_string())]
+    );
+    assert!(runner.calls().contains(&vec![lookup.to_string(), "present-tool".to_string()]));
+}